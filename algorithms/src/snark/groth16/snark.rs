@@ -15,6 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use super::{
+    batch_verify_proofs,
     create_random_proof,
     generate_random_parameters,
     prepare_verifying_key,
@@ -31,7 +32,7 @@ use snarkos_models::{
     gadgets::r1cs::ConstraintSynthesizer,
 };
 
-use rand::Rng;
+use rand::{thread_rng, Rng};
 use std::marker::PhantomData;
 
 /// Note: V should serialize its contents to `Vec<E::Fr>` in the same order as
@@ -91,4 +92,27 @@ impl<E: PairingEngine, C: ConstraintSynthesizer<E::Fr>, V: ToConstraintField<E::
         end_timer!(verify_time);
         Ok(result)
     }
+
+    fn batch_verify(
+        vk: &Self::PreparedVerificationParameters,
+        inputs_and_proofs: &[(&Self::VerifierInput, &Self::Proof)],
+    ) -> Result<bool, SNARKError> {
+        let verify_time = start_timer!(|| "{Groth 2016}::BatchVerify");
+        let conversion_time = start_timer!(|| "Convert inputs to E::Fr");
+        let inputs = inputs_and_proofs
+            .iter()
+            .map(|(input, _)| input.to_field_elements())
+            .collect::<Result<Vec<_>, _>>()?;
+        end_timer!(conversion_time);
+        let proofs_and_inputs: Vec<_> = inputs_and_proofs
+            .iter()
+            .zip(&inputs)
+            .map(|((_, proof), input)| (*proof, &input[..]))
+            .collect();
+        let verification = start_timer!(|| format!("Batch verify {} proofs", proofs_and_inputs.len()));
+        let result = batch_verify_proofs(&vk, &proofs_and_inputs, &mut thread_rng())?;
+        end_timer!(verification);
+        end_timer!(verify_time);
+        Ok(result)
+    }
 }
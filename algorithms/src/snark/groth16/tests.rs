@@ -53,7 +53,13 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MySillyCircuit<C
 
 mod bls12_377 {
     use super::*;
-    use crate::snark::groth16::{create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof};
+    use crate::snark::groth16::{
+        batch_verify_proofs,
+        create_random_proof,
+        generate_random_parameters,
+        prepare_verifying_key,
+        verify_proof,
+    };
     use core::ops::MulAssign;
     use snarkos_curves::bls12_377::{Bls12_377, Fr};
     use snarkos_utilities::rand::{test_rng, UniformRand};
@@ -78,6 +84,46 @@ mod bls12_377 {
             assert!(!verify_proof(&pvk, &proof, &[a]).unwrap());
         }
     }
+
+    #[test]
+    fn batch_prove_and_verify() {
+        let rng = &mut test_rng();
+
+        let params = generate_random_parameters::<Bls12_377, _, _>(MySillyCircuit { a: None, b: None }, rng).unwrap();
+
+        let pvk = prepare_verifying_key::<Bls12_377>(&params.vk);
+
+        let mut proofs = vec![];
+        let mut inputs = vec![];
+        for _ in 0..10 {
+            let a = Fr::rand(rng);
+            let b = Fr::rand(rng);
+            let mut c = a;
+            c.mul_assign(&b);
+
+            proofs.push(create_random_proof(MySillyCircuit { a: Some(a), b: Some(b) }, &params, rng).unwrap());
+            inputs.push(vec![c]);
+        }
+
+        let batch: Vec<_> = proofs
+            .iter()
+            .zip(&inputs)
+            .map(|(proof, input)| (proof, &input[..]))
+            .collect();
+
+        assert!(batch_verify_proofs(&pvk, &batch, rng).unwrap());
+        assert!(batch_verify_proofs(&pvk, &[], rng).unwrap());
+
+        // A single invalid proof fails the whole batch.
+        let wrong_input = [Fr::rand(rng)];
+        let mut invalid_batch = batch.clone();
+        invalid_batch[3].1 = &wrong_input;
+        assert!(!batch_verify_proofs(&pvk, &invalid_batch, rng).unwrap());
+
+        // Inputs of the wrong length are rejected.
+        invalid_batch[3].1 = &[];
+        assert!(batch_verify_proofs(&pvk, &invalid_batch, rng).is_err());
+    }
 }
 
 mod bw6_761 {
@@ -16,9 +16,12 @@
 
 use super::{PreparedVerifyingKey, Proof, VerifyingKey};
 use snarkos_errors::gadgets::SynthesisError;
-use snarkos_models::curves::{AffineCurve, PairingCurve, PairingEngine, PrimeField, ProjectiveCurve};
+use snarkos_models::curves::{AffineCurve, Field, One, PairingCurve, PairingEngine, PrimeField, ProjectiveCurve, Zero};
+use snarkos_utilities::rand::UniformRand;
 
 use core::ops::{AddAssign, Neg};
+use rand::Rng;
+use rayon::prelude::*;
 
 pub fn prepare_verifying_key<E: PairingEngine>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
     PreparedVerifyingKey {
@@ -57,3 +60,71 @@ pub fn verify_proof<E: PairingEngine>(
 
     Ok(test == pvk.alpha_g1_beta_g2)
 }
+
+/// Verifies several proofs against the same verifying key with a single final exponentiation.
+///
+/// Each proof is scaled by a random challenge `r_i`, and the random linear combination
+/// `prod e(r_i * A_i, B_i) * e(sum r_i * IC_i, -gamma) * e(sum r_i * C_i, -delta) == e(alpha, beta)^(sum r_i)`
+/// is checked in place of the pairing equation of every proof. A batch containing an invalid proof
+/// passes with negligible probability, as long as the challenges are unknown to the prover.
+pub fn batch_verify_proofs<E: PairingEngine, R: Rng>(
+    pvk: &PreparedVerifyingKey<E>,
+    proofs_and_inputs: &[(&Proof<E>, &[E::Fr])],
+    rng: &mut R,
+) -> Result<bool, SynthesisError> {
+    if proofs_and_inputs
+        .iter()
+        .any(|(_, public_inputs)| (public_inputs.len() + 1) != pvk.gamma_abc_g1.len())
+    {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    if proofs_and_inputs.is_empty() {
+        return Ok(true);
+    }
+
+    let challenges: Vec<E::Fr> = proofs_and_inputs.iter().map(|_| E::Fr::rand(rng)).collect();
+
+    // Scale every proof by its challenge and run its Miller loop, in parallel.
+    let scaled: Vec<(E::Fqk, E::G1Projective, E::G1Projective)> = proofs_and_inputs
+        .par_iter()
+        .zip(challenges.par_iter())
+        .map(|((proof, public_inputs), challenge)| {
+            let challenge = challenge.into_repr();
+
+            let mut g_ic = pvk.gamma_abc_g1[0].into_projective();
+            for (i, b) in public_inputs.iter().zip(pvk.gamma_abc_g1.iter().skip(1)) {
+                g_ic.add_assign(&b.mul(i.into_repr()));
+            }
+            g_ic.mul_assign(challenge);
+
+            let a = proof.a.mul(challenge).into_affine().prepare();
+            let qap = E::miller_loop([(&a, &proof.b.prepare())].iter());
+
+            (qap, g_ic, proof.c.mul(challenge))
+        })
+        .collect();
+
+    let mut qap = E::Fqk::one();
+    let mut g_ic = E::G1Projective::zero();
+    let mut c = E::G1Projective::zero();
+    for (scaled_qap, scaled_g_ic, scaled_c) in &scaled {
+        qap *= scaled_qap;
+        g_ic.add_assign(scaled_g_ic);
+        c.add_assign(scaled_c);
+    }
+
+    qap *= &E::miller_loop(
+        [
+            (&g_ic.into_affine().prepare(), &pvk.gamma_g2_neg_pc),
+            (&c.into_affine().prepare(), &pvk.delta_g2_neg_pc),
+        ]
+        .iter(),
+    );
+
+    let test = E::final_exponentiation(&qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+    let challenge_sum = challenges.iter().fold(E::Fr::zero(), |sum, challenge| sum + challenge);
+
+    Ok(test == pvk.alpha_g1_beta_g2.pow(challenge_sum.into_repr()))
+}
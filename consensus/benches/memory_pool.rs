@@ -0,0 +1,87 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::memory_pool::{Entry, MemoryPool};
+use snarkos_dpc::base_dpc::instantiated::CommitmentMerkleParameters;
+use snarkos_models::objects::Transaction;
+use snarkos_testing::{consensus::MockTx, storage::*};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// The number of pending transactions in the benchmarked memory pools.
+const NUM_TRANSACTIONS: u64 = 5000;
+
+/// The number of transactions in a block removed from the benchmarked memory pools.
+const NUM_BLOCK_TRANSACTIONS: u64 = 100;
+
+type BenchLedger = Ledger<MockTx, CommitmentMerkleParameters>;
+
+/// Returns the `index`th pending transaction, paying a fee depending on its index.
+fn entry(index: u64) -> Entry<MockTx> {
+    let transaction = MockTx::new(index, (index % 100) as i64);
+
+    Entry {
+        size: transaction.size(),
+        transaction,
+    }
+}
+
+fn filled_memory_pool(storage: &BenchLedger) -> MemoryPool<MockTx> {
+    let mut memory_pool = MemoryPool::new();
+
+    for index in 0..NUM_TRANSACTIONS {
+        memory_pool.insert(storage, entry(index)).unwrap();
+    }
+
+    memory_pool
+}
+
+fn memory_pool_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memory_pool");
+    group.sample_size(10);
+
+    let (storage, _path) = open_test_blockchain::<MockTx, CommitmentMerkleParameters>();
+
+    group.bench_function("insert", |b| {
+        b.iter(|| filled_memory_pool(&storage));
+    });
+
+    group.bench_function("remove_block_transactions", |b| {
+        b.iter_batched(
+            || filled_memory_pool(&storage),
+            |mut memory_pool| {
+                for index in 0..NUM_BLOCK_TRANSACTIONS {
+                    memory_pool.remove_conflicts(&entry(index).transaction).unwrap();
+                }
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    let memory_pool = filled_memory_pool(&storage);
+
+    group.bench_function("get_candidates", |b| {
+        b.iter(|| memory_pool.get_candidates(&storage, 1_000_000).unwrap());
+    });
+
+    group.finish();
+
+    kill_storage_sync(storage);
+}
+
+criterion_group!(benches, memory_pool_bench);
+
+criterion_main!(benches);
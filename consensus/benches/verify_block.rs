@@ -0,0 +1,62 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_dpc::base_dpc::instantiated::InstantiatedDPC;
+use snarkos_models::dpc::DPCScheme;
+use snarkos_testing::{
+    consensus::{DATA, FIXTURE_VK, TEST_CONSENSUS},
+    storage::kill_storage,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn verify_block_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_block");
+    group.sample_size(10);
+
+    let parameters = &FIXTURE_VK.parameters;
+    let ledger = FIXTURE_VK.ledger();
+    let block = &DATA.block_1;
+    let transactions = &block.transactions.0;
+
+    group.bench_function("verify_transactions_sequential", |b| {
+        b.iter(|| {
+            for transaction in transactions {
+                assert!(InstantiatedDPC::verify(parameters, transaction, &ledger).unwrap());
+            }
+        });
+    });
+
+    group.bench_function("verify_transactions", |b| {
+        b.iter(|| {
+            assert!(TEST_CONSENSUS
+                .verify_transactions(parameters, transactions, &ledger)
+                .unwrap())
+        });
+    });
+
+    group.bench_function("verify_block", |b| {
+        b.iter(|| assert!(TEST_CONSENSUS.verify_block(parameters, block, &ledger).unwrap()));
+    });
+
+    group.finish();
+
+    kill_storage(ledger);
+}
+
+criterion_group!(benches, verify_block_bench);
+
+criterion_main!(benches);
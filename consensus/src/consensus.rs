@@ -14,7 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{difficulty::bitcoin_retarget, memory_pool::MemoryPool, MerkleTreeLedger};
+use crate::{
+    difficulty::bitcoin_retarget,
+    memory_pool::{MemoryPool, Rejection},
+    ChainEvent,
+    ChainNotifier,
+    MerkleTreeLedger,
+};
 use snarkos_curves::bls12_377::Bls12_377;
 use snarkos_dpc::base_dpc::{
     instantiated::*,
@@ -28,7 +34,7 @@ use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::{
     algorithms::{CRH, SNARK},
     dpc::{DPCComponents, DPCScheme, Program},
-    objects::{AccountScheme, LedgerScheme},
+    objects::{AccountScheme, LedgerScheme, Transaction},
 };
 use snarkos_objects::{
     dpc::DPCTransactions,
@@ -65,6 +71,9 @@ pub struct ConsensusParameters {
     /// The amount of time it should take to find a block
     pub target_block_time: i64,
 
+    /// A fixed difficulty target for every block, which disables difficulty retargeting
+    pub fixed_difficulty_target: Option<u64>,
+
     /// Network
     pub network: Network,
 
@@ -73,6 +82,9 @@ pub struct ConsensusParameters {
 
     /// The authorized inner SNARK IDs
     pub authorized_inner_snark_ids: Vec<Vec<u8>>,
+
+    /// Notifies subscribers of new canon blocks and chain reorganizations
+    pub notifier: ChainNotifier,
 }
 
 /// Calculate a block reward that halves every 4 years * 365 days * 24 hours * 100 blocks/hr = 3,504,000 blocks.
@@ -94,6 +106,10 @@ pub fn get_block_reward(block_num: u32) -> AleoAmount {
 impl ConsensusParameters {
     /// Calculate the difficulty for the next block based off how long it took to mine the last one.
     pub fn get_block_difficulty(&self, prev_header: &BlockHeader, block_timestamp: i64) -> u64 {
+        if let Some(difficulty_target) = self.fixed_difficulty_target {
+            return difficulty_target;
+        }
+
         bitcoin_retarget(
             block_timestamp,
             prev_header.time,
@@ -120,12 +136,6 @@ impl ConsensusParameters {
         merkle_root_hash: &MerkleRootHash,
         pedersen_merkle_root_hash: &PedersenMerkleRootHash,
     ) -> Result<(), ConsensusError> {
-        let hash_result = header.to_difficulty_hash();
-
-        let now = Utc::now().timestamp();
-        let future_timelimit: i64 = now + TWO_HOURS_UNIX;
-        let expected_difficulty = self.get_block_difficulty(parent_header, header.time);
-
         if parent_header.get_hash() != header.previous_block_hash {
             return Err(ConsensusError::NoParent(
                 parent_header.get_hash().to_string(),
@@ -137,14 +147,32 @@ impl ConsensusParameters {
             return Err(ConsensusError::PedersenMerkleRoot(
                 header.pedersen_merkle_root_hash.to_string(),
             ));
+        }
+
+        self.verify_header_chain(header, parent_header)
+    }
+
+    /// Verify a block header against its parent header without the block transactions.
+    /// Used to validate a header chain before downloading the blocks it commits to.
+    /// 1. The parent hash points to the parent header.
+    /// 2. The timestamp is less than 2 hours into the future.
+    /// 3. The timestamp is greater than parent timestamp.
+    /// 4. The difficulty target is the expected difficulty after the parent.
+    /// 5. The header carries a valid proof of work (see `verify_header_proof`).
+    pub fn verify_header_chain(&self, header: &BlockHeader, parent_header: &BlockHeader) -> Result<(), ConsensusError> {
+        let now = Utc::now().timestamp();
+        let future_timelimit: i64 = now + TWO_HOURS_UNIX;
+        let expected_difficulty = self.get_block_difficulty(parent_header, header.time);
+
+        if parent_header.get_hash() != header.previous_block_hash {
+            return Err(ConsensusError::NoParent(
+                parent_header.get_hash().to_string(),
+                header.previous_block_hash.to_string(),
+            ));
         } else if header.time > future_timelimit {
             return Err(ConsensusError::FuturisticTimestamp(future_timelimit, header.time));
         } else if header.time < parent_header.time {
             return Err(ConsensusError::TimestampInvalid(header.time, parent_header.time));
-        } else if hash_result > header.difficulty_target {
-            return Err(ConsensusError::PowInvalid(header.difficulty_target, hash_result));
-        } else if header.nonce >= self.max_nonce {
-            return Err(ConsensusError::NonceInvalid(header.nonce, self.max_nonce));
         } else if header.difficulty_target != expected_difficulty {
             return Err(ConsensusError::DifficultyMismatch(
                 expected_difficulty,
@@ -152,6 +180,22 @@ impl ConsensusParameters {
             ));
         }
 
+        self.verify_header_proof(header)
+    }
+
+    /// Verify the proof of work of a block header, which does not depend on the ledger state.
+    /// 1. The header is greater than or equal to its difficulty target.
+    /// 2. The nonce is within the limit.
+    /// 3. The PoSW proof is valid for the pedersen merkle root.
+    pub fn verify_header_proof(&self, header: &BlockHeader) -> Result<(), ConsensusError> {
+        let hash_result = header.to_difficulty_hash();
+
+        if hash_result > header.difficulty_target {
+            return Err(ConsensusError::PowInvalid(header.difficulty_target, hash_result));
+        } else if header.nonce >= self.max_nonce {
+            return Err(ConsensusError::NonceInvalid(header.nonce, self.max_nonce));
+        }
+
         // Verify the proof
         let proof = <Marlin<Bls12_377> as SNARK>::Proof::read(&header.proof.0[..])?;
         let verification_timer = start_timer!(|| "POSW verify");
@@ -179,6 +223,23 @@ impl ConsensusParameters {
         Ok(InstantiatedDPC::verify(parameters, transaction, ledger)?)
     }
 
+    /// Check if the transaction fits the ledger state of this node, which does not depend on the transaction alone.
+    /// Returns the rejection of a transaction that may still be valid on a node with a different ledger state.
+    /// 1. The transaction does not spend or create records that already exist in the ledger.
+    /// 2. The ledger digest of the transaction is known to the ledger.
+    pub fn verify_transaction_ledger_state(&self, transaction: &Tx, ledger: &MerkleTreeLedger) -> Option<Rejection> {
+        if ledger.contains_memo(transaction.memorandum())
+            || transaction.old_serial_numbers().iter().any(|sn| ledger.contains_sn(sn))
+            || transaction.new_commitments().iter().any(|cm| ledger.contains_cm(cm))
+        {
+            return Some(Rejection::SpentRecords);
+        } else if !ledger.validate_digest(&transaction.ledger_digest) {
+            return Some(Rejection::UnknownLedgerDigest);
+        }
+
+        None
+    }
+
     /// Check if the transactions are valid.
     pub fn verify_transactions(
         &self,
@@ -192,7 +253,30 @@ impl ConsensusParameters {
             }
         }
 
-        Ok(InstantiatedDPC::verify_transactions(parameters, transactions, ledger)?)
+        Ok(InstantiatedDPC::verify_transactions_in_parallel(
+            parameters,
+            transactions,
+            ledger,
+        )?)
+    }
+
+    /// Verify the parts of a block that do not depend on the ledger state of this node.
+    /// A block failing these checks is invalid on every node, unlike a block that only fails to extend this ledger.
+    /// 1. The transactions hash to the merkle roots of the header.
+    /// 2. The header carries a valid proof of work.
+    pub fn verify_block_integrity(&self, block: &Block<Tx>) -> Result<(), ConsensusError> {
+        let transaction_ids = block.transactions.to_transaction_ids()?;
+        let (merkle_root, pedersen_merkle_root, _) = txids_to_roots(&transaction_ids);
+
+        if block.header.merkle_root_hash != merkle_root {
+            return Err(ConsensusError::MerkleRoot(block.header.merkle_root_hash.to_string()));
+        } else if block.header.pedersen_merkle_root_hash != pedersen_merkle_root {
+            return Err(ConsensusError::PedersenMerkleRoot(
+                block.header.pedersen_merkle_root_hash.to_string(),
+            ));
+        }
+
+        self.verify_header_proof(&block.header)
     }
 
     /// Check if the block is valid.
@@ -254,6 +338,7 @@ impl ConsensusParameters {
     /// 1. Verify that the block header is valid.
     /// 2. Verify that the transactions are valid.
     /// 3. Insert/canonize block.
+    /// 4. Notify subscribers of the new canon block.
     pub fn process_block(
         &self,
         parameters: &PublicParameters<Components>,
@@ -273,11 +358,17 @@ impl ConsensusParameters {
         // 2. Insert/canonize block
         storage.insert_and_commit(block)?;
 
-        // 3. Remove transactions from the mempool
-        for transaction_id in block.transactions.to_transaction_ids()? {
-            memory_pool.remove_by_hash(&transaction_id)?;
+        // 3. Remove transactions, and the transactions conflicting with them, from the mempool
+        for transaction in block.transactions.iter() {
+            memory_pool.remove_conflicts(transaction)?;
         }
 
+        // 4. Notify subscribers of the new canon block
+        self.notifier.notify(ChainEvent::NewBlock {
+            block_hash: block.header.get_hash(),
+            block_height: storage.get_latest_block_height(),
+        });
+
         Ok(())
     }
 
@@ -317,7 +408,7 @@ impl ConsensusParameters {
 
                     // Attempt to fast forward the block state if the node already stores
                     // the children of the new canon block.
-                    let (_, child_path) = storage.longest_child_path(block.header.get_hash())?;
+                    let (_, child_path) = storage.heaviest_child_path(block.header.get_hash())?;
                     for child_block_hash in child_path {
                         let new_block = storage.get_block(&child_block_hash)?;
                         self.process_block(parameters, &storage, memory_pool, &new_block)?;
@@ -329,18 +420,34 @@ impl ConsensusParameters {
                         side_chain_path.new_block_number
                     );
 
-                    // If the side chain is now longer than the canon chain,
+                    let canon_cumulative_work =
+                        storage.get_cumulative_work(&storage.get_block_hash(storage.get_latest_block_height())?)?;
+
+                    // If the side chain now has more cumulative work than the canon chain,
                     // perform a fork to the side chain.
-                    if side_chain_path.new_block_number > storage.get_latest_block_height() {
+                    if side_chain_path.cumulative_work > canon_cumulative_work {
                         debug!(
-                            "Determined side chain is longer than canon chain by {} blocks",
-                            side_chain_path.new_block_number - storage.get_latest_block_height()
+                            "Determined side chain has more work than canon chain ({} > {})",
+                            side_chain_path.cumulative_work, canon_cumulative_work
                         );
                         warn!("A valid fork has been detected. Performing a fork to the side chain.");
 
+                        let mut reverted_block_hashes = vec![];
+                        for block_height in
+                            (side_chain_path.shared_block_number + 1)..=storage.get_latest_block_height()
+                        {
+                            reverted_block_hashes.push(storage.get_block_hash(block_height)?);
+                        }
+
                         // Fork to superior side chain
                         storage.revert_for_fork(&side_chain_path)?;
 
+                        self.notifier.notify(ChainEvent::ChainReorg {
+                            shared_block_height: side_chain_path.shared_block_number,
+                            reverted_block_hashes,
+                            new_block_height: side_chain_path.new_block_number,
+                        });
+
                         if !side_chain_path.path.is_empty() {
                             for block_hash in side_chain_path.path {
                                 if block_hash == block.header.get_hash() {
@@ -352,7 +459,7 @@ impl ConsensusParameters {
                             }
                         }
                     } else {
-                        // If the sidechain is not longer than the main canon chain, simply store the block
+                        // If the sidechain doesn't have more work than the main canon chain, simply store the block
                         storage.insert_only(block)?;
                     }
                 }
@@ -590,9 +697,11 @@ mod tests {
             max_block_size: 1_000_000usize,
             max_nonce: std::u32::MAX - 1,
             target_block_time: 2i64, //unix seconds
+            fixed_difficulty_target: None,
             network: Network::Mainnet,
             verifier: posw,
             authorized_inner_snark_ids: vec![],
+            notifier: ChainNotifier::default(),
         };
 
         let b1 = DATA.block_1.clone();
@@ -664,4 +773,68 @@ mod tests {
             .verify_header(&h2_err, &h1, &merkle_root_hash, &pedersen_merkle_root)
             .unwrap_err();
     }
+
+    #[test]
+    fn verify_header_chain() {
+        let posw = PoswMarlin::load().unwrap();
+
+        let consensus: ConsensusParameters = ConsensusParameters {
+            max_block_size: 1_000_000usize,
+            max_nonce: std::u32::MAX - 1,
+            target_block_time: 2i64, //unix seconds
+            fixed_difficulty_target: None,
+            network: Network::Mainnet,
+            verifier: posw,
+            authorized_inner_snark_ids: vec![],
+            notifier: ChainNotifier::default(),
+        };
+
+        let h1 = DATA.block_1.header.clone();
+        let h2 = DATA.block_2.header.clone();
+
+        // OK
+        consensus.verify_header_chain(&h2, &h1).unwrap();
+
+        // invalid parent hash
+        let mut h2_err = h2.clone();
+        h2_err.previous_block_hash = BlockHeaderHash([9; 32]);
+        consensus.verify_header_chain(&h2_err, &h1).unwrap_err();
+
+        // past block
+        let mut h2_err = h2.clone();
+        h2_err.time = 100;
+        consensus.verify_header_chain(&h2_err, &h1).unwrap_err();
+
+        // invalid pedersen merkle root hash fails the PoSW proof
+        let mut h2_err = h2.clone();
+        h2_err.pedersen_merkle_root_hash = PedersenMerkleRootHash([9; 32]);
+        consensus.verify_header_chain(&h2_err, &h1).unwrap_err();
+    }
+
+    #[test]
+    fn verify_block_integrity() {
+        let consensus: ConsensusParameters = ConsensusParameters {
+            max_block_size: 1_000_000usize,
+            max_nonce: std::u32::MAX - 1,
+            target_block_time: 2i64, //unix seconds
+            fixed_difficulty_target: None,
+            network: Network::Mainnet,
+            verifier: PoswMarlin::verify_only().unwrap(),
+            authorized_inner_snark_ids: vec![],
+            notifier: ChainNotifier::default(),
+        };
+
+        // OK, even though the block does not extend any ledger
+        consensus.verify_block_integrity(&DATA.block_2).unwrap();
+
+        // transactions not matching the merkle root
+        let mut block_err = DATA.block_2.clone();
+        block_err.transactions = DATA.block_1.transactions.clone();
+        consensus.verify_block_integrity(&block_err).unwrap_err();
+
+        // proof not matching the nonce
+        let mut block_err = DATA.block_2.clone();
+        block_err.header.nonce = block_err.header.nonce.wrapping_add(1) % consensus.max_nonce;
+        consensus.verify_block_integrity(&block_err).unwrap_err();
+    }
 }
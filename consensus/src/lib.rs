@@ -46,9 +46,15 @@ pub use difficulty::*;
 pub mod miner;
 pub use miner::Miner;
 
+pub mod notifier;
+pub use notifier::{ChainEvent, ChainNotifier};
+
 pub mod memory_pool;
 pub use memory_pool::MemoryPool;
 
+pub mod wallet;
+pub use wallet::{Wallet, WalletAccount};
+
 use snarkos_dpc::base_dpc::instantiated::{CommitmentMerkleParameters, Tx};
 use snarkos_storage::Ledger;
 
@@ -16,13 +16,11 @@
 
 //! Transactions memory pool
 //!
-//! `MemoryPool` keeps a vector of transactions seen by the miner.
+//! `MemoryPool` keeps the transactions seen by the miner, ordered by fee rate.
 
+use crate::{ChainEvent, ChainNotifier};
 use snarkos_errors::consensus::ConsensusError;
-use snarkos_models::{
-    algorithms::LoadableMerkleParameters,
-    objects::{LedgerScheme, Transaction},
-};
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
 use snarkos_objects::{dpc::DPCTransactions, BlockHeader};
 use snarkos_storage::Ledger;
 use snarkos_utilities::{
@@ -31,7 +29,124 @@ use snarkos_utilities::{
     to_bytes,
 };
 
-use std::collections::HashMap;
+use chrono::Utc;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+/// The default maximum total size in bytes of the transactions in the memory pool.
+pub const MEMORY_POOL_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// The maximum number of pending transactions a conflicting transaction may replace.
+pub const MAX_REPLACEMENTS: usize = 100;
+
+/// The outcome of submitting a transaction to the memory pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// The transaction was added to the memory pool.
+    Accepted(Vec<u8>),
+    /// The transaction was added to the memory pool, replacing the conflicting pending transactions.
+    Replaced {
+        transaction_id: Vec<u8>,
+        replaced: Vec<Vec<u8>>,
+    },
+    /// The transaction was not added to the memory pool.
+    Rejected(Rejection),
+}
+
+impl Admission {
+    /// Returns the id of the transaction, if it was added to the memory pool.
+    pub fn transaction_id(self) -> Option<Vec<u8>> {
+        match self {
+            Admission::Accepted(transaction_id) => Some(transaction_id),
+            Admission::Replaced { transaction_id, .. } => Some(transaction_id),
+            Admission::Rejected(_) => None,
+        }
+    }
+}
+
+/// The reason a transaction was not added to the memory pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The transaction failed the verification that does not depend on the ledger state,
+    /// or spends or creates the same record twice.
+    Invalid,
+    /// The transaction is a coinbase transaction, which is only valid in a block.
+    Coinbase,
+    /// The transaction is already in the memory pool.
+    AlreadyKnown,
+    /// The transaction spends or creates records that already exist in the ledger.
+    SpentRecords,
+    /// The transaction was created against a ledger digest unknown to this node.
+    UnknownLedgerDigest,
+    /// The transaction pays a lower fee rate than the minimum relay fee.
+    FeeBelowMinimum,
+    /// The transaction conflicts with pending transactions paying a higher fee or fee rate.
+    InsufficientReplacementFee,
+    /// The transaction conflicts with more pending transactions than it may replace.
+    TooManyReplacements(usize),
+    /// The memory pool is full of transactions paying a higher fee rate.
+    MemoryPoolFull,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::Invalid => write!(f, "the transaction is invalid"),
+            Rejection::Coinbase => write!(f, "coinbase transactions are not relayed"),
+            Rejection::AlreadyKnown => write!(f, "the transaction is already in the memory pool"),
+            Rejection::SpentRecords => write!(f, "the transaction contains spent records"),
+            Rejection::UnknownLedgerDigest => write!(f, "the transaction refers to an unknown ledger digest"),
+            Rejection::FeeBelowMinimum => write!(f, "the transaction pays less than the minimum relay fee"),
+            Rejection::InsufficientReplacementFee => write!(
+                f,
+                "the transaction conflicts with pending transactions, and pays too little to replace them"
+            ),
+            Rejection::TooManyReplacements(count) => write!(
+                f,
+                "the transaction would replace {} pending transactions, more than the limit of {}",
+                count, MAX_REPLACEMENTS
+            ),
+            Rejection::MemoryPoolFull => write!(f, "the memory pool is full of transactions paying a higher fee rate"),
+        }
+    }
+}
+
+/// The fee of a transaction per byte, compared without rounding.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeRate {
+    pub fee: u64,
+    pub size: usize,
+}
+
+impl FeeRate {
+    /// Construct a new `FeeRate` of the given fee paid for the given number of bytes.
+    pub fn new(fee: u64, size: usize) -> Self {
+        Self { fee, size }
+    }
+}
+
+impl Ord for FeeRate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.fee as u128 * other.size as u128).cmp(&(other.fee as u128 * self.size as u128))
+    }
+}
+
+impl PartialOrd for FeeRate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FeeRate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FeeRate {}
 
 /// Stores a transaction and it's size in the memory pool.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -40,14 +155,85 @@ pub struct Entry<T: Transaction> {
     pub transaction: T,
 }
 
+impl<T: Transaction> Entry<T> {
+    /// Returns the fee paid by the transaction, which is its positive value balance.
+    pub fn fee(&self) -> u64 {
+        let value_balance: i64 = self.transaction.value_balance().into();
+
+        value_balance.max(0) as u64
+    }
+
+    /// Returns the fee paid by the transaction per byte.
+    pub fn fee_rate(&self) -> FeeRate {
+        FeeRate::new(self.fee(), self.size)
+    }
+}
+
+/// The serialized serial numbers, commitments and memorandum of a transaction.
+/// Two transactions sharing any of them conflict, and can't both be included in the ledger.
+struct ConflictKeys {
+    serial_numbers: Vec<Vec<u8>>,
+    commitments: Vec<Vec<u8>>,
+    memo: Vec<u8>,
+}
+
+impl ConflictKeys {
+    fn new<T: Transaction>(transaction: &T) -> Result<Self, ConsensusError> {
+        let mut serial_numbers = vec![];
+        for sn in transaction.old_serial_numbers() {
+            serial_numbers.push(to_bytes![sn]?);
+        }
+
+        let mut commitments = vec![];
+        for cm in transaction.new_commitments() {
+            commitments.push(to_bytes![cm]?);
+        }
+
+        let memo = to_bytes![transaction.memorandum()]?;
+
+        Ok(Self {
+            serial_numbers,
+            commitments,
+            memo,
+        })
+    }
+}
+
 /// Stores transactions received by the server.
 /// Transaction entries will eventually be fetched by the miner and assembled into blocks.
+///
+/// Once the total size of the transactions exceeds `max_size`, the transactions with the lowest fee rate are evicted.
+/// Transactions paying less than `min_relay_fee` per 1000 bytes are rejected.
 #[derive(Debug, Clone)]
 pub struct MemoryPool<T: Transaction> {
     pub total_size: usize,
 
+    // The maximum total size of the transactions
+    pub max_size: usize,
+
+    // The minimum fee per 1000 bytes of admitted transactions
+    pub min_relay_fee: u64,
+
     // Hashmap transaction_id -> Entry
     pub transactions: HashMap<Vec<u8>, Entry<T>>,
+
+    // Transaction ids ordered by the fee rate of their entries
+    pub fee_rates: BTreeSet<(FeeRate, Vec<u8>)>,
+
+    // Hashmap serial_number -> transaction_id
+    pub serial_numbers: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap commitment -> transaction_id
+    pub commitments: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap memorandum -> transaction_id
+    pub memos: HashMap<Vec<u8>, Vec<u8>>,
+
+    // Hashmap transaction_id -> Arrival timestamp
+    pub arrival_times: HashMap<Vec<u8>, i64>,
+
+    // Notifies subscribers of transactions admitted to the memory pool
+    pub notifier: ChainNotifier,
 }
 
 const BLOCK_HEADER_SIZE: usize = BlockHeader::size();
@@ -59,7 +245,15 @@ impl<T: Transaction> MemoryPool<T> {
     pub fn new() -> Self {
         Self {
             total_size: 0,
+            max_size: MEMORY_POOL_MAX_SIZE,
+            min_relay_fee: 0,
             transactions: HashMap::<Vec<u8>, Entry<T>>::new(),
+            fee_rates: BTreeSet::new(),
+            serial_numbers: HashMap::new(),
+            commitments: HashMap::new(),
+            memos: HashMap::new(),
+            arrival_times: HashMap::<Vec<u8>, i64>::new(),
+            notifier: ChainNotifier::default(),
         }
     }
 
@@ -97,82 +291,173 @@ impl<T: Transaction> MemoryPool<T> {
         Ok(())
     }
 
+    /// Sets the maximum total size of the transactions, and evicts the transactions with the lowest fee rate
+    /// until the memory pool fits.
+    #[inline]
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), ConsensusError> {
+        self.max_size = max_size;
+        self.evict()
+    }
+
     /// Adds entry to memory pool if valid in the current ledger.
+    /// Returns the transaction id if the entry was admitted, see `submit`.
     #[inline]
     pub fn insert<P: LoadableMerkleParameters>(
         &mut self,
         storage: &Ledger<T, P>,
         entry: Entry<T>,
     ) -> Result<Option<Vec<u8>>, ConsensusError> {
+        Ok(self.submit(storage, entry)?.transaction_id())
+    }
+
+    /// Adds entry to memory pool if valid in the current ledger, and returns whether it was admitted.
+    ///
+    /// An entry conflicting with pending transactions replaces them if it pays a higher fee than all of them
+    /// combined, and a higher fee rate than each of them, unless it would replace more than `MAX_REPLACEMENTS`.
+    /// A full memory pool only admits the entry if it pays a higher fee rate than the evicted entries.
+    #[inline]
+    pub fn submit<P: LoadableMerkleParameters>(
+        &mut self,
+        storage: &Ledger<T, P>,
+        entry: Entry<T>,
+    ) -> Result<Admission, ConsensusError> {
         let transaction_serial_numbers = entry.transaction.old_serial_numbers();
         let transaction_commitments = entry.transaction.new_commitments();
-        let transaction_memo = entry.transaction.memorandum();
 
-        if has_duplicates(transaction_serial_numbers)
-            || has_duplicates(transaction_commitments)
-            || self.contains(&entry)
-        {
-            return Ok(None);
+        if has_duplicates(transaction_serial_numbers) || has_duplicates(transaction_commitments) {
+            return Ok(Admission::Rejected(Rejection::Invalid));
         }
 
-        let mut holding_serial_numbers = vec![];
-        let mut holding_commitments = vec![];
-        let mut holding_memos = vec![];
+        if self.contains(&entry) {
+            return Ok(Admission::Rejected(Rejection::AlreadyKnown));
+        }
 
-        for (_, tx) in self.transactions.iter() {
-            holding_serial_numbers.extend(tx.transaction.old_serial_numbers());
-            holding_commitments.extend(tx.transaction.new_commitments());
-            holding_memos.push(tx.transaction.memorandum());
+        let fee_rate = entry.fee_rate();
+        if fee_rate < FeeRate::new(self.min_relay_fee, 1000) {
+            return Ok(Admission::Rejected(Rejection::FeeBelowMinimum));
         }
 
-        for sn in transaction_serial_numbers {
-            if storage.contains_sn(sn) || holding_serial_numbers.contains(&sn) {
-                return Ok(None);
-            }
+        if storage.transcation_conflicts(&entry.transaction) {
+            return Ok(Admission::Rejected(Rejection::SpentRecords));
         }
 
-        for cm in transaction_commitments {
-            if storage.contains_cm(cm) || holding_commitments.contains(&cm) {
-                return Ok(None);
-            }
+        let keys = ConflictKeys::new(&entry.transaction)?;
+        let conflicting = self.conflicting(&keys);
+
+        if conflicting.len() > MAX_REPLACEMENTS {
+            return Ok(Admission::Rejected(Rejection::TooManyReplacements(conflicting.len())));
         }
 
-        if storage.contains_memo(transaction_memo) || holding_memos.contains(&transaction_memo) {
-            return Ok(None);
+        let replaced_fee = conflicting
+            .iter()
+            .map(|transaction_id| self.transactions[transaction_id].fee())
+            .fold(0u64, |total, fee| total.saturating_add(fee));
+        let pays_higher_fee_rate = conflicting
+            .iter()
+            .all(|transaction_id| fee_rate > self.transactions[transaction_id].fee_rate());
+
+        if !conflicting.is_empty() && (entry.fee() <= replaced_fee || !pays_higher_fee_rate) {
+            return Ok(Admission::Rejected(Rejection::InsufficientReplacementFee));
         }
 
         let transaction_id = entry.transaction.transaction_id()?.to_vec();
 
+        if !self.fits_after_eviction(&(fee_rate, transaction_id.clone()), entry.size, &conflicting) {
+            return Ok(Admission::Rejected(Rejection::MemoryPoolFull));
+        }
+
+        let mut replaced = vec![];
+        for transaction_id in conflicting {
+            if self.remove_entry(&transaction_id)?.is_some() {
+                replaced.push(transaction_id);
+            }
+        }
+        replaced.sort();
+
+        for sn in keys.serial_numbers {
+            self.serial_numbers.insert(sn, transaction_id.clone());
+        }
+        for cm in keys.commitments {
+            self.commitments.insert(cm, transaction_id.clone());
+        }
+        self.memos.insert(keys.memo, transaction_id.clone());
+
         self.total_size += entry.size;
         self.transactions.insert(transaction_id.clone(), entry);
-
-        Ok(Some(transaction_id))
+        self.fee_rates.insert((fee_rate, transaction_id.clone()));
+        self.arrival_times
+            .insert(transaction_id.clone(), Utc::now().timestamp());
+
+        self.evict()?;
+
+        self.notifier.notify(ChainEvent::NewTransaction {
+            transaction_id: transaction_id.clone(),
+            replaced: replaced.clone(),
+        });
+
+        match replaced.is_empty() {
+            true => Ok(Admission::Accepted(transaction_id)),
+            false => {
+                debug!(
+                    "Transaction {} replaced {} transactions in the memory pool",
+                    hex::encode(&transaction_id),
+                    replaced.len()
+                );
+
+                Ok(Admission::Replaced {
+                    transaction_id,
+                    replaced,
+                })
+            }
+        }
     }
 
     /// Cleanse the memory pool of outdated transactions.
+    /// Transactions conflicting with the ledger, or whose ledger digest is no longer a past digest of the ledger,
+    /// are removed.
     #[inline]
     pub fn cleanse<P: LoadableMerkleParameters>(&mut self, storage: &Ledger<T, P>) -> Result<(), ConsensusError> {
-        let mut new_memory_pool = Self::new();
-
-        for (_, entry) in self.clone().transactions.iter() {
-            new_memory_pool.insert(&storage, entry.clone())?;
+        let past_digests = storage.past_digests()?;
+
+        let mut outdated = vec![];
+        for (transaction_id, entry) in self.transactions.iter() {
+            if storage.transcation_conflicts(&entry.transaction)
+                || !past_digests.contains(&to_bytes![entry.transaction.ledger_digest()]?.to_vec())
+            {
+                outdated.push(transaction_id.clone());
+            }
         }
 
-        self.total_size = new_memory_pool.total_size;
-        self.transactions = new_memory_pool.transactions;
+        for transaction_id in outdated {
+            self.remove_entry(&transaction_id)?;
+        }
 
         Ok(())
     }
 
+    /// Removes the transactions conflicting with the given transaction, such as a transaction included in a new block.
+    /// Returns the ids of the removed transactions.
+    #[inline]
+    pub fn remove_conflicts(&mut self, transaction: &T) -> Result<Vec<Vec<u8>>, ConsensusError> {
+        let keys = ConflictKeys::new(transaction)?;
+
+        let mut removed = vec![];
+        for transaction_id in self.conflicting(&keys) {
+            if self.remove_entry(&transaction_id)?.is_some() {
+                removed.push(transaction_id);
+            }
+        }
+
+        Ok(removed)
+    }
+
     /// Removes transaction from memory pool or error.
     #[inline]
     pub fn remove(&mut self, entry: &Entry<T>) -> Result<Option<Vec<u8>>, ConsensusError> {
         if self.contains(entry) {
-            self.total_size -= entry.size;
-
             let transaction_id = entry.transaction.transaction_id()?.to_vec();
 
-            self.transactions.remove(&transaction_id);
+            self.remove_entry(&transaction_id)?;
 
             return Ok(Some(transaction_id));
         }
@@ -183,15 +468,7 @@ impl<T: Transaction> MemoryPool<T> {
     /// Removes transaction from memory pool based on the transaction id.
     #[inline]
     pub fn remove_by_hash(&mut self, transaction_id: &Vec<u8>) -> Result<Option<Entry<T>>, ConsensusError> {
-        match self.transactions.clone().get(transaction_id) {
-            Some(entry) => {
-                self.total_size -= entry.size;
-                self.transactions.remove(transaction_id);
-
-                Ok(Some(entry.clone()))
-            }
-            None => Ok(None),
-        }
+        self.remove_entry(transaction_id)
     }
 
     /// Returns whether or not the memory pool contains the entry.
@@ -204,6 +481,7 @@ impl<T: Transaction> MemoryPool<T> {
     }
 
     /// Get candidate transactions for a new block.
+    /// Transactions are selected by descending fee rate, skipping the ones that conflict or don't fit.
     #[inline]
     pub fn get_candidates<P: LoadableMerkleParameters>(
         &self,
@@ -215,8 +493,9 @@ impl<T: Transaction> MemoryPool<T> {
         let mut block_size = 0;
         let mut transactions = DPCTransactions::new();
 
-        // TODO Change naive transaction selection
-        for (_transaction_id, entry) in self.transactions.clone() {
+        for (_fee_rate, transaction_id) in self.fee_rates.iter().rev() {
+            let entry = &self.transactions[transaction_id];
+
             if block_size + entry.size <= max_size {
                 if storage.transcation_conflicts(&entry.transaction) || transactions.conflicts(&entry.transaction) {
                     continue;
@@ -229,12 +508,97 @@ impl<T: Transaction> MemoryPool<T> {
 
         Ok(transactions)
     }
+
+    /// Returns the ids of the pending transactions sharing a serial number, commitment or memorandum
+    /// with the given keys.
+    fn conflicting(&self, keys: &ConflictKeys) -> HashSet<Vec<u8>> {
+        let mut conflicting = HashSet::new();
+
+        for sn in &keys.serial_numbers {
+            conflicting.extend(self.serial_numbers.get(sn).cloned());
+        }
+        for cm in &keys.commitments {
+            conflicting.extend(self.commitments.get(cm).cloned());
+        }
+        conflicting.extend(self.memos.get(&keys.memo).cloned());
+
+        conflicting
+    }
+
+    /// Returns true if an entry of the given size would not be evicted once added to the memory pool,
+    /// after the given transactions it replaces are removed.
+    fn fits_after_eviction(&self, key: &(FeeRate, Vec<u8>), size: usize, replaced: &HashSet<Vec<u8>>) -> bool {
+        let replaced_size: usize = replaced
+            .iter()
+            .map(|transaction_id| self.transactions[transaction_id].size)
+            .sum();
+        let mut required_size = self.total_size - replaced_size + size;
+        if required_size <= self.max_size {
+            return true;
+        }
+
+        // Walk the entries with a lower fee rate only until enough of them would be evicted.
+        for (_fee_rate, transaction_id) in self.fee_rates.range(..key) {
+            if replaced.contains(transaction_id) {
+                continue;
+            }
+
+            required_size -= self.transactions[transaction_id].size;
+            if required_size <= self.max_size {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Evicts the transactions with the lowest fee rate until the memory pool fits its maximum size.
+    fn evict(&mut self) -> Result<(), ConsensusError> {
+        while self.total_size > self.max_size {
+            let transaction_id = match self.fee_rates.iter().next() {
+                Some((_fee_rate, transaction_id)) => transaction_id.clone(),
+                None => break,
+            };
+
+            debug!(
+                "Evicting transaction {} from the memory pool",
+                hex::encode(&transaction_id)
+            );
+
+            self.remove_entry(&transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the entry with the given transaction id from all indexes.
+    fn remove_entry(&mut self, transaction_id: &[u8]) -> Result<Option<Entry<T>>, ConsensusError> {
+        let entry = match self.transactions.remove(transaction_id) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let keys = ConflictKeys::new(&entry.transaction)?;
+        for sn in &keys.serial_numbers {
+            self.serial_numbers.remove(sn);
+        }
+        for cm in &keys.commitments {
+            self.commitments.remove(cm);
+        }
+        self.memos.remove(&keys.memo);
+
+        self.total_size -= entry.size;
+        self.fee_rates.remove(&(entry.fee_rate(), transaction_id.to_vec()));
+        self.arrival_times.remove(transaction_id);
+
+        Ok(Some(entry))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use snarkos_dpc::base_dpc::instantiated::Tx;
+    use snarkos_dpc::base_dpc::instantiated::{CommitmentMerkleParameters, Tx};
     use snarkos_objects::Block;
     use snarkos_testing::{consensus::*, storage::*};
 
@@ -312,6 +676,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(1, mem_pool.transactions.len());
+        assert_eq!(1, mem_pool.arrival_times.len());
         assert_eq!(size, mem_pool.total_size);
 
         mem_pool
@@ -319,6 +684,237 @@ mod tests {
             .unwrap();
 
         assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.arrival_times.len());
+        assert_eq!(0, mem_pool.total_size);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn remove_conflicting_transactions() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut mem_pool = MemoryPool::new();
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        mem_pool
+            .insert(&blockchain, Entry {
+                size,
+                transaction: transaction.clone(),
+            })
+            .unwrap();
+
+        assert_eq!(transaction.old_serial_numbers().len(), mem_pool.serial_numbers.len());
+        assert_eq!(transaction.new_commitments().len(), mem_pool.commitments.len());
+        assert_eq!(1, mem_pool.memos.len());
+
+        // A block including the transaction removes it from all indexes
+
+        let removed = mem_pool.remove_conflicts(&transaction).unwrap();
+
+        assert_eq!(vec![transaction.transaction_id().unwrap().to_vec()], removed);
+        assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.serial_numbers.len());
+        assert_eq!(0, mem_pool.commitments.len());
+        assert_eq!(0, mem_pool.memos.len());
+        assert_eq!(0, mem_pool.total_size);
+
+        kill_storage_sync(blockchain);
+    }
+
+    fn mock_entry(transaction: MockTx) -> Entry<MockTx> {
+        Entry {
+            size: transaction.size(),
+            transaction,
+        }
+    }
+
+    #[test]
+    fn replace_by_fee() {
+        let (blockchain, _) = open_test_blockchain::<MockTx, CommitmentMerkleParameters>();
+
+        let mut mem_pool = MemoryPool::new();
+
+        let original = MockTx::new(0, 10);
+
+        let mut replacement = MockTx::new(1, 20);
+        replacement.serial_numbers[0] = original.serial_numbers[0];
+
+        let mut underpaying = MockTx::new(2, 15);
+        underpaying.serial_numbers[0] = original.serial_numbers[0];
+
+        let admission = mem_pool.submit(&blockchain, mock_entry(original.clone())).unwrap();
+        assert_eq!(Admission::Accepted(original.id.to_vec()), admission);
+
+        let admission = mem_pool.submit(&blockchain, mock_entry(replacement.clone())).unwrap();
+        assert_eq!(
+            Admission::Replaced {
+                transaction_id: replacement.id.to_vec(),
+                replaced: vec![original.id.to_vec()],
+            },
+            admission
+        );
+
+        let admission = mem_pool.submit(&blockchain, mock_entry(underpaying)).unwrap();
+        assert_eq!(Admission::Rejected(Rejection::InsufficientReplacementFee), admission);
+
+        assert_eq!(1, mem_pool.transactions.len());
+        assert!(mem_pool.transactions.contains_key(&replacement.id.to_vec()));
+        assert_eq!(replacement.serial_numbers.len(), mem_pool.serial_numbers.len());
+        assert_eq!(replacement.size(), mem_pool.total_size);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn replace_too_many_transactions() {
+        let (blockchain, _) = open_test_blockchain::<MockTx, CommitmentMerkleParameters>();
+
+        let mut mem_pool = MemoryPool::new();
+        let mut replacement = MockTx::new(0, 1_000_000);
+        replacement.serial_numbers.clear();
+
+        for index in 1..=(MAX_REPLACEMENTS as u64 + 1) {
+            let transaction = MockTx::new(index, 1);
+            replacement.serial_numbers.push(transaction.serial_numbers[0]);
+
+            mem_pool.submit(&blockchain, mock_entry(transaction)).unwrap();
+        }
+
+        let admission = mem_pool.submit(&blockchain, mock_entry(replacement)).unwrap();
+        assert_eq!(
+            Admission::Rejected(Rejection::TooManyReplacements(MAX_REPLACEMENTS + 1)),
+            admission
+        );
+        assert_eq!(MAX_REPLACEMENTS + 1, mem_pool.transactions.len());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn replace_in_full_memory_pool() {
+        let (blockchain, _) = open_test_blockchain::<MockTx, CommitmentMerkleParameters>();
+
+        let original = MockTx::new(0, 10);
+        let other = MockTx::new(1, 30);
+        let size = original.size();
+
+        let mut mem_pool = MemoryPool::new();
+        mem_pool.set_max_size(2 * size).unwrap();
+
+        mem_pool.submit(&blockchain, mock_entry(original.clone())).unwrap();
+        mem_pool.submit(&blockchain, mock_entry(other.clone())).unwrap();
+
+        // A replacement that would be evicted right away leaves the replaced transaction in the memory pool
+
+        let mut evicted = MockTx::new(2, 40);
+        evicted.serial_numbers[0] = original.serial_numbers[0];
+
+        let admission = mem_pool
+            .submit(&blockchain, Entry {
+                size: 2 * size,
+                transaction: evicted,
+            })
+            .unwrap();
+
+        assert_eq!(Admission::Rejected(Rejection::MemoryPoolFull), admission);
+        assert_eq!(2, mem_pool.transactions.len());
+        assert!(mem_pool.transactions.contains_key(&original.id.to_vec()));
+        assert_eq!(
+            original.serial_numbers.len() + other.serial_numbers.len(),
+            mem_pool.serial_numbers.len()
+        );
+        assert_eq!(2 * size, mem_pool.total_size);
+
+        // A replacement paying a higher fee rate evicts the other transaction
+
+        let mut replacement = MockTx::new(3, 80);
+        replacement.serial_numbers[0] = original.serial_numbers[0];
+
+        let admission = mem_pool
+            .submit(&blockchain, Entry {
+                size: 2 * size,
+                transaction: replacement.clone(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            Admission::Replaced {
+                transaction_id: replacement.id.to_vec(),
+                replaced: vec![original.id.to_vec()],
+            },
+            admission
+        );
+        assert_eq!(1, mem_pool.transactions.len());
+        assert!(mem_pool.transactions.contains_key(&replacement.id.to_vec()));
+        assert_eq!(2 * size, mem_pool.total_size);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn fee_rate_ordering() {
+        assert!(FeeRate::new(2, 100) > FeeRate::new(1, 100));
+        assert!(FeeRate::new(1, 100) > FeeRate::new(1, 200));
+        assert!(FeeRate::new(0, 100) < FeeRate::new(1, 1000));
+        assert_eq!(FeeRate::new(1, 100), FeeRate::new(10, 1000));
+    }
+
+    #[test]
+    fn reject_below_min_relay_fee() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let mut mem_pool = MemoryPool::new();
+        mem_pool.min_relay_fee = u64::MAX;
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        let inserted = mem_pool.insert(&blockchain, Entry { size, transaction }).unwrap();
+
+        assert!(inserted.is_none());
+        assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.total_size);
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    fn evict_above_max_size() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let size = TRANSACTION_2.len();
+
+        // A transaction larger than the memory pool is evicted right away
+
+        let mut mem_pool = MemoryPool::new();
+        mem_pool.set_max_size(size - 1).unwrap();
+
+        let inserted = mem_pool
+            .insert(&blockchain, Entry {
+                size,
+                transaction: transaction.clone(),
+            })
+            .unwrap();
+
+        assert!(inserted.is_none());
+        assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.fee_rates.len());
+
+        // Shrinking the memory pool evicts its transactions
+
+        let mut mem_pool = MemoryPool::new();
+        mem_pool.insert(&blockchain, Entry { size, transaction }).unwrap();
+
+        assert_eq!(1, mem_pool.fee_rates.len());
+
+        mem_pool.set_max_size(size - 1).unwrap();
+
+        assert_eq!(0, mem_pool.transactions.len());
+        assert_eq!(0, mem_pool.fee_rates.len());
+        assert_eq!(0, mem_pool.arrival_times.len());
         assert_eq!(0, mem_pool.total_size);
 
         kill_storage_sync(blockchain);
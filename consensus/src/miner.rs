@@ -29,7 +29,7 @@ use snarkos_utilities::{bytes::ToBytes, to_bytes};
 
 use chrono::Utc;
 use rand::{thread_rng, Rng};
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 use tokio::sync::Mutex;
 
 /// Compiles transactions into blocks to be submitted to the network.
@@ -44,6 +44,9 @@ pub struct Miner {
 
     /// The miner instance (must be initialized with a Proving Key)
     miner: PoswMarlin,
+
+    /// Set to stop the proof of work of the block that is currently being mined.
+    cancelled: Arc<AtomicBool>,
 }
 
 impl Miner {
@@ -54,9 +57,16 @@ impl Miner {
             consensus,
             // load the miner with the proving key, this should never fail
             miner: PoswMarlin::load().expect("could not instantiate the miner"),
+            cancelled: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Returns the miner with a flag that cancels its proof of work once set.
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
     /// Fetches new transactions from the memory pool.
     pub async fn fetch_memory_pool_transactions<T: Transaction, P: LoadableMerkleParameters>(
         storage: &Arc<Ledger<T, P>>,
@@ -132,7 +142,7 @@ impl Miner {
     }
 
     /// Run proof of work to find block.
-    /// Returns BlockHeader with nonce solution.
+    /// Returns BlockHeader with nonce solution, or an error if the miner is cancelled first.
     pub fn find_block<T: Transaction>(
         &self,
         transactions: &DPCTransactions<T>,
@@ -145,11 +155,12 @@ impl Miner {
         let difficulty_target = self.consensus.get_block_difficulty(parent_header, time);
 
         // TODO: Switch this to use a user-provided RNG
-        let (nonce, proof) = self.miner.mine(
+        let (nonce, proof) = self.miner.mine_until_cancelled(
             &subroots,
             difficulty_target,
             &mut thread_rng(),
             self.consensus.max_nonce,
+            &self.cancelled,
         )?;
 
         Ok(BlockHeader {
@@ -0,0 +1,72 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Chain event notifications
+//!
+//! `ChainNotifier` broadcasts the changes of the local chain state and memory pool to its subscribers.
+
+use snarkos_objects::BlockHeaderHash;
+
+use tokio::sync::broadcast;
+
+/// A change of the local chain state or memory pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainEvent {
+    /// A block was committed to the canon chain.
+    NewBlock {
+        block_hash: BlockHeaderHash,
+        block_height: u32,
+    },
+    /// A transaction was admitted to the memory pool, replacing the conflicting pending transactions.
+    NewTransaction {
+        transaction_id: Vec<u8>,
+        replaced: Vec<Vec<u8>>,
+    },
+    /// The canon chain was reverted to the block shared with a side chain that has more cumulative work.
+    ChainReorg {
+        shared_block_height: u32,
+        reverted_block_hashes: Vec<BlockHeaderHash>,
+        new_block_height: u32,
+    },
+}
+
+/// Broadcasts chain events to its subscribers. The default notifier discards all events.
+#[derive(Clone, Debug, Default)]
+pub struct ChainNotifier {
+    sender: Option<broadcast::Sender<ChainEvent>>,
+}
+
+impl ChainNotifier {
+    /// Creates a new notifier that buffers up to `capacity` events for each lagging subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+
+        Self { sender: Some(sender) }
+    }
+
+    /// Returns a receiver of the events sent after this call, or `None` for a discarding notifier.
+    pub fn subscribe(&self) -> Option<broadcast::Receiver<ChainEvent>> {
+        self.sender.as_ref().map(|sender| sender.subscribe())
+    }
+
+    /// Sends an event to the current subscribers.
+    pub fn notify(&self, event: ChainEvent) {
+        if let Some(sender) = &self.sender {
+            // Sending only fails if there are no subscribers, in which case the event is not needed.
+            let _ = sender.send(event);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Node wallet
+//!
+//! `Wallet` tracks the records owned by a set of accounts by scanning the encrypted records of
+//! the canon chain with the account view keys. The spent state of a record can only be tracked
+//! for accounts registered with their private key, as serial numbers are derived from it.
+
+use crate::MerkleTreeLedger;
+use snarkos_dpc::base_dpc::{
+    instantiated::{Components, InstantiatedDPC, Tx},
+    parameters::SystemParameters,
+    record::DPCRecord,
+    record_encryption::RecordEncryption,
+};
+use snarkos_errors::consensus::ConsensusError;
+use snarkos_models::{
+    dpc::Record,
+    objects::{LedgerScheme, Transaction},
+};
+use snarkos_objects::{AccountAddress, AccountPrivateKey, AccountViewKey, Block};
+use snarkos_storage::WalletRecord;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use std::str::FromStr;
+
+/// An account tracked by the wallet.
+#[derive(Clone)]
+pub struct WalletAccount {
+    pub address: AccountAddress<Components>,
+    pub view_key: AccountViewKey<Components>,
+    pub private_key: Option<AccountPrivateKey<Components>>,
+}
+
+impl WalletAccount {
+    /// Creates a wallet account from an account private key or an account view key string.
+    pub fn new(system_parameters: &SystemParameters<Components>, account: &str) -> Result<Self, ConsensusError> {
+        let (view_key, private_key) = match AccountPrivateKey::<Components>::from_str(account) {
+            Ok(private_key) => {
+                let view_key = AccountViewKey::from_private_key(
+                    &system_parameters.account_signature,
+                    &system_parameters.account_commitment,
+                    &private_key,
+                )?;
+
+                (view_key, Some(private_key))
+            }
+            Err(_) => (AccountViewKey::<Components>::from_str(account)?, None),
+        };
+
+        let address = AccountAddress::from_view_key(&system_parameters.account_encryption, &view_key)?;
+
+        Ok(Self {
+            address,
+            view_key,
+            private_key,
+        })
+    }
+}
+
+/// Tracks the records owned by a set of accounts in the canon chain.
+#[derive(Clone)]
+pub struct Wallet {
+    system_parameters: SystemParameters<Components>,
+    accounts: Vec<WalletAccount>,
+}
+
+impl Wallet {
+    pub fn new(system_parameters: SystemParameters<Components>, accounts: Vec<WalletAccount>) -> Self {
+        Self {
+            system_parameters,
+            accounts,
+        }
+    }
+
+    /// Bring the wallet state up to date with the canon chain and return the number of scanned blocks.
+    /// 1. Rescan the chain if the tracked accounts changed since the wallet state was built.
+    /// 2. Remove the records of scanned blocks that are no longer canon after a fork.
+    /// 3. Scan the canon blocks after the wallet tip for owned records.
+    /// 4. Mark the records whose serial numbers exist in the canon chain as spent.
+    pub fn sync(&self, storage: &MerkleTreeLedger) -> Result<u32, ConsensusError> {
+        if storage.is_empty() {
+            return Ok(0);
+        }
+
+        // 1. Rescan the chain if the tracked accounts changed
+
+        let accounts_fingerprint = self.accounts_fingerprint()?;
+        if storage.get_wallet_accounts()?.as_ref() != Some(&accounts_fingerprint) {
+            info!("Wallet accounts changed, rescanning the chain");
+            storage.reset_wallet(accounts_fingerprint)?;
+        }
+
+        // 2. Remove the records of reverted blocks
+
+        while let Some((_, block_hash)) = storage.get_wallet_tip()? {
+            if storage.is_canon(&block_hash) {
+                break;
+            }
+
+            debug!("Reverting wallet block {}", block_hash);
+            storage.remove_wallet_tip()?;
+        }
+
+        // 3. Scan the new canon blocks
+
+        let start_height = match storage.get_wallet_tip()? {
+            Some((block_height, _)) => block_height + 1,
+            None => 0,
+        };
+        let latest_block_height = storage.get_latest_block_height();
+
+        let mut num_scanned_blocks = 0;
+        for block_height in start_height..=latest_block_height {
+            let block = storage.get_block_from_block_number(block_height)?;
+            let wallet_records = self.scan_block(&block)?;
+
+            storage.insert_wallet_block(block_height, &block.header, wallet_records)?;
+            num_scanned_blocks += 1;
+        }
+
+        // 4. Update the spent state of the records
+
+        let mut updated_records = vec![];
+        for (commitment, mut wallet_record) in storage.get_wallet_records()? {
+            if let Some(serial_number) = &wallet_record.serial_number {
+                let serial_number: <Tx as Transaction>::SerialNumber = FromBytes::read(&serial_number[..])?;
+                let is_spent = storage.contains_sn(&serial_number);
+
+                if is_spent != wallet_record.is_spent {
+                    wallet_record.is_spent = is_spent;
+                    updated_records.push((commitment, wallet_record));
+                }
+            }
+        }
+        storage.update_wallet_records(updated_records)?;
+
+        Ok(num_scanned_blocks)
+    }
+
+    /// Returns the records in the block owned by the tracked accounts with their commitments.
+    pub fn scan_block(&self, block: &Block<Tx>) -> Result<Vec<(Vec<u8>, WalletRecord)>, ConsensusError> {
+        let mut wallet_records = vec![];
+
+        for transaction in &block.transactions.0 {
+            let encrypted_records = transaction.encrypted_records();
+            let commitments = transaction.new_commitments();
+
+            for (encrypted_record, commitment) in encrypted_records.iter().zip(commitments) {
+                for account in &self.accounts {
+                    // A record decrypted with a key other than the owner's does not match the commitment.
+                    let record = match RecordEncryption::decrypt_record(
+                        &self.system_parameters,
+                        &account.view_key,
+                        encrypted_record,
+                    ) {
+                        Ok(record) if record.commitment() == *commitment => record,
+                        _ => continue,
+                    };
+
+                    if record.is_dummy() {
+                        break;
+                    }
+
+                    let serial_number = match &account.private_key {
+                        Some(private_key) => {
+                            let (serial_number, _) =
+                                InstantiatedDPC::generate_sn(&self.system_parameters, &record, private_key)?;
+                            Some(to_bytes![serial_number]?)
+                        }
+                        None => None,
+                    };
+
+                    wallet_records.push((to_bytes![commitment]?, WalletRecord {
+                        record: to_bytes![record]?,
+                        serial_number,
+                        is_spent: false,
+                    }));
+                    break;
+                }
+            }
+        }
+
+        Ok(wallet_records)
+    }
+
+    /// Returns the unspent records of the given account stored in the wallet state.
+    pub fn get_unspent_records(
+        storage: &MerkleTreeLedger,
+        address: &AccountAddress<Components>,
+    ) -> Result<Vec<DPCRecord<Components>>, ConsensusError> {
+        let mut unspent_records = vec![];
+
+        for (_, wallet_record) in storage.get_wallet_records()? {
+            if wallet_record.is_spent {
+                continue;
+            }
+
+            let record = DPCRecord::<Components>::read(&wallet_record.record[..])?;
+            if record.owner() == address {
+                unspent_records.push(record);
+            }
+        }
+
+        Ok(unspent_records)
+    }
+
+    /// Returns a serialized identifier of the tracked accounts.
+    fn accounts_fingerprint(&self) -> Result<Vec<u8>, ConsensusError> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|account| (account.view_key.to_string(), account.private_key.is_some()))
+            .collect::<Vec<_>>();
+
+        Ok(bincode::serialize(&accounts)?)
+    }
+}
@@ -116,6 +116,11 @@ mod consensus_dpc {
             miner.establish_block(&parameters, &ledger, &transactions).unwrap();
 
         assert!(InstantiatedDPC::verify_transactions(&parameters, &transactions, &ledger).unwrap());
+        assert!(
+            consensus
+                .verify_transactions(&parameters, &transactions, &ledger)
+                .unwrap()
+        );
 
         let header = miner.find_block(&transactions, &previous_block_header).unwrap();
         let new_block = Block { header, transactions };
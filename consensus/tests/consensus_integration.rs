@@ -15,11 +15,11 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 mod consensus_integration {
-    use snarkos_consensus::miner::Miner;
+    use snarkos_consensus::{memory_pool::Rejection, miner::Miner, MemoryPool};
     use snarkos_dpc::base_dpc::instantiated::Tx;
     use snarkos_objects::{dpc::DPCTransactions, BlockHeader};
     use snarkos_posw::txids_to_roots;
-    use snarkos_testing::consensus::*;
+    use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, storage::kill_storage};
 
     // this test ensures that a block is found by running the proof of work
     // and that it doesnt loop forever
@@ -54,4 +54,27 @@ mod consensus_integration {
         let parent_header = genesis().header;
         test_find_block(&transactions, &parent_header);
     }
+
+    #[test]
+    fn verify_transaction_ledger_state() {
+        let ledger = FIXTURE_VK.ledger();
+        let parameters = load_verifying_parameters();
+        let consensus = TEST_CONSENSUS.clone();
+        let mut memory_pool = MemoryPool::new();
+
+        let transaction = &DATA.block_1.transactions.0[0];
+        assert_eq!(None, consensus.verify_transaction_ledger_state(transaction, &ledger));
+
+        // The records of the transaction are spent once the block is in the ledger
+        consensus
+            .receive_block(&parameters, &ledger, &mut memory_pool, &DATA.block_1)
+            .unwrap();
+
+        assert_eq!(
+            Some(Rejection::SpentRecords),
+            consensus.verify_transaction_ledger_state(transaction, &ledger)
+        );
+
+        kill_storage(ledger);
+    }
 }
@@ -129,7 +129,7 @@ mod consensus_sidechain {
 
         assert_eq!(old_block_height + 1, new_block_height);
 
-        // 2. Receive longer chain of blocks 1 and 2 from the sidechain (the longest chain wins).
+        // 2. Receive longer chain of blocks 1 and 2 from the sidechain (the chain with more cumulative work wins).
 
         old_block_height = blockchain.get_latest_block_height();
 
@@ -148,6 +148,128 @@ mod consensus_sidechain {
         kill_storage_sync(blockchain);
     }
 
+    // Receive two blocks at the same height where the sidechain block has a harder difficulty target.
+    // The sidechain carries more cumulative work, so it replaces the canonical block.
+    #[test]
+    fn accept_heavier_equal_length() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+
+        let mut memory_pool = MemoryPool::new();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        let block_1_canon = Block::<Tx>::read(&BLOCK_1[..]).unwrap();
+        let block_1_side = Block::<Tx>::read(&HARDER_BLOCK_1[..]).unwrap();
+
+        let old_block_height = blockchain.get_latest_block_height();
+
+        // 1. Receive canonchain block 1.
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_canon)
+            .unwrap();
+
+        // 2. Receive the harder sidechain block 1.
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_side)
+            .unwrap();
+
+        let new_block_height = blockchain.get_latest_block_height();
+
+        assert_eq!(old_block_height + 1, new_block_height);
+
+        // 3. Ensure the harder sidechain block 1 is accepted.
+
+        let accepted = blockchain.get_latest_block().unwrap();
+
+        assert_eq!(accepted, block_1_side);
+
+        let block_hash = block_1_side.header.get_hash();
+        let expected_work = genesis().header.work() + block_1_side.header.work();
+
+        assert_eq!(expected_work, blockchain.get_cumulative_work(&block_hash).unwrap());
+
+        kill_storage_sync(blockchain);
+    }
+
+    // Receive two blocks at the same height where the canonical block has a harder difficulty target.
+    // The lighter sidechain block is stored but does not replace the canonical block.
+    #[test]
+    fn reject_lighter_equal_length() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+
+        let mut memory_pool = MemoryPool::new();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        let block_1_canon = Block::<Tx>::read(&HARDER_BLOCK_1[..]).unwrap();
+        let block_1_side = Block::<Tx>::read(&BLOCK_1[..]).unwrap();
+
+        // 1. Receive the harder canonchain block 1.
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_canon)
+            .unwrap();
+
+        // 2. Receive the lighter sidechain block 1.
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_side)
+            .unwrap();
+
+        // 3. Ensure the lighter sidechain block 1 is stored but rejected.
+
+        let accepted = blockchain.get_latest_block().unwrap();
+
+        assert_eq!(accepted, block_1_canon);
+        assert!(blockchain.block_hash_exists(&block_1_side.header.get_hash()));
+
+        kill_storage_sync(blockchain);
+    }
+
+    // Receive a single harder sidechain block that outweighs a longer canonical chain.
+    #[test]
+    fn accept_heavier_shorter() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+
+        let mut memory_pool = MemoryPool::new();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        let block_1_canon = Block::<Tx>::read(&BLOCK_1[..]).unwrap();
+        let block_2_canon = Block::<Tx>::read(&BLOCK_2[..]).unwrap();
+        let block_1_side = Block::<Tx>::read(&HARDER_BLOCK_1[..]).unwrap();
+
+        // 1. Receive canonchain blocks 1 and 2.
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_canon)
+            .unwrap();
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_2_canon)
+            .unwrap();
+
+        let old_block_height = blockchain.get_latest_block_height();
+
+        // 2. Receive the harder sidechain block 1 (the chain with more cumulative work wins).
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &block_1_side)
+            .unwrap();
+
+        let new_block_height = blockchain.get_latest_block_height();
+
+        assert_eq!(old_block_height - 1, new_block_height);
+        assert_eq!(blockchain.get_latest_block().unwrap(), block_1_side);
+
+        kill_storage_sync(blockchain);
+    }
+
     // Receive blocks from a sidechain (out of order) that overtakes our current canonical chain.
     #[test]
     fn fork_out_of_order() {
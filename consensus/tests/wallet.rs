@@ -0,0 +1,102 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod wallet {
+    use snarkos_consensus::{MemoryPool, Wallet, WalletAccount};
+    use snarkos_dpc::base_dpc::{instantiated::Components, record::DPCRecord};
+    use snarkos_models::dpc::Record;
+    use snarkos_objects::AccountViewKey;
+    use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, storage::*};
+
+    use std::sync::Arc;
+
+    fn non_dummy_records(records: &[DPCRecord<Components>]) -> Vec<DPCRecord<Components>> {
+        records.iter().filter(|record| !record.is_dummy()).cloned().collect()
+    }
+
+    // Track the miner by its private key and the receiver by its view key.
+    // The records of both accounts should be discovered, and the miner records spent in
+    // block 2 should no longer be unspent. Reverting block 2 should restore the previous state.
+    #[test]
+    fn sync_and_revert() {
+        let blockchain = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let system_parameters = &parameters.system_parameters;
+
+        let mut memory_pool = MemoryPool::new();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        let [miner_acc, receiver_acc, _] = &FIXTURE_VK.test_accounts;
+
+        let receiver_view_key = AccountViewKey::from_private_key(
+            &system_parameters.account_signature,
+            &system_parameters.account_commitment,
+            &receiver_acc.private_key,
+        )
+        .unwrap();
+
+        let wallet = Wallet::new(system_parameters.clone(), vec![
+            WalletAccount::new(system_parameters, &miner_acc.private_key.to_string()).unwrap(),
+            WalletAccount::new(system_parameters, &receiver_view_key.to_string()).unwrap(),
+        ]);
+
+        // Scan the genesis block and block 1
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &DATA.block_1)
+            .unwrap();
+
+        assert_eq!(wallet.sync(&blockchain).unwrap(), 2);
+
+        let miner_records = Wallet::get_unspent_records(&blockchain, &miner_acc.address).unwrap();
+        assert_eq!(miner_records, non_dummy_records(&DATA.records_1));
+
+        // Scan block 2, which spends the block 1 records
+
+        consensus
+            .receive_block(&parameters, &blockchain, &mut memory_pool, &DATA.block_2)
+            .unwrap();
+
+        assert_eq!(wallet.sync(&blockchain).unwrap(), 1);
+
+        let miner_records = Wallet::get_unspent_records(&blockchain, &miner_acc.address).unwrap();
+        for record in non_dummy_records(&DATA.records_2) {
+            assert!(miner_records.contains(&record));
+        }
+        for record in non_dummy_records(&DATA.records_1) {
+            assert!(!miner_records.contains(&record));
+        }
+
+        let receiver_records = Wallet::get_unspent_records(&blockchain, &receiver_acc.address).unwrap();
+        assert_eq!(receiver_records.len(), 1);
+        assert_eq!(receiver_records[0].value(), 10);
+
+        // Revert block 2
+
+        blockchain.decommit_latest_block().unwrap();
+
+        assert_eq!(wallet.sync(&blockchain).unwrap(), 0);
+
+        let miner_records = Wallet::get_unspent_records(&blockchain, &miner_acc.address).unwrap();
+        assert_eq!(miner_records, non_dummy_records(&DATA.records_1));
+
+        let receiver_records = Wallet::get_unspent_records(&blockchain, &receiver_acc.address).unwrap();
+        assert!(receiver_records.is_empty());
+
+        kill_storage_sync(blockchain);
+    }
+}
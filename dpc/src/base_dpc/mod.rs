@@ -46,6 +46,7 @@ use snarkos_utilities::{
 
 use itertools::Itertools;
 use rand::Rng;
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
 pub mod inner_circuit;
@@ -336,6 +337,171 @@ impl<Components: BaseDPCComponents> DPC<Components> {
         end_timer!(record_time);
         Ok(record)
     }
+
+    /// Returns true iff the transaction passes the checks that don't require a pairing: its serial numbers
+    /// and commitments are unique and new to the ledger, its ledger digest is valid, and its signatures verify.
+    pub fn verify_prechecks<L>(
+        parameters: &PublicParameters<Components>,
+        transaction: &DPCTransaction<Components>,
+        ledger: &L,
+    ) -> Result<bool, DPCError>
+    where
+        L: LedgerScheme<
+            Commitment = <Components::RecordCommitment as CommitmentScheme>::Output,
+            MerkleParameters = Components::MerkleParameters,
+            MerklePath = MerklePath<Components::MerkleParameters>,
+            MerkleTreeDigest = MerkleTreeDigest<Components::MerkleParameters>,
+            SerialNumber = <Components::AccountSignature as SignatureScheme>::PublicKey,
+            Transaction = DPCTransaction<Components>,
+        >,
+    {
+        // Returns false if there are duplicate serial numbers in the transaction.
+        if has_duplicates(transaction.old_serial_numbers().iter()) {
+            eprintln!("Transaction contains duplicate serial numbers");
+            return Ok(false);
+        }
+
+        // Returns false if there are duplicate commitments numbers in the transaction.
+        if has_duplicates(transaction.new_commitments().iter()) {
+            eprintln!("Transaction contains duplicate commitments");
+            return Ok(false);
+        }
+
+        let ledger_time = start_timer!(|| "Ledger checks");
+
+        // Returns false if the transaction memo previously existed in the ledger.
+        if ledger.contains_memo(transaction.memorandum()) {
+            eprintln!("Ledger already contains this transaction memo.");
+            return Ok(false);
+        }
+
+        // Returns false if any transaction serial number previously existed in the ledger.
+        for sn in transaction.old_serial_numbers() {
+            if ledger.contains_sn(sn) {
+                eprintln!("Ledger already contains this transaction serial number.");
+                return Ok(false);
+            }
+        }
+
+        // Returns false if any transaction commitment previously existed in the ledger.
+        for cm in transaction.new_commitments() {
+            if ledger.contains_cm(cm) {
+                eprintln!("Ledger already contains this transaction commitment.");
+                return Ok(false);
+            }
+        }
+
+        // Returns false if the ledger digest in the transaction is invalid.
+        if !ledger.validate_digest(&transaction.ledger_digest) {
+            eprintln!("Ledger digest is invalid.");
+            return Ok(false);
+        }
+
+        end_timer!(ledger_time);
+
+        let signature_time = start_timer!(|| "Signature checks");
+
+        let signature_message = &to_bytes![
+            transaction.network_id(),
+            transaction.ledger_digest(),
+            transaction.old_serial_numbers(),
+            transaction.new_commitments(),
+            transaction.program_commitment(),
+            transaction.local_data_root(),
+            transaction.value_balance(),
+            transaction.memorandum()
+        ]?;
+
+        let account_signature = &parameters.system_parameters.account_signature;
+        for (pk, sig) in transaction.old_serial_numbers().iter().zip(&transaction.signatures) {
+            if !Components::AccountSignature::verify(account_signature, pk, signature_message, sig)? {
+                eprintln!("Signature didn't verify.");
+                return Ok(false);
+            }
+        }
+
+        end_timer!(signature_time);
+
+        Ok(true)
+    }
+
+    /// Returns the id of the inner SNARK verification key, which every outer SNARK proof is checked against.
+    pub fn inner_snark_id(
+        parameters: &PublicParameters<Components>,
+    ) -> Result<<Components::InnerSNARKVerificationKeyCRH as CRH>::Output, DPCError> {
+        let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
+            parameters.inner_snark_parameters.1.clone().into();
+
+        Ok(Components::InnerSNARKVerificationKeyCRH::hash(
+            &parameters.system_parameters.inner_snark_verification_key_crh,
+            &to_bytes![inner_snark_vk]?,
+        )?)
+    }
+
+    /// Returns the public input of the outer SNARK proof of the transaction.
+    pub fn outer_snark_input<L>(
+        parameters: &PublicParameters<Components>,
+        inner_snark_id: &<Components::InnerSNARKVerificationKeyCRH as CRH>::Output,
+        transaction: &DPCTransaction<Components>,
+        ledger: &L,
+    ) -> Result<OuterCircuitVerifierInput<Components>, DPCError>
+    where
+        L: LedgerScheme<
+            Commitment = <Components::RecordCommitment as CommitmentScheme>::Output,
+            MerkleParameters = Components::MerkleParameters,
+            MerklePath = MerklePath<Components::MerkleParameters>,
+            MerkleTreeDigest = MerkleTreeDigest<Components::MerkleParameters>,
+            SerialNumber = <Components::AccountSignature as SignatureScheme>::PublicKey,
+            Transaction = DPCTransaction<Components>,
+        >,
+    {
+        // Construct the ciphertext hashes
+
+        let mut new_encrypted_record_hashes = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
+        for encrypted_record in &transaction.encrypted_records {
+            let encrypted_record_hash =
+                RecordEncryption::encrypted_record_hash(&parameters.system_parameters, encrypted_record)?;
+
+            new_encrypted_record_hashes.push(encrypted_record_hash);
+        }
+
+        let inner_snark_input = InnerCircuitVerifierInput {
+            system_parameters: parameters.system_parameters.clone(),
+            ledger_parameters: ledger.parameters().clone(),
+            ledger_digest: transaction.ledger_digest().clone(),
+            old_serial_numbers: transaction.old_serial_numbers().to_vec(),
+            new_commitments: transaction.new_commitments().to_vec(),
+            new_encrypted_record_hashes,
+            memo: transaction.memorandum().clone(),
+            program_commitment: transaction.program_commitment().clone(),
+            local_data_root: transaction.local_data_root().clone(),
+            value_balance: transaction.value_balance(),
+            network_id: transaction.network_id(),
+        };
+
+        Ok(OuterCircuitVerifierInput {
+            inner_snark_verifier_input: inner_snark_input,
+            inner_snark_id: inner_snark_id.clone(),
+        })
+    }
+
+    /// Returns true iff the outer SNARK proofs of all the transactions verify, checking them as a single batch.
+    pub fn verify_transaction_proofs(
+        parameters: &PublicParameters<Components>,
+        outer_snark_inputs: &[OuterCircuitVerifierInput<Components>],
+        transactions: &[DPCTransaction<Components>],
+    ) -> Result<bool, DPCError> {
+        let inputs_and_proofs: Vec<_> = outer_snark_inputs
+            .iter()
+            .zip(transactions)
+            .map(|(input, transaction)| (input, &transaction.transaction_proof))
+            .collect();
+
+        Ok(Components::OuterSNARK::batch_verify(
+            &parameters.outer_snark_parameters.1,
+            &inputs_and_proofs,
+        )?)
+    }
 }
 
 impl<Components: BaseDPCComponents, L: LedgerScheme> DPCScheme<L> for DPC<Components>
@@ -871,116 +1037,116 @@ where
     fn verify(parameters: &Self::Parameters, transaction: &Self::Transaction, ledger: &L) -> Result<bool, DPCError> {
         let verify_time = start_timer!(|| "BaseDPC::verify");
 
-        // Returns false if there are duplicate serial numbers in the transaction.
-        if has_duplicates(transaction.old_serial_numbers().iter()) {
-            eprintln!("Transaction contains duplicate serial numbers");
+        if !Self::verify_prechecks(parameters, transaction, ledger)? {
             return Ok(false);
         }
 
-        // Returns false if there are duplicate commitments numbers in the transaction.
-        if has_duplicates(transaction.new_commitments().iter()) {
-            eprintln!("Transaction contains duplicate commitments");
+        let inner_snark_id = Self::inner_snark_id(parameters)?;
+        let outer_snark_input = Self::outer_snark_input(parameters, &inner_snark_id, transaction, ledger)?;
+
+        if !Components::OuterSNARK::verify(
+            &parameters.outer_snark_parameters.1,
+            &outer_snark_input,
+            &transaction.transaction_proof,
+        )? {
+            eprintln!("Transaction proof failed to verify.");
             return Ok(false);
         }
 
-        let ledger_time = start_timer!(|| "Ledger checks");
+        end_timer!(verify_time);
 
-        // Returns false if the transaction memo previously existed in the ledger.
-        if ledger.contains_memo(transaction.memorandum()) {
-            eprintln!("Ledger already contains this transaction memo.");
+        Ok(true)
+    }
+
+    /// Returns true iff all the transactions in the block are valid according to the ledger,
+    /// and no two of them spend the same record.
+    /// Every transaction is checked before any pairing work, and the transaction proofs are verified as a batch.
+    fn verify_transactions(
+        parameters: &Self::Parameters,
+        transactions: &Vec<Self::Transaction>,
+        ledger: &L,
+    ) -> Result<bool, DPCError> {
+        let verify_time = start_timer!(|| "BaseDPC::verify_transactions");
+
+        // Returns false if two transactions spend the same record.
+        if has_duplicates(transactions.iter().flat_map(|transaction| transaction.old_serial_numbers())) {
+            eprintln!("Transactions contain duplicate serial numbers");
             return Ok(false);
         }
 
-        // Returns false if any transaction serial number previously existed in the ledger.
-        for sn in transaction.old_serial_numbers() {
-            if ledger.contains_sn(sn) {
-                eprintln!("Ledger already contains this transaction serial number.");
+        for transaction in transactions {
+            if !Self::verify_prechecks(parameters, transaction, ledger)? {
                 return Ok(false);
             }
         }
 
-        // Returns false if any transaction commitment previously existed in the ledger.
-        for cm in transaction.new_commitments() {
-            if ledger.contains_cm(cm) {
-                eprintln!("Ledger already contains this transaction commitment.");
-                return Ok(false);
-            }
-        }
+        let inner_snark_id = Self::inner_snark_id(parameters)?;
+        let outer_snark_inputs = transactions
+            .iter()
+            .map(|transaction| Self::outer_snark_input(parameters, &inner_snark_id, transaction, ledger))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Returns false if the ledger digest in the transaction is invalid.
-        if !ledger.validate_digest(&transaction.ledger_digest) {
-            eprintln!("Ledger digest is invalid.");
+        if !Self::verify_transaction_proofs(parameters, &outer_snark_inputs, transactions)? {
+            eprintln!("Transaction proofs failed to verify.");
             return Ok(false);
         }
 
-        end_timer!(ledger_time);
-
-        let signature_time = start_timer!(|| "Signature checks");
+        end_timer!(verify_time);
 
-        let signature_message = &to_bytes![
-            transaction.network_id(),
-            transaction.ledger_digest(),
-            transaction.old_serial_numbers(),
-            transaction.new_commitments(),
-            transaction.program_commitment(),
-            transaction.local_data_root(),
-            transaction.value_balance(),
-            transaction.memorandum()
-        ]?;
+        Ok(true)
+    }
+}
 
-        let account_signature = &parameters.system_parameters.account_signature;
-        for (pk, sig) in transaction.old_serial_numbers().iter().zip(&transaction.signatures) {
-            if !Components::AccountSignature::verify(account_signature, pk, signature_message, sig)? {
-                eprintln!("Signature didn't verify.");
-                return Ok(false);
-            }
+impl<Components: BaseDPCComponents> DPC<Components>
+where
+    PublicParameters<Components>: Sync,
+    DPCTransaction<Components>: Sync,
+    OuterCircuitVerifierInput<Components>: Send,
+    <Components::InnerSNARKVerificationKeyCRH as CRH>::Output: Sync,
+{
+    /// Returns true iff all the transactions in the block are valid according to the ledger, as `verify_transactions`.
+    /// Every transaction is checked in parallel before any pairing work, and the transaction proofs
+    /// are then verified as a batch.
+    pub fn verify_transactions_in_parallel<L>(
+        parameters: &PublicParameters<Components>,
+        transactions: &[DPCTransaction<Components>],
+        ledger: &L,
+    ) -> Result<bool, DPCError>
+    where
+        L: LedgerScheme<
+                Commitment = <Components::RecordCommitment as CommitmentScheme>::Output,
+                MerkleParameters = Components::MerkleParameters,
+                MerklePath = MerklePath<Components::MerkleParameters>,
+                MerkleTreeDigest = MerkleTreeDigest<Components::MerkleParameters>,
+                SerialNumber = <Components::AccountSignature as SignatureScheme>::PublicKey,
+                Transaction = DPCTransaction<Components>,
+            > + Sync,
+    {
+        let verify_time = start_timer!(|| "BaseDPC::verify_transactions_in_parallel");
+
+        // Returns false if two transactions spend the same record.
+        if has_duplicates(transactions.iter().flat_map(|transaction| transaction.old_serial_numbers())) {
+            eprintln!("Transactions contain duplicate serial numbers");
+            return Ok(false);
         }
 
-        end_timer!(signature_time);
-
-        // Construct the ciphertext hashes
+        let prechecks = transactions
+            .par_iter()
+            .map(|transaction| Self::verify_prechecks(parameters, transaction, ledger))
+            .collect::<Result<Vec<bool>, _>>()?;
 
-        let mut new_encrypted_record_hashes = Vec::with_capacity(Components::NUM_OUTPUT_RECORDS);
-        for encrypted_record in &transaction.encrypted_records {
-            let encrypted_record_hash =
-                RecordEncryption::encrypted_record_hash(&parameters.system_parameters, encrypted_record)?;
-
-            new_encrypted_record_hashes.push(encrypted_record_hash);
+        if prechecks.contains(&false) {
+            return Ok(false);
         }
 
-        let inner_snark_input = InnerCircuitVerifierInput {
-            system_parameters: parameters.system_parameters.clone(),
-            ledger_parameters: ledger.parameters().clone(),
-            ledger_digest: transaction.ledger_digest().clone(),
-            old_serial_numbers: transaction.old_serial_numbers().to_vec(),
-            new_commitments: transaction.new_commitments().to_vec(),
-            new_encrypted_record_hashes,
-            memo: transaction.memorandum().clone(),
-            program_commitment: transaction.program_commitment().clone(),
-            local_data_root: transaction.local_data_root().clone(),
-            value_balance: transaction.value_balance(),
-            network_id: transaction.network_id(),
-        };
-
-        let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
-            parameters.inner_snark_parameters.1.clone().into();
-
-        let inner_snark_id = Components::InnerSNARKVerificationKeyCRH::hash(
-            &parameters.system_parameters.inner_snark_verification_key_crh,
-            &to_bytes![inner_snark_vk]?,
-        )?;
+        let inner_snark_id = Self::inner_snark_id(parameters)?;
+        let outer_snark_inputs = transactions
+            .par_iter()
+            .map(|transaction| Self::outer_snark_input(parameters, &inner_snark_id, transaction, ledger))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let outer_snark_input = OuterCircuitVerifierInput {
-            inner_snark_verifier_input: inner_snark_input,
-            inner_snark_id,
-        };
-
-        if !Components::OuterSNARK::verify(
-            &parameters.outer_snark_parameters.1,
-            &outer_snark_input,
-            &transaction.transaction_proof,
-        )? {
-            eprintln!("Transaction proof failed to verify.");
+        if !Self::verify_transaction_proofs(parameters, &outer_snark_inputs, transactions)? {
+            eprintln!("Transaction proofs failed to verify.");
             return Ok(false);
         }
 
@@ -988,19 +1154,4 @@ where
 
         Ok(true)
     }
-
-    /// Returns true iff all the transactions in the block are valid according to the ledger.
-    fn verify_transactions(
-        parameters: &Self::Parameters,
-        transactions: &Vec<Self::Transaction>,
-        ledger: &L,
-    ) -> Result<bool, DPCError> {
-        for transaction in transactions {
-            if !Self::verify(parameters, transaction, ledger)? {
-                return Ok(false);
-            }
-        }
-
-        Ok(true)
-    }
 }
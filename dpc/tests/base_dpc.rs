@@ -223,7 +223,7 @@ fn base_dpc_integration_test() {
     let previous_block = ledger.get_latest_block().unwrap();
 
     let mut transactions = DPCTransactions::new();
-    transactions.push(transaction);
+    transactions.push(transaction.clone());
 
     let transaction_ids: Vec<Vec<u8>> = transactions
         .to_transaction_ids()
@@ -251,6 +251,14 @@ fn base_dpc_integration_test() {
     };
 
     assert!(InstantiatedDPC::verify_transactions(&parameters, &transactions.0, &ledger).unwrap());
+    assert!(InstantiatedDPC::verify_transactions_in_parallel(&parameters, &transactions.0, &ledger).unwrap());
+
+    // Transactions spending the same records can't be in the same block
+    let mut double_spend = transactions.0.clone();
+    double_spend.push(transaction);
+
+    assert!(!InstantiatedDPC::verify_transactions(&parameters, &double_spend, &ledger).unwrap());
+    assert!(!InstantiatedDPC::verify_transactions_in_parallel(&parameters, &double_spend, &ledger).unwrap());
 
     let block = Block { header, transactions };
 
@@ -17,7 +17,7 @@
 use crate::{
     algorithms::CRHError,
     dpc::DPCError,
-    objects::{BlockError, TransactionError},
+    objects::{AccountError, BlockError, TransactionError},
     posw::PoswError,
     storage::StorageError,
 };
@@ -27,6 +27,9 @@ use std::fmt::Debug;
 /// Possible block verification errors
 #[derive(Debug, Error)]
 pub enum ConsensusError {
+    #[error("{}", _0)]
+    AccountError(AccountError),
+
     #[error("UTXO has already been spent {:?} index: {:?}", _0, _1)]
     AlreadySpent(Vec<u8>, u32),
 
@@ -106,6 +109,12 @@ pub enum ConsensusError {
     TransactionOverspending,
 }
 
+impl From<AccountError> for ConsensusError {
+    fn from(error: AccountError) -> Self {
+        ConsensusError::AccountError(error)
+    }
+}
+
 impl From<BlockError> for ConsensusError {
     fn from(error: BlockError) -> Self {
         ConsensusError::BlockError(error)
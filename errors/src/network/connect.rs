@@ -34,6 +34,18 @@ pub enum ConnectError {
 
     #[error("Address {:?} not found", _0)]
     AddressNotFound(SocketAddr),
+
+    #[error("Rejected an unencrypted connection from {:?}", _0)]
+    EncryptionRequired(SocketAddr),
+
+    #[error("Received an encrypted connection request from {:?}, but encryption is disabled", _0)]
+    EncryptionUnavailable(SocketAddr),
+
+    #[error("Invalid noise preamble {:?}", _0)]
+    InvalidPreamble([u8; 4]),
+
+    #[error("Invalid static key file {}", _0)]
+    InvalidStaticKey(String),
 }
 
 impl From<MessageError> for ConnectError {
@@ -24,14 +24,23 @@ pub enum MessageError {
     #[error("{}", _0)]
     Message(String),
 
+    #[error("Inbound byte budget of {} bytes exceeded", _0)]
+    InboundBudgetExceeded(u64),
+
     #[error("Invalid message length {}. Expected length of {}", _0, _1)]
     InvalidLength(usize, usize),
 
     #[error("{}", _0)]
     MessageHeaderError(MessageHeaderError),
 
+    #[error("Message length {} exceeds the maximum length of {}", _0, _1)]
+    MessageTooLarge(usize, usize),
+
     #[error("{}", 0)]
     SteamReadError(StreamReadError),
+
+    #[error("Unknown message name {}", _0)]
+    UnknownMessage(String),
 }
 
 impl From<MessageHeaderError> for MessageError {
@@ -27,6 +27,18 @@ pub enum MessageHeaderError {
     #[error("Invalid message header length {}. Expected length of 16", _0)]
     InvalidLength(usize),
 
+    #[error("Invalid frame header length {}. Expected length of 24", _0)]
+    InvalidFrameLength(usize),
+
+    #[error("Invalid network magic {:?}", _0)]
+    InvalidMagic([u8; 4]),
+
+    #[error("Received a frame for network {}. Expected network {}", _0, _1)]
+    NetworkMismatch(u8, u8),
+
+    #[error("Invalid payload checksum {:08x}. Expected checksum {:08x}", _0, _1)]
+    InvalidChecksum(u32, u32),
+
     #[error("{}", _0)]
     StreamReadError(StreamReadError),
 }
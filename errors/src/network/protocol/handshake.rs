@@ -43,6 +43,21 @@ pub enum HandshakeError {
     #[error("Expected nonce {}. Got {}", _0, _1)]
     InvalidNonce(u64, u64),
 
+    #[error("Peer is running on network {}. Expected network {}", _0, _1)]
+    NetworkMismatch(u8, u8),
+
+    #[error("Peer is running on a chain with genesis block {}. Expected genesis block {}", _0, _1)]
+    GenesisMismatch(String, String),
+
+    #[error("Peer {} authenticated with a different static key than before", _0)]
+    PublicKeyMismatch(SocketAddr),
+
+    #[error(
+        "Rejected a handshake request from {} as the maximum number of inbound peers is reached",
+        _0
+    )]
+    InboundSlotsFull(SocketAddr),
+
     #[error("{}", _0)]
     ConnectError(ConnectError),
 
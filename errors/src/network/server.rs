@@ -15,11 +15,14 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    consensus::ConsensusError,
     network::{message::MessageError, ConnectError, HandshakeError, PingProtocolError, SendError},
     objects::{BlockError, TransactionError},
     storage::StorageError,
 };
 
+use std::net::SocketAddr;
+
 #[derive(Debug, Error)]
 pub enum ServerError {
     #[error("{}", _0)]
@@ -31,15 +34,24 @@ pub enum ServerError {
     #[error("{}", _0)]
     ConnectError(ConnectError),
 
+    #[error("{}", _0)]
+    ConsensusError(ConsensusError),
+
     #[error("{}", _0)]
     HandshakeError(HandshakeError),
 
+    #[error("invalid subnet: {}", _0)]
+    InvalidSubnet(String),
+
     #[error("{}", _0)]
     Message(String),
 
     #[error("{}", _0)]
     MessageError(MessageError),
 
+    #[error("peer {} is banned", _0)]
+    PeerBanned(SocketAddr),
+
     #[error("{}", _0)]
     PingProtocolError(PingProtocolError),
 
@@ -65,6 +77,12 @@ impl From<ConnectError> for ServerError {
     }
 }
 
+impl From<ConsensusError> for ServerError {
+    fn from(error: ConsensusError) -> Self {
+        ServerError::ConsensusError(error)
+    }
+}
+
 impl From<HandshakeError> for ServerError {
     fn from(error: HandshakeError) -> Self {
         ServerError::HandshakeError(error)
@@ -14,14 +14,28 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::{algorithms::CRHError, consensus::ConsensusError, storage::StorageError};
+
 #[derive(Debug, Error)]
 pub enum CliError {
     #[error("{}: {}", _0, _1)]
     Crate(&'static str, String),
 
+    #[error("ConsensusError: {0}")]
+    ConsensusError(#[from] ConsensusError),
+
+    #[error("CRHError: {0}")]
+    CRHError(#[from] CRHError),
+
     #[error("IoError: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("{}", _0)]
+    Message(String),
+
+    #[error("StorageError: {0}")]
+    StorageError(#[from] StorageError),
+
     #[error("TomlSerError: {0}")]
     TomlSerError(#[from] toml::ser::Error),
 
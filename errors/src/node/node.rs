@@ -17,6 +17,7 @@
 use crate::{
     algorithms::CRHError,
     consensus::ConsensusError,
+    dpc::LedgerError,
     network::ServerError,
     node::CliError,
     objects::AccountError,
@@ -40,6 +41,9 @@ pub enum NodeError {
     #[error("{}: {}", _0, _1)]
     Crate(&'static str, String),
 
+    #[error("{}", _0)]
+    LedgerError(LedgerError),
+
     #[error("{}", _0)]
     Message(String),
 
@@ -80,6 +84,12 @@ impl From<hex::FromHexError> for NodeError {
     }
 }
 
+impl From<LedgerError> for NodeError {
+    fn from(error: LedgerError) -> Self {
+        NodeError::LedgerError(error)
+    }
+}
+
 impl From<ServerError> for NodeError {
     fn from(error: ServerError) -> Self {
         NodeError::ServerError(error)
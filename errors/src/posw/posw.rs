@@ -26,6 +26,10 @@ pub enum PoswError {
     #[error("could not load PoSW parameters: {0}")]
     Parameters(#[from] ParametersError),
 
+    /// Thrown when mining is cancelled before a proof is found
+    #[error("mining was cancelled")]
+    MiningCancelled,
+
     /// Thrown when a proof fails verification
     #[error("could not verify PoSW")]
     PoswVerificationFailed,
@@ -18,7 +18,7 @@ use crate::{
     algorithms::CRHError,
     consensus::ConsensusError,
     dpc::DPCError,
-    network::SendError,
+    network::{SendError, ServerError},
     objects::{AccountError, BlockError, TransactionError},
     storage::StorageError,
 };
@@ -57,6 +57,9 @@ pub enum RpcError {
     #[error("{}", _0)]
     SendError(SendError),
 
+    #[error("{}", _0)]
+    ServerError(ServerError),
+
     #[error("{}", _0)]
     StorageError(StorageError),
 
@@ -100,6 +103,12 @@ impl From<SendError> for RpcError {
     }
 }
 
+impl From<ServerError> for RpcError {
+    fn from(error: ServerError) -> Self {
+        RpcError::ServerError(error)
+    }
+}
+
 impl From<StorageError> for RpcError {
     fn from(error: StorageError) -> Self {
         RpcError::StorageError(error)
@@ -130,6 +139,12 @@ impl From<std::io::Error> for RpcError {
     }
 }
 
+impl From<std::net::AddrParseError> for RpcError {
+    fn from(error: std::net::AddrParseError) -> Self {
+        RpcError::Crate("std::net::AddrParseError", format!("{:?}", error))
+    }
+}
+
 impl From<&'static str> for RpcError {
     fn from(msg: &'static str) -> Self {
         RpcError::Message(msg.into())
@@ -49,4 +49,19 @@ pub trait SNARK {
         input: &Self::VerifierInput,
         proof: &Self::Proof,
     ) -> Result<bool, SNARKError>;
+
+    /// Returns true iff all the proofs verify against the same verification key.
+    /// Implementations may check the proofs together, which is cheaper than verifying each on its own.
+    fn batch_verify(
+        verifier_key: &Self::PreparedVerificationParameters,
+        inputs_and_proofs: &[(&Self::VerifierInput, &Self::Proof)],
+    ) -> Result<bool, SNARKError> {
+        for (input, proof) in inputs_and_proofs {
+            if !Self::verify(verifier_key, input, proof)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
 }
@@ -0,0 +1,238 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::{message::MessageName, message_types::SyncBlock, Message};
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+use tokio::time::{delay_for, Instant};
+
+/// The name under which the bytes of messages with an unknown name are accounted.
+pub const UNKNOWN_MESSAGE: &str = "unknown";
+
+/// Stores the number of bytes sent and received per message name.
+/// Messages with an unknown name share a single bucket, so peers can't grow the maps with arbitrary names.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Traffic {
+    /// Bytes sent per message name
+    pub sent: HashMap<String, u64>,
+
+    /// Bytes received per message name
+    pub received: HashMap<String, u64>,
+}
+
+impl Traffic {
+    /// Accounts for `len` bytes sent in a message with the given name.
+    pub fn record_sent(&mut self, name: &MessageName, len: u64) {
+        *self.sent.entry(name.to_string()).or_insert(0) += len;
+    }
+
+    /// Accounts for `len` bytes received in a message with the given name.
+    pub fn record_received(&mut self, name: &MessageName, len: u64) {
+        *self.received.entry(name.to_string()).or_insert(0) += len;
+    }
+
+    /// Accounts for `len` bytes received in a message with an unknown name.
+    pub fn record_received_unknown(&mut self, len: u64) {
+        *self.received.entry(UNKNOWN_MESSAGE.to_string()).or_insert(0) += len;
+    }
+
+    /// Returns the total number of bytes sent.
+    pub fn total_sent(&self) -> u64 {
+        self.sent.values().sum()
+    }
+
+    /// Returns the total number of bytes received.
+    pub fn total_received(&self) -> u64 {
+        self.received.values().sum()
+    }
+}
+
+/// The maximum rates of bytes per second a node sends and receives. A rate of `None` or zero is unlimited.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RateLimits {
+    /// Bytes per second received from all peers
+    pub inbound: Option<u64>,
+
+    /// Bytes per second sent to all peers, except for blocks served to syncing peers
+    pub outbound: Option<u64>,
+
+    /// Bytes per second of blocks served to syncing peers
+    pub block_serving: Option<u64>,
+}
+
+/// A token bucket refilled at a fixed rate of bytes per second, holding up to one second of tokens.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Construct a new full `TokenBucket` with the given rate of bytes per second.
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes `len` tokens from the bucket, and returns the time to wait until they were refilled.
+    /// The bucket may go into debt, so messages larger than a second of tokens are delayed instead of blocked.
+    pub fn take(&mut self, len: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.tokens -= len as f64;
+        self.last_refill = now;
+
+        match self.tokens < 0.0 && self.rate > 0 {
+            true => Duration::from_secs_f64(-self.tokens / self.rate as f64),
+            false => Duration::from_secs(0),
+        }
+    }
+}
+
+/// Accounts for the bytes a node sends to and receives from all its peers,
+/// and throttles its traffic to the configured rate limits.
+#[derive(Debug, Default)]
+pub struct Bandwidth {
+    traffic: Mutex<Traffic>,
+    inbound: Mutex<Option<TokenBucket>>,
+    outbound: Mutex<Option<TokenBucket>>,
+    block_serving: Mutex<Option<TokenBucket>>,
+}
+
+impl Bandwidth {
+    /// Construct a new `Bandwidth` throttled to the given rate limits.
+    pub fn new(limits: RateLimits) -> Self {
+        let bandwidth = Self::default();
+        bandwidth.set_limits(limits);
+        bandwidth
+    }
+
+    /// Replaces the rate limits.
+    pub fn set_limits(&self, limits: RateLimits) {
+        *self.inbound.lock().unwrap() = limits.inbound.map(TokenBucket::new);
+        *self.outbound.lock().unwrap() = limits.outbound.map(TokenBucket::new);
+        *self.block_serving.lock().unwrap() = limits.block_serving.map(TokenBucket::new);
+    }
+
+    /// Returns a copy of the bytes sent and received per message name.
+    pub fn traffic(&self) -> Traffic {
+        self.traffic.lock().unwrap().clone()
+    }
+
+    /// Waits until the outbound rate limit allows sending a message of `len` bytes.
+    /// Blocks served to syncing peers are throttled by their own rate limit.
+    pub async fn throttle_send(&self, name: &MessageName, len: u64) {
+        let bucket = match *name == SyncBlock::name() {
+            true => &self.block_serving,
+            false => &self.outbound,
+        };
+
+        Self::throttle(bucket, len).await;
+    }
+
+    /// Waits until the inbound rate limit allows reading a message of `len` bytes.
+    pub async fn throttle_receive(&self, len: u64) {
+        Self::throttle(&self.inbound, len).await;
+    }
+
+    /// Accounts for `len` bytes sent in a message with the given name.
+    pub fn record_sent(&self, name: &MessageName, len: u64) {
+        self.traffic.lock().unwrap().record_sent(name, len);
+    }
+
+    /// Accounts for `len` bytes received in a message with the given name.
+    pub fn record_received(&self, name: &MessageName, len: u64) {
+        self.traffic.lock().unwrap().record_received(name, len);
+    }
+
+    /// Accounts for `len` bytes received in a message with an unknown name.
+    pub fn record_received_unknown(&self, len: u64) {
+        self.traffic.lock().unwrap().record_received_unknown(len);
+    }
+
+    async fn throttle(bucket: &Mutex<Option<TokenBucket>>, len: u64) {
+        let delay = match &mut *bucket.lock().unwrap() {
+            Some(bucket) => bucket.take(len),
+            None => return,
+        };
+
+        if delay > Duration::from_secs(0) {
+            delay_for(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::message_types::{Block, Ping};
+
+    #[test]
+    fn test_traffic() {
+        let bandwidth = Bandwidth::default();
+
+        bandwidth.record_sent(&Ping::name(), 10);
+        bandwidth.record_sent(&Ping::name(), 10);
+        bandwidth.record_sent(&Block::name(), 100);
+        bandwidth.record_received(&Ping::name(), 5);
+        bandwidth.record_received_unknown(24);
+        bandwidth.record_received_unknown(24);
+
+        let traffic = bandwidth.traffic();
+        assert_eq!(Some(&20), traffic.sent.get(&Ping::name().to_string()));
+        assert_eq!(Some(&48), traffic.received.get(UNKNOWN_MESSAGE));
+        assert_eq!(120, traffic.total_sent());
+        assert_eq!(53, traffic.total_received());
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket() {
+        tokio::time::pause();
+
+        let mut bucket = TokenBucket::new(1000);
+
+        assert_eq!(Duration::from_secs(0), bucket.take(1000));
+        assert_eq!(Duration::from_millis(500), bucket.take(500));
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+
+        assert_eq!(Duration::from_secs(0), bucket.take(500));
+    }
+
+    #[tokio::test]
+    async fn test_block_serving_limit() {
+        let bandwidth = Bandwidth::new(RateLimits {
+            inbound: None,
+            outbound: Some(1000),
+            block_serving: Some(100),
+        });
+
+        // The blocks served to syncing peers don't use up the outbound rate limit.
+        let start = Instant::now();
+        bandwidth.throttle_send(&SyncBlock::name(), 100).await;
+        bandwidth.throttle_send(&Ping::name(), 1000).await;
+        assert!(start.elapsed() < Duration::from_millis(250));
+
+        bandwidth.throttle_send(&SyncBlock::name(), 50).await;
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+}
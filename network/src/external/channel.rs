@@ -14,60 +14,194 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::external::message::{
-    read::{read_header, read_message},
-    Message,
-    MessageHeader,
-    MessageName,
+use crate::external::{
+    message::{
+        read::{read_frame_header, read_message},
+        verify_checksum,
+        FrameHeader,
+        InboundBudget,
+        Message,
+        MessageHeader,
+        MessageLimits,
+        MessageName,
+        NetworkMagic,
+    },
+    read_preamble,
+    Bandwidth,
+    Encryption,
+    NoiseReader,
+    NoiseSession,
+    Stream,
+    Transport,
+};
+use snarkos_errors::network::{message::MessageError, ConnectError};
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    io::{AsyncRead, AsyncWriteExt},
+    net::TcpStream,
+    sync::{Mutex, Notify},
 };
-use snarkos_errors::network::ConnectError;
-
-use std::{net::SocketAddr, sync::Arc};
-use tokio::{io::AsyncWriteExt, net::TcpStream, sync::Mutex};
 
 /// A channel for reading and writing messages to a peer.
 /// The channel manages two streams to allow for simultaneous reading and writing.
 /// Each stream is protected by an Arc + Mutex to allow for channel cloning.
+/// The number of bytes sent and received, the inbound byte budget, the negotiated wire format
+/// and the close signal are shared by all clones of the channel.
+///
+/// The bytes of each message are accounted per message name in the `bandwidth` shared by all
+/// channels of a node, which throttles them to its rate limits.
+///
+/// Messages are written with legacy headers until the peer advertises a protocol version supporting
+/// framed messages. Both wire formats are accepted when reading.
+///
+/// Each stream may carry a noise session established when it was connected, in which case all
+/// messages sent or received on it are encrypted.
 #[derive(Clone, Debug)]
 pub struct Channel {
     pub address: SocketAddr,
-    pub reader: Arc<Mutex<TcpStream>>,
-    pub writer: Arc<Mutex<TcpStream>>,
+    pub reader: Arc<Mutex<Stream>>,
+    pub writer: Arc<Mutex<Stream>>,
+    pub bytes_sent: Arc<AtomicU64>,
+    pub bytes_received: Arc<AtomicU64>,
+    pub inbound_budget: Arc<Mutex<InboundBudget>>,
+    pub bandwidth: Arc<Bandwidth>,
+    pub magic: NetworkMagic,
+    pub framed: Arc<AtomicBool>,
+    pub reader_session: Option<Arc<NoiseSession>>,
+    pub writer_session: Option<Arc<NoiseSession>>,
+    pub close_signal: Arc<Notify>,
 }
 
 impl Channel {
     pub async fn new(
         address: SocketAddr,
-        reader: Arc<Mutex<TcpStream>>,
-        writer: Arc<Mutex<TcpStream>>,
+        reader: Arc<Mutex<Stream>>,
+        writer: Arc<Mutex<Stream>>,
     ) -> Result<Self, ConnectError> {
         Ok(Self {
             address,
             reader,
             writer,
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            inbound_budget: Default::default(),
+            bandwidth: Default::default(),
+            magic: Default::default(),
+            framed: Default::default(),
+            reader_session: None,
+            writer_session: None,
+            close_signal: Default::default(),
         })
     }
 
     /// Returns a new channel with a writer only stream.
     pub async fn new_write_only(address: SocketAddr) -> Result<Self, ConnectError> {
-        let stream = Arc::new(Mutex::new(TcpStream::connect(address).await?));
+        let stream = Arc::new(Mutex::new(Stream::from(TcpStream::connect(address).await?)));
+
+        Ok(Self {
+            address,
+            reader: stream.clone(),
+            writer: stream,
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            inbound_budget: Default::default(),
+            bandwidth: Default::default(),
+            magic: Default::default(),
+            framed: Default::default(),
+            reader_session: None,
+            writer_session: None,
+            close_signal: Default::default(),
+        })
+    }
+
+    /// Returns a new channel with a writer only stream connected over the given transport.
+    /// The stream is encrypted if the encryption settings contain a static keypair.
+    pub async fn connect(
+        address: SocketAddr,
+        transport: &Transport,
+        encryption: &Encryption,
+    ) -> Result<Self, ConnectError> {
+        let mut stream = transport.connect(address).await?;
+
+        let session = match &encryption.keypair {
+            Some(keypair) => Some(Arc::new(NoiseSession::initiate(&mut stream, keypair).await?)),
+            None => None,
+        };
+
+        let stream = Arc::new(Mutex::new(stream));
 
         Ok(Self {
             address,
             reader: stream.clone(),
             writer: stream,
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            inbound_budget: Default::default(),
+            bandwidth: Default::default(),
+            magic: Default::default(),
+            framed: Default::default(),
+            reader_session: session.clone(),
+            writer_session: session,
+            close_signal: Default::default(),
         })
     }
 
     /// Returns a new channel with a reader only stream.
     pub fn new_read_only(reader: TcpStream) -> Result<Self, ConnectError> {
         let address = reader.peer_addr()?;
-        let stream = Arc::new(Mutex::new(reader));
+        let stream = Arc::new(Mutex::new(Stream::from(reader)));
 
         Ok(Self {
             address,
             reader: stream.clone(),
             writer: stream.clone(),
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            inbound_budget: Default::default(),
+            bandwidth: Default::default(),
+            magic: Default::default(),
+            framed: Default::default(),
+            reader_session: None,
+            writer_session: None,
+            close_signal: Default::default(),
+        })
+    }
+
+    /// Returns a new channel with a reader only stream accepted from a peer.
+    /// Completes the noise handshake if the peer requests an encrypted connection.
+    /// Unencrypted connections are rejected if encryption is required.
+    pub async fn accept(mut reader: Stream, encryption: &Encryption) -> Result<Self, ConnectError> {
+        let address = reader.peer_addr()?;
+
+        let session = match (read_preamble(&mut reader).await?, &encryption.keypair) {
+            (true, Some(keypair)) => Some(Arc::new(NoiseSession::respond(&mut reader, keypair).await?)),
+            (true, None) => return Err(ConnectError::EncryptionUnavailable(address)),
+            (false, _) if encryption.required => return Err(ConnectError::EncryptionRequired(address)),
+            (false, _) => None,
+        };
+
+        let stream = Arc::new(Mutex::new(reader));
+
+        Ok(Self {
+            address,
+            reader: stream.clone(),
+            writer: stream,
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            inbound_budget: Default::default(),
+            bandwidth: Default::default(),
+            magic: Default::default(),
+            framed: Default::default(),
+            reader_session: session.clone(),
+            writer_session: session,
+            close_signal: Default::default(),
         })
     }
 
@@ -77,44 +211,183 @@ impl Channel {
             address,
             reader: self.reader.clone(),
             writer: self.writer.clone(),
+            bytes_sent: self.bytes_sent.clone(),
+            bytes_received: self.bytes_received.clone(),
+            inbound_budget: self.inbound_budget.clone(),
+            bandwidth: self.bandwidth.clone(),
+            magic: self.magic,
+            framed: self.framed.clone(),
+            reader_session: self.reader_session.clone(),
+            writer_session: self.writer_session.clone(),
+            close_signal: self.close_signal.clone(),
         }
     }
 
     /// Returns a new channel with the specified reader stream.
-    pub fn update_reader(&self, reader: Arc<Mutex<TcpStream>>) -> Self {
+    pub fn update_reader(&self, reader: Arc<Mutex<Stream>>) -> Self {
         Self {
             address: self.address,
             reader,
             writer: self.writer.clone(),
+            bytes_sent: self.bytes_sent.clone(),
+            bytes_received: self.bytes_received.clone(),
+            inbound_budget: self.inbound_budget.clone(),
+            bandwidth: self.bandwidth.clone(),
+            magic: self.magic,
+            framed: self.framed.clone(),
+            reader_session: None,
+            writer_session: self.writer_session.clone(),
+            close_signal: self.close_signal.clone(),
+        }
+    }
+
+    /// Returns a new channel with the reader stream of the specified channel.
+    pub fn update_reader_from(&self, channel: &Channel) -> Self {
+        Self {
+            address: self.address,
+            reader: channel.reader.clone(),
+            writer: self.writer.clone(),
+            bytes_sent: self.bytes_sent.clone(),
+            bytes_received: self.bytes_received.clone(),
+            inbound_budget: self.inbound_budget.clone(),
+            bandwidth: self.bandwidth.clone(),
+            magic: self.magic,
+            framed: self.framed.clone(),
+            reader_session: channel.reader_session.clone(),
+            writer_session: self.writer_session.clone(),
+            close_signal: self.close_signal.clone(),
         }
     }
 
     /// Returns a new channel with the specified address and new writer stream.
     pub async fn update_writer(&self, address: SocketAddr) -> Result<Self, ConnectError> {
+        self.connect_writer(address, &Transport::Tcp, &Encryption::default()).await
+    }
+
+    /// Returns a new channel with the specified address and new writer stream connected over the given transport.
+    /// The writer stream is encrypted if the encryption settings contain a static keypair.
+    pub async fn connect_writer(
+        &self,
+        address: SocketAddr,
+        transport: &Transport,
+        encryption: &Encryption,
+    ) -> Result<Self, ConnectError> {
+        let writer = Self::connect(address, transport, encryption).await?;
+
         Ok(Self {
             address,
             reader: self.reader.clone(),
-            writer: Arc::new(Mutex::new(TcpStream::connect(address).await?)),
+            writer: writer.writer,
+            bytes_sent: self.bytes_sent.clone(),
+            bytes_received: self.bytes_received.clone(),
+            inbound_budget: self.inbound_budget.clone(),
+            bandwidth: self.bandwidth.clone(),
+            magic: self.magic,
+            framed: self.framed.clone(),
+            reader_session: self.reader_session.clone(),
+            writer_session: writer.writer_session,
+            close_signal: self.close_signal.clone(),
         })
     }
 
+    /// Returns a new channel sending and accepting framed messages of the network with the given id.
+    pub fn with_network(self, network_id: u8) -> Self {
+        Self {
+            magic: NetworkMagic::new(network_id),
+            ..self
+        }
+    }
+
+    /// Returns a new channel accounting its traffic in, and throttled by, the given node bandwidth.
+    pub fn with_bandwidth(self, bandwidth: Arc<Bandwidth>) -> Self {
+        Self { bandwidth, ..self }
+    }
+
+    /// Signals the connection thread reading from the channel to stop, once the peer is disconnected.
+    pub fn close(&self) {
+        self.close_signal.notify();
+    }
+
+    /// Resolves once the channel is closed.
+    pub async fn closed(&self) {
+        self.close_signal.notified().await
+    }
+
+    /// Writes all following messages as framed messages.
+    /// Should only be called once the peer advertised a protocol version supporting them.
+    pub fn enable_framing(&self) {
+        self.framed.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if messages are written as framed messages.
+    pub fn is_framed(&self) -> bool {
+        self.framed.load(Ordering::SeqCst)
+    }
+
     /// Writes a message header + message.
     pub async fn write<M: Message>(&self, message: &M) -> Result<(), ConnectError> {
         debug!("Message {:?}, Sent to {:?}", M::name().to_string(), self.address);
 
         let serialized = message.serialize()?;
-        let header = MessageHeader::new(M::name(), serialized.len() as u32);
+        let header = match self.is_framed() {
+            true => FrameHeader::new(self.magic, M::name(), &serialized).serialize()?,
+            false => MessageHeader::new(M::name(), serialized.len() as u32).serialize()?,
+        };
+
+        // The header and payload are flushed at once, so a memory stream sends them as a single message.
+        let bytes = [&header[..], &serialized[..]].concat();
+        let len = bytes.len() as u64;
+
+        self.bandwidth.throttle_send(&M::name(), len).await;
 
         let mut writer = self.writer.lock().await;
-        writer.write_all(&header.serialize()?).await?;
-        writer.write_all(&serialized).await?;
+        match &self.writer_session {
+            Some(session) => writer.write_all(&session.encrypt(&bytes)?).await?,
+            None => writer.write_all(&bytes).await?,
+        }
+        writer.flush().await?;
+
+        self.bytes_sent.fetch_add(len, Ordering::Relaxed);
+        self.bandwidth.record_sent(&M::name(), len);
 
         Ok(())
     }
 
-    /// Reads a message header + message.
+    /// Reads a message header + message within the default message limits.
     pub async fn read(&self) -> Result<(MessageName, Vec<u8>), ConnectError> {
-        let header = read_header(&mut *self.reader.lock().await).await?;
+        self.read_bounded(&MessageLimits::default()).await
+    }
+
+    /// Reads a message header + message.
+    /// Messages with an unknown name, a payload exceeding its maximum size, or a payload exceeding
+    /// the inbound byte budget of the channel are rejected before the payload is read.
+    /// Framed messages from another network are rejected, as are framed payloads with an invalid checksum.
+    pub async fn read_bounded(&self, limits: &MessageLimits) -> Result<(MessageName, Vec<u8>), ConnectError> {
+        let mut reader = self.reader.lock().await;
+
+        match &self.reader_session {
+            Some(session) => {
+                let mut reader = NoiseReader {
+                    stream: &mut *reader,
+                    session,
+                };
+                self.read_from(&mut reader, limits).await
+            }
+            None => self.read_from(&mut *reader, limits).await,
+        }
+    }
+
+    /// Reads a message header + message from the given decrypted stream.
+    async fn read_from<T: AsyncRead + Unpin>(
+        &self,
+        reader: &mut T,
+        limits: &MessageLimits,
+    ) -> Result<(MessageName, Vec<u8>), ConnectError> {
+        let (header, checksum) = read_frame_header(reader, self.magic).await?;
+        let header_size = match checksum {
+            Some(_) => FrameHeader::size(),
+            None => MessageHeader::size(),
+        };
 
         debug!(
             "Message {:?}, Received from {:?}",
@@ -122,10 +395,34 @@ impl Channel {
             self.address
         );
 
-        Ok((
-            header.name,
-            read_message(&mut *self.reader.lock().await, header.len as usize).await?,
-        ))
+        let max_len = match limits.max_payload_size(&header.name) {
+            Some(max_len) => max_len,
+            None => {
+                // Only the headers of unknown messages are read, and they are accounted in a single bucket.
+                self.bytes_received.fetch_add(header_size as u64, Ordering::Relaxed);
+                self.bandwidth.record_received_unknown(header_size as u64);
+                return Err(MessageError::UnknownMessage(header.name.to_string()).into());
+            }
+        };
+
+        let len = (header_size + header.len as usize) as u64;
+        if !self.inbound_budget.lock().await.consume(len, limits.inbound_byte_budget) {
+            return Err(MessageError::InboundBudgetExceeded(limits.inbound_byte_budget).into());
+        }
+
+        self.bandwidth.throttle_receive(len).await;
+
+        let message = read_message(reader, header.len as usize, max_len).await?;
+
+        let len = (header_size + message.len()) as u64;
+        self.bytes_received.fetch_add(len, Ordering::Relaxed);
+        self.bandwidth.record_received(&header.name, len);
+
+        if let Some(checksum) = checksum {
+            verify_checksum(&message, checksum)?;
+        }
+
+        Ok((header.name, message))
     }
 }
 
@@ -134,6 +431,7 @@ mod tests {
     use crate::external::message_types::{Ping, Pong};
 
     use super::*;
+    use snarkos_errors::network::message::MessageHeaderError;
     use snarkos_testing::network::{random_socket_address, simulate_active_node};
 
     use serial_test::serial;
@@ -185,6 +483,59 @@ mod tests {
         assert!(Ping::deserialize(bytes).is_ok());
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_read_framed() {
+        let remote_address = random_socket_address();
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        tokio::spawn(async move {
+            // 1. Server connects to peer on the test network
+
+            let server_channel = Channel::new_write_only(remote_address).await.unwrap().with_network(1);
+            server_channel.enable_framing();
+
+            // 2. Server writes framed ping message
+
+            server_channel.write(&Ping::new()).await.unwrap();
+        });
+
+        // 3. Peer on the test network reads the framed ping message
+
+        let (reader, _address) = remote_listener.accept().await.unwrap();
+        let peer_channel = Channel::new_read_only(reader).unwrap().with_network(1);
+
+        let (name, bytes) = peer_channel.read().await.unwrap();
+
+        assert_eq!(Ping::name(), name);
+        assert!(Ping::deserialize(bytes).is_ok());
+        assert_eq!(
+            (FrameHeader::size() + 8) as u64,
+            peer_channel.bytes_received.load(Ordering::Relaxed)
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reject_framed_from_other_network() {
+        let remote_address = random_socket_address();
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        tokio::spawn(async move {
+            let server_channel = Channel::new_write_only(remote_address).await.unwrap().with_network(1);
+            server_channel.enable_framing();
+            server_channel.write(&Ping::new()).await.unwrap();
+        });
+
+        let (reader, _address) = remote_listener.accept().await.unwrap();
+        let peer_channel = Channel::new_read_only(reader).unwrap();
+
+        match peer_channel.read().await {
+            Err(ConnectError::MessageHeaderError(MessageHeaderError::NetworkMismatch(1, 0))) => {}
+            result => panic!("expected the message to be rejected, got {:?}", result),
+        }
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_channel_update() {
@@ -209,7 +560,7 @@ mod tests {
 
             let (reader, _socket) = server_listener.accept().await.unwrap();
 
-            channel = channel.update_reader(Arc::new(Mutex::new(reader)));
+            channel = channel.update_reader(Arc::new(Mutex::new(reader.into())));
 
             // 5. Local node writes ping
 
@@ -0,0 +1,205 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::MessageName;
+use snarkos_algorithms::crh::double_sha256;
+use snarkos_errors::network::message::MessageHeaderError;
+use snarkos_objects::Network;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::Cursor;
+
+/// The leading bytes of every network magic.
+/// Legacy message headers start with an ASCII message name, so they never begin with this prefix.
+pub const MAGIC_PREFIX: [u8; 3] = [0xd9, 0xb4, 0xbe];
+
+/// The four bytes that start every framed message, identifying the network it was sent on.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct NetworkMagic([u8; 4]);
+
+impl NetworkMagic {
+    /// Construct the `NetworkMagic` of the network with the given id.
+    pub fn new(network_id: u8) -> Self {
+        NetworkMagic([MAGIC_PREFIX[0], MAGIC_PREFIX[1], MAGIC_PREFIX[2], network_id])
+    }
+
+    /// Returns the `NetworkMagic` in the given bytes, or None if they don't start with the magic prefix.
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<Self> {
+        if bytes[..3] == MAGIC_PREFIX {
+            Some(NetworkMagic(bytes))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the network the magic belongs to.
+    pub fn network_id(&self) -> u8 {
+        self.0[3]
+    }
+
+    pub fn as_bytes(&self) -> [u8; 4] {
+        self.0
+    }
+}
+
+impl From<Network> for NetworkMagic {
+    fn from(network: Network) -> Self {
+        Self::new(network.id())
+    }
+}
+
+impl Default for NetworkMagic {
+    fn default() -> Self {
+        Self::from(Network::Mainnet)
+    }
+}
+
+/// Returns the checksum of a message payload, the first four bytes of its double sha256 hash.
+pub fn checksum(payload: &[u8]) -> u32 {
+    let hash = double_sha256(payload);
+
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&hash[..4]);
+
+    u32::from_be_bytes(bytes)
+}
+
+/// Returns an error if the checksum of the payload does not match the expected checksum.
+pub fn verify_checksum(payload: &[u8], expected: u32) -> Result<(), MessageHeaderError> {
+    let actual = checksum(payload);
+
+    if actual != expected {
+        return Err(MessageHeaderError::InvalidChecksum(actual, expected));
+    }
+
+    Ok(())
+}
+
+/// The header of a framed message.
+/// Carries the network magic and a payload checksum in addition to the message name and length.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub magic: NetworkMagic,
+    pub name: MessageName,
+    pub len: u32,
+    pub checksum: u32,
+}
+
+impl FrameHeader {
+    /// Construct a new `FrameHeader` for the given payload.
+    pub fn new(magic: NetworkMagic, name: MessageName, payload: &[u8]) -> Self {
+        Self {
+            magic,
+            name,
+            len: payload.len() as u32,
+            checksum: checksum(payload),
+        }
+    }
+
+    /// Returns the size of a serialized frame header in bytes.
+    pub const fn size() -> usize {
+        24
+    }
+
+    /// Returns an error if the checksum of the payload does not match the checksum of the frame.
+    pub fn verify(&self, payload: &[u8]) -> Result<(), MessageHeaderError> {
+        verify_checksum(payload, self.checksum)
+    }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, MessageHeaderError> {
+        let mut result = vec![];
+        result.extend_from_slice(&self.magic.as_bytes());
+        result.extend_from_slice(&self.name.as_bytes());
+        result.write_u32::<BigEndian>(self.len)?;
+        result.write_u32::<BigEndian>(self.checksum)?;
+
+        Ok(result)
+    }
+
+    /// Deserializes a frame header sent on the network with the given magic.
+    pub fn deserialize(vec: Vec<u8>, expected_magic: NetworkMagic) -> Result<Self, MessageHeaderError> {
+        if vec.len() != Self::size() {
+            return Err(MessageHeaderError::InvalidFrameLength(vec.len()));
+        }
+
+        let mut magic = [0u8; 4];
+        magic.copy_from_slice(&vec[..4]);
+
+        let magic = match NetworkMagic::from_bytes(magic) {
+            Some(magic) if magic == expected_magic => magic,
+            Some(magic) => {
+                return Err(MessageHeaderError::NetworkMismatch(
+                    magic.network_id(),
+                    expected_magic.network_id(),
+                ));
+            }
+            None => return Err(MessageHeaderError::InvalidMagic(magic)),
+        };
+
+        let mut name_bytes = [0u8; 12];
+        name_bytes.copy_from_slice(&vec[4..16]);
+
+        let mut rdr = Cursor::new(&vec[16..]);
+
+        Ok(Self {
+            magic,
+            name: MessageName::from(name_bytes),
+            len: rdr.read_u32::<BigEndian>()?,
+            checksum: rdr.read_u32::<BigEndian>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::message::MessageHeader;
+
+    #[test]
+    fn test_frame_header() {
+        let magic = NetworkMagic::from(Network::Testnet1);
+        let header = FrameHeader::new(magic, MessageName::from("ping"), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let serialized = header.serialize().unwrap();
+        assert_eq!(FrameHeader::size(), serialized.len());
+        assert_eq!(&[0xd9, 0xb4, 0xbe, 1], &serialized[..4]);
+
+        assert_eq!(header, FrameHeader::deserialize(serialized, magic).unwrap());
+        assert!(header.verify(&[1, 2, 3, 4, 5, 6, 7, 8]).is_ok());
+        assert!(header.verify(&[1, 2, 3, 4, 5, 6, 7, 9]).is_err());
+    }
+
+    #[test]
+    fn test_frame_header_network_mismatch() {
+        let header = FrameHeader::new(NetworkMagic::from(Network::Testnet1), MessageName::from("ping"), &[]);
+        let serialized = header.serialize().unwrap();
+
+        match FrameHeader::deserialize(serialized, NetworkMagic::from(Network::Mainnet)) {
+            Err(MessageHeaderError::NetworkMismatch(1, 0)) => {}
+            result => panic!("expected a network mismatch, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_legacy_header_is_not_magic() {
+        let legacy = MessageHeader::new(MessageName::from("ping"), 8).serialize().unwrap();
+
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&legacy[..4]);
+
+        assert_eq!(None, NetworkMagic::from_bytes(bytes));
+    }
+}
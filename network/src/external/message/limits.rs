@@ -0,0 +1,186 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::{
+    message::{Message, MessageName},
+    message_types::*,
+};
+
+use snarkos_objects::BlockHeader;
+
+use std::time::{Duration, Instant};
+
+/// The maximum block size used when no consensus parameters are available.
+pub const DEFAULT_MAX_BLOCK_SIZE: usize = 1_000_000;
+
+/// The maximum number of block hashes sent in a single `Sync` message.
+pub const MAX_SYNC_BLOCK_HASHES: u32 = 4000;
+
+/// The maximum number of block headers sent in a single `Headers` message.
+pub const MAX_HEADERS: u32 = 2000;
+
+/// The maximum number of items sent in a single `Inv`, `GetData` or `NotFound` message.
+pub const MAX_INVENTORY: usize = 50_000;
+
+/// The maximum payload size of the messages with a small, fixed layout.
+pub const MAX_FIXED_MESSAGE_SIZE: usize = 1024;
+
+/// The maximum serialized size of a single address in a `Peers` message.
+pub const MAX_PEER_ENTRY_SIZE: usize = 64;
+
+/// The number of maximum sized blocks a connection may receive per budget interval.
+pub const INBOUND_BUDGET_BLOCKS: u64 = 64;
+
+/// The number of seconds after which the inbound byte budget of a connection is renewed.
+pub const INBOUND_BUDGET_INTERVAL_SECS: u64 = 10;
+
+/// The size of the length prefix bincode writes before a vector.
+const LENGTH_PREFIX_SIZE: usize = 8;
+
+/// The size of a serialized block header hash.
+const BLOCK_HASH_SIZE: usize = 32;
+
+/// The maximum serialized size of an inventory item, the variant index and a transaction id or block hash.
+const MAX_INVENTORY_ITEM_SIZE: usize = 4 + LENGTH_PREFIX_SIZE + BLOCK_HASH_SIZE;
+
+/// The maximum payload sizes of the messages accepted from peers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MessageLimits {
+    /// The maximum size of a block
+    pub max_block_size: usize,
+
+    /// The number of bytes a connection may receive per budget interval
+    pub inbound_byte_budget: u64,
+}
+
+impl MessageLimits {
+    /// Construct new `MessageLimits` for the given maximum block size.
+    pub fn new(max_block_size: usize) -> Self {
+        Self {
+            max_block_size,
+            inbound_byte_budget: max_block_size as u64 * INBOUND_BUDGET_BLOCKS,
+        }
+    }
+
+    /// Returns the maximum payload size of a message, or None if the message name is unknown.
+    pub fn max_payload_size(&self, name: &MessageName) -> Option<usize> {
+        let max_size = if *name == Block::name()
+            || *name == SyncBlock::name()
+            || *name == CompactBlock::name()
+            || *name == GetBlockTxn::name()
+            || *name == BlockTxn::name()
+            || *name == Transaction::name()
+            || *name == MemoryPool::name()
+        {
+            LENGTH_PREFIX_SIZE + self.max_block_size
+        } else if *name == GetSync::name() || *name == Sync::name() || *name == GetHeaders::name() {
+            LENGTH_PREFIX_SIZE + MAX_SYNC_BLOCK_HASHES as usize * BLOCK_HASH_SIZE
+        } else if *name == Headers::name() {
+            LENGTH_PREFIX_SIZE + MAX_HEADERS as usize * BlockHeader::size()
+        } else if *name == Inv::name() || *name == GetData::name() || *name == NotFound::name() {
+            LENGTH_PREFIX_SIZE + MAX_INVENTORY * MAX_INVENTORY_ITEM_SIZE
+        } else if *name == Peers::name() {
+            LENGTH_PREFIX_SIZE + u16::MAX as usize * MAX_PEER_ENTRY_SIZE
+        } else if *name == GetBlock::name()
+            || *name == GetMemoryPool::name()
+            || *name == GetPeers::name()
+            || *name == Ping::name()
+            || *name == Pong::name()
+            || *name == Verack::name()
+            || *name == Version::name()
+        {
+            MAX_FIXED_MESSAGE_SIZE
+        } else {
+            return None;
+        };
+
+        Some(max_size)
+    }
+}
+
+impl Default for MessageLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BLOCK_SIZE)
+    }
+}
+
+/// Tracks the number of bytes a connection received in the current budget interval.
+#[derive(Debug)]
+pub struct InboundBudget {
+    interval_start: Instant,
+    bytes_received: u64,
+}
+
+impl InboundBudget {
+    /// Construct a new `InboundBudget` starting a new interval.
+    pub fn new() -> Self {
+        Self {
+            interval_start: Instant::now(),
+            bytes_received: 0,
+        }
+    }
+
+    /// Accounts for `len` incoming bytes.
+    /// Returns false if they exceed the budget of the current interval.
+    pub fn consume(&mut self, len: u64, budget: u64) -> bool {
+        if self.interval_start.elapsed() >= Duration::from_secs(INBOUND_BUDGET_INTERVAL_SECS) {
+            self.interval_start = Instant::now();
+            self.bytes_received = 0;
+        }
+
+        if self.bytes_received + len > budget {
+            return false;
+        }
+
+        self.bytes_received += len;
+        true
+    }
+}
+
+impl Default for InboundBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_payload_size() {
+        let limits = MessageLimits::new(1000);
+
+        assert_eq!(Some(1008), limits.max_payload_size(&Block::name()));
+        assert_eq!(Some(1008), limits.max_payload_size(&SyncBlock::name()));
+        assert_eq!(Some(MAX_FIXED_MESSAGE_SIZE), limits.max_payload_size(&Ping::name()));
+        assert_eq!(
+            Some(8 + MAX_HEADERS as usize * BlockHeader::size()),
+            limits.max_payload_size(&Headers::name())
+        );
+        assert_eq!(None, limits.max_payload_size(&MessageName::from("disconnect")));
+        assert_eq!(None, limits.max_payload_size(&MessageName::from("unknown")));
+    }
+
+    #[test]
+    fn test_inbound_budget() {
+        let mut budget = InboundBudget::new();
+
+        assert!(budget.consume(60, 100));
+        assert!(budget.consume(40, 100));
+        assert!(!budget.consume(1, 100));
+    }
+}
@@ -32,6 +32,11 @@ impl MessageHeader {
         MessageHeader { name, len }
     }
 
+    /// Returns the size of a serialized message header in bytes.
+    pub const fn size() -> usize {
+        16
+    }
+
     pub fn serialize(&self) -> Result<Vec<u8>, MessageHeaderError> {
         let mut result = vec![];
         result.extend_from_slice(&self.name.as_bytes());
@@ -14,9 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod frame;
+pub use frame::*;
+
 pub mod hash;
 pub use hash::*;
 
+pub mod limits;
+pub use limits::*;
+
 pub mod message;
 pub use message::*;
 
@@ -14,13 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::external::message::MessageHeader;
+use crate::external::message::{FrameHeader, MessageHeader, NetworkMagic};
 use snarkos_errors::network::message::{MessageError, MessageHeaderError, StreamReadError};
 
 use tokio::{io::AsyncRead, prelude::*};
 
 /// Returns message bytes read from an input stream.
-pub async fn read_message<T: AsyncRead + Unpin>(mut stream: &mut T, len: usize) -> Result<Vec<u8>, MessageError> {
+/// Messages longer than `max_len` are rejected before the buffer is allocated.
+pub async fn read_message<T: AsyncRead + Unpin>(
+    mut stream: &mut T,
+    len: usize,
+    max_len: usize,
+) -> Result<Vec<u8>, MessageError> {
+    if len > max_len {
+        return Err(MessageError::MessageTooLarge(len, max_len));
+    }
+
     let mut buffer: Vec<u8> = vec![0; len];
 
     stream_read(&mut stream, &mut buffer).await?;
@@ -37,6 +46,33 @@ pub async fn read_header<T: AsyncRead + Unpin>(mut stream: &mut T) -> Result<Mes
     Ok(MessageHeader::from(buffer))
 }
 
+/// Returns a message header read from an input stream in either wire format, along with the
+/// payload checksum if the message was framed.
+/// Framed messages are identified by their network magic and rejected if it belongs to another network.
+pub async fn read_frame_header<T: AsyncRead + Unpin>(
+    mut stream: &mut T,
+    magic: NetworkMagic,
+) -> Result<(MessageHeader, Option<u32>), MessageHeaderError> {
+    let mut prefix = [0u8; 4];
+    stream_read(&mut stream, &mut prefix).await?;
+
+    if NetworkMagic::from_bytes(prefix).is_some() {
+        let mut buffer = vec![0u8; FrameHeader::size()];
+        buffer[..4].copy_from_slice(&prefix);
+        stream_read(&mut stream, &mut buffer[4..]).await?;
+
+        let frame = FrameHeader::deserialize(buffer, magic)?;
+
+        Ok((MessageHeader::new(frame.name, frame.len), Some(frame.checksum)))
+    } else {
+        let mut buffer = [0u8; 16];
+        buffer[..4].copy_from_slice(&prefix);
+        stream_read(&mut stream, &mut buffer[4..]).await?;
+
+        Ok((MessageHeader::from(buffer), None))
+    }
+}
+
 /// Reads bytes from an input stream to fill the buffer.
 async fn stream_read<'a, T: AsyncRead + Unpin>(stream: &'a mut T, buffer: &'a mut [u8]) -> Result<(), StreamReadError> {
     stream.read_exact(buffer).await?;
@@ -47,7 +83,7 @@ async fn stream_read<'a, T: AsyncRead + Unpin>(stream: &'a mut T, buffer: &'a mu
 mod tests {
     use super::*;
     use crate::external::{
-        message::{message::Message, MessageHeader},
+        message::{checksum, message::Message, MessageHeader},
         message_types::Ping,
     };
     use snarkos_testing::network::random_socket_address;
@@ -109,6 +145,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_read_frame_header() {
+        let address = random_socket_address();
+        let mut listener = TcpListener::bind(address).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+
+            let frame = FrameHeader::new(NetworkMagic::default(), Ping::name(), &[0u8; 8]);
+            stream.write_all(&frame.serialize().unwrap()).await.unwrap();
+
+            let legacy = MessageHeader::new(Ping::name(), 8);
+            stream.write_all(&legacy.serialize().unwrap()).await.unwrap();
+        });
+
+        let (mut stream, _socket) = listener.accept().await.unwrap();
+
+        let (header, checksum) = read_frame_header(&mut stream, NetworkMagic::default()).await.unwrap();
+        assert_eq!(MessageHeader::new(Ping::name(), 8), header);
+        assert_eq!(Some(checksum(&[0u8; 8])), checksum);
+
+        let (header, checksum) = read_frame_header(&mut stream, NetworkMagic::default()).await.unwrap();
+        assert_eq!(MessageHeader::new(Ping::name(), 8), header);
+        assert_eq!(None, checksum);
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_read_message() {
@@ -124,7 +187,7 @@ mod tests {
 
         let (mut stream, _socket) = listener.accept().await.unwrap();
 
-        let bytes = read_message(&mut stream, 8usize).await.unwrap();
+        let bytes = read_message(&mut stream, 8usize, 8usize).await.unwrap();
         let actual = Ping::deserialize(bytes).unwrap();
 
         assert_eq!(message_copy, actual);
@@ -0,0 +1,72 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_errors::network::message::MessageError;
+use snarkos_objects::BlockHeaderHash;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/block_txn.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockTxn {
+    /// the hash of the compact block
+    pub block_hash: BlockHeaderHash,
+    /// serialized transactions requested with a `GetBlockTxn` message, in the requested order
+    pub transactions: Vec<Vec<u8>>,
+}
+
+impl BlockTxn {
+    pub fn new(block_hash: BlockHeaderHash, transactions: Vec<Vec<u8>>) -> Self {
+        Self {
+            block_hash,
+            transactions,
+        }
+    }
+}
+
+impl Message for BlockTxn {
+    fn name() -> MessageName {
+        MessageName::from("blocktxn")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        let (block_hash, transactions) = bincode::deserialize(&vec)?;
+
+        Ok(Self::new(block_hash, transactions))
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&(&self.block_hash, &self.transactions))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::consensus::{BLOCK_1_HEADER_HASH, TRANSACTION_1};
+
+    #[test]
+    fn test_block_txn() {
+        let message = BlockTxn::new(
+            BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec()),
+            vec![TRANSACTION_1.to_vec()],
+        );
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = BlockTxn::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_algorithms::crh::double_sha256;
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_errors::{network::message::MessageError, objects::TransactionError};
+use snarkos_models::objects::Transaction;
+use snarkos_objects::{Block as BlockStruct, BlockHeader, BlockHeaderHash};
+use snarkos_utilities::{bytes::ToBytes, to_bytes};
+
+use serde::{Deserialize, Serialize};
+
+/// Returns the short id of a transaction in the block with the given hash,
+/// the first eight bytes of the double sha256 hash of the block hash and the transaction id.
+/// Salting with the block hash prevents precomputed short id collisions.
+pub fn short_transaction_id(block_hash: &BlockHeaderHash, transaction_id: &[u8]) -> u64 {
+    let mut preimage = block_hash.0.to_vec();
+    preimage.extend_from_slice(transaction_id);
+
+    let hash = double_sha256(&preimage);
+
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+
+    u64::from_le_bytes(bytes)
+}
+
+/// A transaction sent in full in a `CompactBlock`, with its index in the block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PrefilledTransaction {
+    /// The index of the transaction in the block
+    pub index: u32,
+    /// The serialized transaction
+    pub bytes: Vec<u8>,
+}
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/compact_block.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompactBlock {
+    /// the header of the block
+    pub header: BlockHeader,
+    /// short ids of the block transactions that are not prefilled, in block order
+    pub short_ids: Vec<u64>,
+    /// block transactions the receiver is not expected to have
+    pub prefilled_transactions: Vec<PrefilledTransaction>,
+}
+
+impl CompactBlock {
+    pub fn new(header: BlockHeader, short_ids: Vec<u64>, prefilled_transactions: Vec<PrefilledTransaction>) -> Self {
+        Self {
+            header,
+            short_ids,
+            prefilled_transactions,
+        }
+    }
+
+    /// Construct a `CompactBlock` from a block.
+    /// Coinbase transactions are prefilled, as they are not in the memory pool of any peer.
+    pub fn from_block(block: &BlockStruct<Tx>) -> Result<Self, TransactionError> {
+        let block_hash = block.header.get_hash();
+
+        let mut short_ids = vec![];
+        let mut prefilled_transactions = vec![];
+
+        for (index, transaction) in block.transactions.iter().enumerate() {
+            if transaction.value_balance.is_negative() {
+                prefilled_transactions.push(PrefilledTransaction {
+                    index: index as u32,
+                    bytes: to_bytes![transaction]?,
+                });
+            } else {
+                short_ids.push(short_transaction_id(&block_hash, &transaction.transaction_id()?));
+            }
+        }
+
+        Ok(Self::new(block.header.clone(), short_ids, prefilled_transactions))
+    }
+}
+
+impl Message for CompactBlock {
+    fn name() -> MessageName {
+        MessageName::from("compactblock")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        let (header, short_ids, prefilled_transactions) = bincode::deserialize(&vec)?;
+
+        Ok(Self::new(header, short_ids, prefilled_transactions))
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&(
+            &self.header,
+            &self.short_ids,
+            &self.prefilled_transactions,
+        ))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::consensus::DATA;
+
+    #[test]
+    fn test_compact_block() {
+        let block = DATA.block_1.clone();
+        let message = CompactBlock::from_block(&block).unwrap();
+
+        assert_eq!(
+            block.transactions.len(),
+            message.short_ids.len() + message.prefilled_transactions.len()
+        );
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = CompactBlock::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+
+    #[test]
+    fn test_short_transaction_id() {
+        let transaction_id = [1u8; 32];
+
+        let short_id = short_transaction_id(&BlockHeaderHash([0u8; 32]), &transaction_id);
+
+        assert_eq!(short_id, short_transaction_id(&BlockHeaderHash([0u8; 32]), &transaction_id));
+        assert_ne!(short_id, short_transaction_id(&BlockHeaderHash([1u8; 32]), &transaction_id));
+    }
+}
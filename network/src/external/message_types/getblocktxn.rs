@@ -0,0 +1,66 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_errors::network::message::MessageError;
+use snarkos_objects::BlockHeaderHash;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_block_txn.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct GetBlockTxn {
+    /// the hash of the compact block
+    pub block_hash: BlockHeaderHash,
+    /// indexes of the requested transactions in the block, in ascending order
+    pub indexes: Vec<u32>,
+}
+
+impl GetBlockTxn {
+    pub fn new(block_hash: BlockHeaderHash, indexes: Vec<u32>) -> Self {
+        Self { block_hash, indexes }
+    }
+}
+
+impl Message for GetBlockTxn {
+    fn name() -> MessageName {
+        MessageName::from("getblocktxn")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        let (block_hash, indexes) = bincode::deserialize(&vec)?;
+
+        Ok(Self::new(block_hash, indexes))
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&(&self.block_hash, &self.indexes))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::consensus::BLOCK_1_HEADER_HASH;
+
+    #[test]
+    fn test_get_block_txn() {
+        let message = GetBlockTxn::new(BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec()), vec![1, 3]);
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = GetBlockTxn::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+}
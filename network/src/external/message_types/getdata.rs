@@ -0,0 +1,70 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::{
+    message::{Message, MessageName},
+    message_types::InventoryItem,
+};
+use snarkos_errors::network::message::MessageError;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_data.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct GetData {
+    /// transactions and blocks requested from the receiver
+    pub inventory: Vec<InventoryItem>,
+}
+
+impl GetData {
+    pub fn new(inventory: Vec<InventoryItem>) -> Self {
+        Self { inventory }
+    }
+}
+
+impl Message for GetData {
+    fn name() -> MessageName {
+        MessageName::from("getdata")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        Ok(Self {
+            inventory: bincode::deserialize(&vec)?,
+        })
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&self.inventory)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_objects::BlockHeaderHash;
+    use snarkos_testing::consensus::BLOCK_1_HEADER_HASH;
+
+    #[test]
+    fn test_get_data() {
+        let message = GetData::new(vec![
+            InventoryItem::Transaction(vec![1u8; 32]),
+            InventoryItem::Block(BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec())),
+        ]);
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = GetData::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+}
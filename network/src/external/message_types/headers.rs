@@ -0,0 +1,64 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_errors::network::message::MessageError;
+use snarkos_objects::BlockHeader;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/headers.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Headers {
+    /// block headers following the latest shared block of the requester, in chain order
+    pub headers: Vec<BlockHeader>,
+}
+
+impl Headers {
+    pub fn new(headers: Vec<BlockHeader>) -> Self {
+        Self { headers }
+    }
+}
+
+impl Message for Headers {
+    fn name() -> MessageName {
+        MessageName::from("headers")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        Ok(Self {
+            headers: bincode::deserialize(&vec)?,
+        })
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&self.headers)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::consensus::DATA;
+
+    #[test]
+    fn test_headers() {
+        let message = Headers::new(vec![DATA.block_1.header.clone(), DATA.block_2.header.clone()]);
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = Headers::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message::{Message, MessageName};
+use snarkos_errors::network::message::MessageError;
+use snarkos_objects::BlockHeaderHash;
+
+use serde::{Deserialize, Serialize};
+
+/// A transaction or block a node can announce to and request from its peers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum InventoryItem {
+    /// A transaction identified by its transaction id
+    Transaction(Vec<u8>),
+    /// A block identified by its block header hash
+    Block(BlockHeaderHash),
+}
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/inv.md"))]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Inv {
+    /// transactions and blocks announced by the sender
+    pub inventory: Vec<InventoryItem>,
+}
+
+impl Inv {
+    pub fn new(inventory: Vec<InventoryItem>) -> Self {
+        Self { inventory }
+    }
+}
+
+impl Message for Inv {
+    fn name() -> MessageName {
+        MessageName::from("inv")
+    }
+
+    fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
+        Ok(Self {
+            inventory: bincode::deserialize(&vec)?,
+        })
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        Ok(bincode::serialize(&self.inventory)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::consensus::BLOCK_1_HEADER_HASH;
+
+    #[test]
+    fn test_inv() {
+        let message = Inv::new(vec![
+            InventoryItem::Transaction(vec![1u8; 32]),
+            InventoryItem::Block(BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec())),
+        ]);
+
+        let serialized = message.serialize().unwrap();
+        let deserialized = Inv::deserialize(serialized).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+}
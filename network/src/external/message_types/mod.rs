@@ -19,11 +19,36 @@ pub mod block;
 #[doc(inline)]
 pub use block::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/block_txn.md"))]
+pub mod blocktxn;
+#[doc(inline)]
+pub use blocktxn::*;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/compact_block.md"))]
+pub mod compactblock;
+#[doc(inline)]
+pub use compactblock::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_block.md"))]
 pub mod getblock;
 #[doc(inline)]
 pub use getblock::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_block_txn.md"))]
+pub mod getblocktxn;
+#[doc(inline)]
+pub use getblocktxn::*;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_data.md"))]
+pub mod getdata;
+#[doc(inline)]
+pub use getdata::*;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_headers.md"))]
+pub mod getheaders;
+#[doc(inline)]
+pub use getheaders::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/get_memory_pool.md"))]
 pub mod getmemorypool;
 #[doc(inline)]
@@ -39,11 +64,26 @@ pub mod getsync;
 #[doc(inline)]
 pub use getsync::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/headers.md"))]
+pub mod headers;
+#[doc(inline)]
+pub use headers::*;
+
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/inv.md"))]
+pub mod inv;
+#[doc(inline)]
+pub use inv::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/memory_pool.md"))]
 pub mod memorypool;
 #[doc(inline)]
 pub use memorypool::*;
 
+#[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/not_found.md"))]
+pub mod notfound;
+#[doc(inline)]
+pub use notfound::*;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/peers.md"))]
 pub mod peers;
 #[doc(inline)]
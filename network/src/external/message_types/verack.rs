@@ -71,12 +71,14 @@ impl Message for Verack {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::external::message_types::Version;
+    use crate::external::{message_types::Version, NodeIdentity, Services};
+    use snarkos_objects::BlockHeaderHash;
     use snarkos_testing::network::random_socket_address;
 
     #[test]
     fn test_verack() {
-        let version = Version::new(1u64, 1u32, random_socket_address(), random_socket_address());
+        let identity = NodeIdentity::new(0, BlockHeaderHash([0u8; 32]), Services::FULL_NODE);
+        let version = Version::new(&identity, 1u32, random_socket_address(), random_socket_address());
 
         let message = Verack::new(version.nonce, version.address_sender, version.address_receiver);
 
@@ -14,13 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::external::message::{Message, MessageName};
+use crate::external::{
+    message::{Message, MessageName},
+    NodeIdentity,
+    Services,
+    MAX_USER_AGENT_LENGTH,
+};
 use snarkos_errors::network::message::MessageError;
+use snarkos_objects::BlockHeaderHash;
 
 use chrono::Utc;
 use rand::Rng;
 use std::net::SocketAddr;
 
+/// The size of the fixed length fields of a serialized `Version` message.
+const FIXED_LENGTH: usize = 89;
+
 #[cfg_attr(nightly, doc(include = "../../../documentation/network_messages/version.md"))]
 #[derive(Debug, PartialEq, Clone)]
 pub struct Version {
@@ -41,36 +50,45 @@ pub struct Version {
 
     /// Network address of message sender
     pub address_sender: SocketAddr,
+
+    /// The id of the network of node sending this message
+    pub network_id: u8,
+
+    /// The genesis block hash of node sending this message
+    pub genesis_hash: BlockHeaderHash,
+
+    /// The services offered by node sending this message
+    pub services: Services,
+
+    /// The software run by node sending this message
+    pub user_agent: String,
 }
 
 impl Version {
-    pub fn new(version: u64, height: u32, address_receiver: SocketAddr, address_sender: SocketAddr) -> Self {
+    pub fn new(identity: &NodeIdentity, height: u32, address_receiver: SocketAddr, address_sender: SocketAddr) -> Self {
         let mut rng = rand::thread_rng();
 
-        Self {
-            version,
-            height,
-            nonce: rng.gen::<u64>(),
-            timestamp: Utc::now().timestamp(),
-            address_receiver,
-            address_sender,
-        }
+        Self::from(identity, height, address_receiver, address_sender, rng.gen::<u64>())
     }
 
     pub fn from(
-        version: u64,
+        identity: &NodeIdentity,
         height: u32,
         address_receiver: SocketAddr,
         address_sender: SocketAddr,
         nonce: u64,
     ) -> Self {
         Self {
-            version,
+            version: identity.version,
             height,
             nonce,
             timestamp: Utc::now().timestamp(),
             address_receiver,
             address_sender,
+            network_id: identity.network_id,
+            genesis_hash: identity.genesis_hash.clone(),
+            services: identity.services,
+            user_agent: identity.user_agent.clone(),
         }
     }
 }
@@ -81,10 +99,13 @@ impl Message for Version {
     }
 
     fn deserialize(vec: Vec<u8>) -> Result<Self, MessageError> {
-        if vec.len() != 48 {
-            return Err(MessageError::InvalidLength(vec.len(), 48));
+        if vec.len() < FIXED_LENGTH || vec.len() > FIXED_LENGTH + 8 + MAX_USER_AGENT_LENGTH {
+            return Err(MessageError::InvalidLength(vec.len(), FIXED_LENGTH));
         }
 
+        let mut genesis_hash = [0u8; 32];
+        genesis_hash.copy_from_slice(&vec[49..81]);
+
         Ok(Version {
             version: bincode::deserialize(&vec[..8])?,
             height: bincode::deserialize(&vec[8..12])?,
@@ -92,10 +113,18 @@ impl Message for Version {
             timestamp: bincode::deserialize(&vec[20..28])?,
             address_receiver: bincode::deserialize(&vec[28..38])?,
             address_sender: bincode::deserialize(&vec[38..48])?,
+            network_id: vec[48],
+            genesis_hash: BlockHeaderHash(genesis_hash),
+            services: Services::from_bits(bincode::deserialize(&vec[81..89])?),
+            user_agent: bincode::deserialize(&vec[89..])?,
         })
     }
 
     fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        if self.user_agent.len() > MAX_USER_AGENT_LENGTH {
+            return Err(MessageError::InvalidLength(self.user_agent.len(), MAX_USER_AGENT_LENGTH));
+        }
+
         let mut writer = vec![];
         writer.extend_from_slice(&bincode::serialize(&self.version)?);
         writer.extend_from_slice(&bincode::serialize(&self.height)?);
@@ -103,6 +132,10 @@ impl Message for Version {
         writer.extend_from_slice(&bincode::serialize(&self.timestamp)?);
         writer.extend_from_slice(&bincode::serialize(&self.address_receiver)?);
         writer.extend_from_slice(&bincode::serialize(&self.address_sender)?);
+        writer.push(self.network_id);
+        writer.extend_from_slice(&self.genesis_hash.0);
+        writer.extend_from_slice(&bincode::serialize(&self.services.bits())?);
+        writer.extend_from_slice(&bincode::serialize(&self.user_agent)?);
         Ok(writer)
     }
 }
@@ -114,7 +147,7 @@ mod tests {
     #[test]
     fn test_version() {
         let version = Version::new(
-            1u64,
+            &NodeIdentity::new(1, BlockHeaderHash([1u8; 32]), Services::FULL_NODE),
             1u32,
             "127.0.0.1:4130".parse::<SocketAddr>().unwrap(),
             "127.0.0.1:4130".parse::<SocketAddr>().unwrap(),
@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod bandwidth;
+pub use bandwidth::*;
+
 pub mod channel;
 pub use channel::*;
 
@@ -25,8 +28,17 @@ pub mod message_types;
 #[doc(inline)]
 pub use message_types::*;
 
+pub mod node_identity;
+pub use node_identity::*;
+
+pub mod noise;
+pub use noise::*;
+
 pub mod propagate;
 pub use propagate::*;
 
 pub mod protocol;
 pub use protocol::*;
+
+pub mod transport;
+pub use transport::*;
@@ -0,0 +1,185 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::network::HandshakeError;
+use snarkos_objects::BlockHeaderHash;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The version of the network protocol spoken by this node.
+pub const PROTOCOL_VERSION: u64 = 5;
+
+/// The first protocol version supporting framed messages.
+pub const FRAMED_PROTOCOL_VERSION: u64 = 2;
+
+/// The first protocol version supporting headers-first sync with `GetHeaders` and `Headers` messages.
+pub const HEADERS_PROTOCOL_VERSION: u64 = 3;
+
+/// The first protocol version supporting `Inv`, `GetData` and `NotFound` messages.
+pub const INVENTORY_PROTOCOL_VERSION: u64 = 4;
+
+/// The first protocol version supporting compact block relay.
+pub const COMPACT_BLOCKS_PROTOCOL_VERSION: u64 = 5;
+
+/// The maximum length of a user agent string in bytes.
+pub const MAX_USER_AGENT_LENGTH: usize = 256;
+
+/// Returns the user agent string of this node.
+pub fn user_agent() -> String {
+    format!("snarkOS/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// The services a node offers to its peers, encoded as bit flags.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Services(u64);
+
+impl Services {
+    /// The node does not offer any services.
+    pub const NONE: Services = Services(0);
+    /// The node validates and relays blocks and transactions.
+    pub const FULL_NODE: Services = Services(1);
+    /// The node mines new blocks.
+    pub const MINER: Services = Services(1 << 1);
+    /// The node is a bootnode.
+    pub const BOOTNODE: Services = Services(1 << 2);
+    /// The node serves the full block history to syncing peers.
+    pub const SERVES_HISTORY: Services = Services(1 << 3);
+
+    /// Construct `Services` from their bit flags. Unknown flags are preserved.
+    pub fn from_bits(bits: u64) -> Self {
+        Services(bits)
+    }
+
+    /// Returns the bit flags of the services.
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns true if all the given services are offered.
+    pub fn contains(&self, services: Services) -> bool {
+        self.0 & services.0 == services.0
+    }
+
+    /// Adds the given services.
+    pub fn insert(&mut self, services: Services) {
+        self.0 |= services.0;
+    }
+
+    /// Returns the names of the known services that are offered.
+    pub fn names(&self) -> Vec<&'static str> {
+        let names = [
+            (Services::FULL_NODE, "full_node"),
+            (Services::MINER, "miner"),
+            (Services::BOOTNODE, "bootnode"),
+            (Services::SERVES_HISTORY, "serves_history"),
+        ];
+
+        names
+            .iter()
+            .filter(|(service, _)| self.contains(*service))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl fmt::Display for Services {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]", self.names().join(", "))
+    }
+}
+
+/// The chain and capabilities a node advertises in its `Version` messages.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeIdentity {
+    /// The network protocol version
+    pub version: u64,
+
+    /// The id of the network the node is running on
+    pub network_id: u8,
+
+    /// The hash of the genesis block of the chain
+    pub genesis_hash: BlockHeaderHash,
+
+    /// The software the node is running
+    pub user_agent: String,
+
+    /// The services the node offers
+    pub services: Services,
+}
+
+impl NodeIdentity {
+    /// Construct a new `NodeIdentity` running the current protocol version and user agent.
+    pub fn new(network_id: u8, genesis_hash: BlockHeaderHash, services: Services) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            network_id,
+            genesis_hash,
+            user_agent: user_agent(),
+            services,
+        }
+    }
+
+    /// Returns an error if a peer with the given network id and genesis hash runs on a different chain.
+    pub fn check_compatibility(&self, network_id: u8, genesis_hash: &BlockHeaderHash) -> Result<(), HandshakeError> {
+        if self.network_id != network_id {
+            return Err(HandshakeError::NetworkMismatch(network_id, self.network_id));
+        }
+
+        if &self.genesis_hash != genesis_hash {
+            return Err(HandshakeError::GenesisMismatch(
+                hex::encode(genesis_hash.0),
+                hex::encode(self.genesis_hash.0),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_services() {
+        let mut services = Services::FULL_NODE;
+        services.insert(Services::MINER);
+
+        assert!(services.contains(Services::FULL_NODE));
+        assert!(services.contains(Services::MINER));
+        assert!(!services.contains(Services::BOOTNODE));
+        assert_eq!(Services::from_bits(0b11), services);
+        assert_eq!("[full_node, miner]", services.to_string());
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let identity = NodeIdentity::new(1, BlockHeaderHash([1u8; 32]), Services::FULL_NODE);
+
+        assert!(identity.check_compatibility(1, &BlockHeaderHash([1u8; 32])).is_ok());
+
+        match identity.check_compatibility(2, &BlockHeaderHash([1u8; 32])) {
+            Err(HandshakeError::NetworkMismatch(2, 1)) => {}
+            result => panic!("expected a network mismatch, got {:?}", result),
+        }
+
+        match identity.check_compatibility(1, &BlockHeaderHash([2u8; 32])) {
+            Err(HandshakeError::GenesisMismatch(_, _)) => {}
+            result => panic!("expected a genesis mismatch, got {:?}", result),
+        }
+    }
+}
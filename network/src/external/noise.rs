@@ -0,0 +1,409 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::Stream;
+use snarkos_errors::network::ConnectError;
+
+use snow::{Builder, HandshakeState, TransportState};
+use std::{
+    fmt,
+    fs,
+    io,
+    path::Path,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+/// The noise protocol used to encrypt peer connections.
+pub const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// The bytes an initiator sends before starting a noise handshake.
+/// Legacy message headers start with an ASCII message name and frames with the network magic,
+/// so neither begins with this preamble.
+pub const NOISE_PREAMBLE: [u8; 4] = [0xe5, 0x4e, 0x4f, 0x01];
+
+/// The maximum length of a single noise message.
+pub const MAX_NOISE_MESSAGE_LENGTH: usize = 65535;
+
+/// The name of the file the static keypair of a node is stored in, inside the node directory.
+pub const STATIC_KEY_FILE: &str = "node.key";
+
+/// The length of the authentication tag appended to every encrypted noise message.
+const TAG_LENGTH: usize = 16;
+
+/// The length of a static key.
+const KEY_LENGTH: usize = 32;
+
+/// Maps a noise protocol error into a `ConnectError`.
+fn noise_error(error: snow::Error) -> ConnectError {
+    ConnectError::Crate("snow", format!("{:?}", error))
+}
+
+/// The static keypair identifying a node in the noise handshakes of its encrypted connections.
+#[derive(Clone)]
+pub struct StaticKeypair {
+    private: Vec<u8>,
+    public: Vec<u8>,
+}
+
+impl StaticKeypair {
+    /// Generates a new random `StaticKeypair`.
+    pub fn generate() -> Result<Self, ConnectError> {
+        let keypair = Builder::new(NOISE_PARAMS.parse().map_err(noise_error)?)
+            .generate_keypair()
+            .map_err(noise_error)?;
+
+        Ok(Self {
+            private: keypair.private,
+            public: keypair.public,
+        })
+    }
+
+    /// Loads the keypair stored at the given path, or generates and stores a new one if there is none.
+    pub fn load_or_generate(path: &Path) -> Result<Self, ConnectError> {
+        if path.exists() {
+            let bytes = fs::read(path)?;
+
+            if bytes.len() != 2 * KEY_LENGTH {
+                return Err(ConnectError::InvalidStaticKey(path.display().to_string()));
+            }
+
+            return Ok(Self {
+                private: bytes[..KEY_LENGTH].to_vec(),
+                public: bytes[KEY_LENGTH..].to_vec(),
+            });
+        }
+
+        let keypair = Self::generate()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, [&keypair.private[..], &keypair.public[..]].concat())?;
+
+        // Only the node operator may read the private key.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(keypair)
+    }
+
+    /// Returns the public key of the keypair.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public
+    }
+}
+
+impl fmt::Debug for StaticKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StaticKeypair {{ public: {} }}", hex::encode(&self.public))
+    }
+}
+
+/// The encryption settings of the peer connections of a node.
+#[derive(Clone, Debug, Default)]
+pub struct Encryption {
+    /// The static keypair of the node. Outbound connections are only encrypted if it is set.
+    pub keypair: Option<StaticKeypair>,
+
+    /// If enabled, unencrypted connection requests from peers are rejected
+    pub required: bool,
+}
+
+impl Encryption {
+    /// Construct new `Encryption` settings encrypting all outbound connections with the given keypair.
+    pub fn new(keypair: StaticKeypair, required: bool) -> Self {
+        Self {
+            keypair: Some(keypair),
+            required,
+        }
+    }
+}
+
+/// Returns true if the peer on the stream starts a noise handshake, consuming the preamble.
+/// Returns false without consuming any bytes otherwise.
+pub async fn read_preamble(stream: &mut Stream) -> Result<bool, ConnectError> {
+    let mut first_byte = [0u8; 1];
+    if stream.peek(&mut first_byte).await? == 0 {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    if first_byte[0] != NOISE_PREAMBLE[0] {
+        return Ok(false);
+    }
+
+    let mut preamble = [0u8; 4];
+    stream.read_exact(&mut preamble).await?;
+
+    if preamble != NOISE_PREAMBLE {
+        return Err(ConnectError::InvalidPreamble(preamble));
+    }
+
+    Ok(true)
+}
+
+/// Writes a length prefixed noise handshake message.
+async fn write_handshake_message(stream: &mut Stream, message: &[u8]) -> Result<(), ConnectError> {
+    stream.write_all(&(message.len() as u16).to_be_bytes()).await?;
+    stream.write_all(message).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
+/// Reads a length prefixed noise handshake message.
+async fn read_handshake_message(stream: &mut Stream) -> Result<Vec<u8>, ConnectError> {
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).await?;
+
+    let mut message = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut message).await?;
+
+    Ok(message)
+}
+
+/// The bytes received on an encrypted stream that have not been read yet.
+#[derive(Default)]
+struct ReadBuffers {
+    /// Decrypted bytes
+    plaintext: Vec<u8>,
+
+    /// Received bytes that do not form a complete noise message yet
+    ciphertext: Vec<u8>,
+}
+
+/// An established noise session encrypting the messages sent on a stream.
+///
+/// The cipher states are only locked while a message is encrypted or decrypted,
+/// so a session can be shared by the reader and the writer of a channel.
+pub struct NoiseSession {
+    transport: Mutex<TransportState>,
+    buffers: Mutex<ReadBuffers>,
+}
+
+impl NoiseSession {
+    /// Performs the initiator side of a noise handshake on a newly connected stream.
+    pub async fn initiate(stream: &mut Stream, keypair: &StaticKeypair) -> Result<Self, ConnectError> {
+        let mut noise = Builder::new(NOISE_PARAMS.parse().map_err(noise_error)?)
+            .local_private_key(&keypair.private)
+            .build_initiator()
+            .map_err(noise_error)?;
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LENGTH];
+
+        stream.write_all(&NOISE_PREAMBLE).await?;
+
+        // -> e
+        let len = noise.write_message(&[], &mut buffer).map_err(noise_error)?;
+        write_handshake_message(stream, &buffer[..len]).await?;
+
+        // <- e, ee, s, es
+        let message = read_handshake_message(stream).await?;
+        noise.read_message(&message, &mut buffer).map_err(noise_error)?;
+
+        // -> s, se
+        let len = noise.write_message(&[], &mut buffer).map_err(noise_error)?;
+        write_handshake_message(stream, &buffer[..len]).await?;
+
+        Self::new(noise)
+    }
+
+    /// Performs the responder side of a noise handshake on a stream whose preamble was read.
+    pub async fn respond(stream: &mut Stream, keypair: &StaticKeypair) -> Result<Self, ConnectError> {
+        let mut noise = Builder::new(NOISE_PARAMS.parse().map_err(noise_error)?)
+            .local_private_key(&keypair.private)
+            .build_responder()
+            .map_err(noise_error)?;
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LENGTH];
+
+        // <- e
+        let message = read_handshake_message(stream).await?;
+        noise.read_message(&message, &mut buffer).map_err(noise_error)?;
+
+        // -> e, ee, s, es
+        let len = noise.write_message(&[], &mut buffer).map_err(noise_error)?;
+        write_handshake_message(stream, &buffer[..len]).await?;
+
+        // <- s, se
+        let message = read_handshake_message(stream).await?;
+        noise.read_message(&message, &mut buffer).map_err(noise_error)?;
+
+        Self::new(noise)
+    }
+
+    fn new(noise: HandshakeState) -> Result<Self, ConnectError> {
+        Ok(Self {
+            transport: Mutex::new(noise.into_transport_mode().map_err(noise_error)?),
+            buffers: Mutex::new(ReadBuffers::default()),
+        })
+    }
+
+    /// Returns the static public key the peer authenticated with.
+    pub fn remote_public_key(&self) -> Option<Vec<u8>> {
+        let transport = self.transport.lock().expect("noise session lock poisoned");
+        transport.get_remote_static().map(|key| key.to_vec())
+    }
+
+    /// Encrypts the bytes into a sequence of length prefixed noise messages.
+    pub fn encrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, ConnectError> {
+        let mut transport = self.transport.lock().expect("noise session lock poisoned");
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LENGTH];
+        let mut result = vec![];
+
+        for chunk in bytes.chunks(MAX_NOISE_MESSAGE_LENGTH - TAG_LENGTH) {
+            let len = transport.write_message(chunk, &mut buffer).map_err(noise_error)?;
+            result.extend_from_slice(&(len as u16).to_be_bytes());
+            result.extend_from_slice(&buffer[..len]);
+        }
+
+        Ok(result)
+    }
+
+    /// Decrypts the complete noise messages among the received bytes.
+    fn decrypt(&self, buffers: &mut ReadBuffers) -> io::Result<()> {
+        let mut transport = self.transport.lock().expect("noise session lock poisoned");
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LENGTH];
+
+        while buffers.ciphertext.len() >= 2 {
+            let len = u16::from_be_bytes([buffers.ciphertext[0], buffers.ciphertext[1]]) as usize;
+            if buffers.ciphertext.len() < 2 + len {
+                break;
+            }
+
+            let plaintext_len = transport
+                .read_message(&buffers.ciphertext[2..2 + len], &mut buffer)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+            buffers.plaintext.extend_from_slice(&buffer[..plaintext_len]);
+            buffers.ciphertext.drain(..2 + len);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for NoiseSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.remote_public_key() {
+            Some(key) => write!(f, "NoiseSession {{ remote: {} }}", hex::encode(key)),
+            None => write!(f, "NoiseSession"),
+        }
+    }
+}
+
+/// Reads the decrypted bytes of a stream carrying an established noise session.
+pub struct NoiseReader<'a> {
+    pub stream: &'a mut Stream,
+    pub session: &'a NoiseSession,
+}
+
+impl AsyncRead for NoiseReader<'_> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        loop {
+            {
+                let mut buffers = this.session.buffers.lock().expect("noise session lock poisoned");
+
+                if !buffers.plaintext.is_empty() {
+                    let len = buf.len().min(buffers.plaintext.len());
+                    buf[..len].copy_from_slice(&buffers.plaintext[..len]);
+                    buffers.plaintext.drain(..len);
+
+                    return Poll::Ready(Ok(len));
+                }
+            }
+
+            let mut chunk = [0u8; 4096];
+            match Pin::new(&mut *this.stream).poll_read(cx, &mut chunk) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Ok(0)),
+                Poll::Ready(Ok(len)) => {
+                    let mut buffers = this.session.buffers.lock().expect("noise session lock poisoned");
+                    buffers.ciphertext.extend_from_slice(&chunk[..len]);
+                    this.session.decrypt(&mut buffers)?;
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::network::random_socket_address;
+
+    use serial_test::serial;
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn test_load_or_generate() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("snarkos_test_{}", random_socket_address().port()));
+        path.push(STATIC_KEY_FILE);
+
+        let keypair = StaticKeypair::load_or_generate(&path).unwrap();
+        let loaded = StaticKeypair::load_or_generate(&path).unwrap();
+
+        assert_eq!(KEY_LENGTH, keypair.public_key().len());
+        assert_eq!(keypair.public_key(), loaded.public_key());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_noise_session() {
+        let address = random_socket_address();
+        let mut listener = TcpListener::bind(address).await.unwrap();
+
+        let initiator_keypair = StaticKeypair::generate().unwrap();
+        let responder_keypair = StaticKeypair::generate().unwrap();
+        let initiator_public_key = initiator_keypair.public_key().to_vec();
+
+        // A message longer than a single noise message
+        let message = vec![7u8; 2 * MAX_NOISE_MESSAGE_LENGTH];
+        let expected = message.clone();
+
+        tokio::spawn(async move {
+            let mut stream = Stream::from(TcpStream::connect(address).await.unwrap());
+            let session = NoiseSession::initiate(&mut stream, &initiator_keypair).await.unwrap();
+
+            stream.write_all(&session.encrypt(&message).unwrap()).await.unwrap();
+        });
+
+        let (stream, _address) = listener.accept().await.unwrap();
+        let mut stream = Stream::from(stream);
+        assert!(read_preamble(&mut stream).await.unwrap());
+
+        let session = NoiseSession::respond(&mut stream, &responder_keypair).await.unwrap();
+        assert_eq!(Some(initiator_public_key), session.remote_public_key());
+
+        let mut received = vec![0u8; expected.len()];
+        let mut reader = NoiseReader {
+            stream: &mut stream,
+            session: &session,
+        };
+        reader.read_exact(&mut received).await.unwrap();
+
+        assert_eq!(expected, received);
+    }
+}
@@ -1,37 +1,34 @@
 use crate::{
-    external::{Block, Transaction},
+    external::{
+        message::Message,
+        Block,
+        CompactBlock,
+        Inv,
+        InventoryItem,
+        Transaction,
+        COMPACT_BLOCKS_PROTOCOL_VERSION,
+        INVENTORY_PROTOCOL_VERSION,
+    },
     internal::context::Context,
 };
+use snarkos_dpc::base_dpc::instantiated::Tx;
 use snarkos_errors::network::SendError;
+use snarkos_objects::{Block as BlockStruct, BlockHeaderHash};
 
 use std::{net::SocketAddr, sync::Arc};
 
 /// Broadcast transaction to connected peers
 pub async fn propagate_transaction(
     context: Arc<Context>,
+    transaction_id: Vec<u8>,
     transaction_bytes: Vec<u8>,
     transaction_sender: SocketAddr,
 ) -> Result<(), SendError> {
     debug!("Propagating a transaction to peers");
 
-    let peer_book = context.peer_book.read().await;
-    let local_address = *context.local_address.read().await;
-    let connections = context.connections.read().await;
-    let mut num_peers = 0;
-
-    for (socket, _) in &peer_book.get_connected() {
-        if *socket != transaction_sender && *socket != local_address {
-            if let Some(channel) = connections.get(socket) {
-                match channel.write(&Transaction::new(transaction_bytes.clone())).await {
-                    Ok(_) => num_peers += 1,
-                    Err(error) => warn!(
-                        "Failed to propagate transaction to peer {}. (error message: {})",
-                        channel.address, error
-                    ),
-                }
-            }
-        }
-    }
+    let item = InventoryItem::Transaction(transaction_id);
+    let payload = Transaction::new(transaction_bytes);
+    let num_peers = announce(context, item, &payload, None, transaction_sender).await;
 
     debug!("Transaction propagated to {} peers", num_peers);
 
@@ -41,31 +38,84 @@ pub async fn propagate_transaction(
 /// Broadcast block to connected peers
 pub async fn propagate_block(
     context: Arc<Context>,
+    block_hash: BlockHeaderHash,
     block_bytes: Vec<u8>,
     block_miner: SocketAddr,
 ) -> Result<(), SendError> {
     debug!("Propagating a block to peers");
 
-    let peer_book = context.peer_book.read().await;
-    let local_address = *context.local_address.read().await;
-    let connections = context.connections.read().await;
-    let mut num_peers = 0;
+    // Peers supporting compact blocks rebuild the block from their memory pool.
+    let compact_block = BlockStruct::<Tx>::deserialize(&block_bytes)
+        .ok()
+        .and_then(|block| CompactBlock::from_block(&block).ok());
+
+    let item = InventoryItem::Block(block_hash);
+    let payload = Block::new(block_bytes);
+    let num_peers = announce(context, item, &payload, compact_block.as_ref(), block_miner).await;
+
+    debug!("Block propagated to {} peers", num_peers);
+
+    Ok(())
+}
+
+/// Announce an inventory item to the connected peers that don't know it yet.
+/// Blocks are sent as compact blocks to the peers supporting them.
+/// Peers running a protocol version without inventory announcements are sent the full payload instead.
+/// The messages are written after releasing the locks on the node state, as writes may be throttled.
+/// Returns the number of peers the item was propagated to.
+async fn announce<M: Message>(
+    context: Arc<Context>,
+    item: InventoryItem,
+    payload: &M,
+    compact_block: Option<&CompactBlock>,
+    sender: SocketAddr,
+) -> usize {
+    let mut targets = vec![];
+    {
+        let peer_book = context.peer_book.read().await;
+        let local_address = *context.local_address.read().await;
+        let connections = context.connections.read().await;
+        let mut known_inventory = context.known_inventory.write().await;
 
-    for (socket, _) in &peer_book.get_connected() {
-        if *socket != block_miner && *socket != local_address {
-            if let Some(channel) = connections.get(socket) {
-                match channel.write(&Block::new(block_bytes.clone())).await {
-                    Ok(_) => num_peers += 1,
-                    Err(error) => warn!(
-                        "Failed to propagate block to peer {}. (error message: {})",
-                        channel.address, error
-                    ),
+        // The sender already has the item.
+        known_inventory.insert(sender, item.clone());
+
+        for (socket, _) in &peer_book.get_connected() {
+            if *socket != sender && *socket != local_address && !known_inventory.contains(socket, &item) {
+                if let Some(channel) = connections.get(socket) {
+                    let version = peer_book.get_peer_info(socket).version.unwrap_or(0);
+                    targets.push((channel.clone(), version));
                 }
             }
         }
     }
 
-    debug!("Block propagated to {} peers", num_peers);
+    let mut num_peers = 0;
 
-    Ok(())
+    for (channel, version) in targets {
+        let result = match compact_block {
+            Some(compact_block) if version >= COMPACT_BLOCKS_PROTOCOL_VERSION => channel.write(compact_block).await,
+            _ if version >= INVENTORY_PROTOCOL_VERSION => channel.write(&Inv::new(vec![item.clone()])).await,
+            _ => channel.write(payload).await,
+        };
+
+        match result {
+            Ok(_) => {
+                context
+                    .known_inventory
+                    .write()
+                    .await
+                    .insert(channel.address, item.clone());
+                num_peers += 1;
+            }
+            Err(error) => warn!(
+                "Failed to propagate {} to peer {}. (error message: {})",
+                M::name(),
+                channel.address,
+                error
+            ),
+        }
+    }
+
+    num_peers
 }
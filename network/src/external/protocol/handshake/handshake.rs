@@ -16,7 +16,11 @@
 
 use crate::external::{
     message_types::{Verack, Version},
+    Bandwidth,
     Channel,
+    Encryption,
+    Transport,
+    FRAMED_PROTOCOL_VERSION,
 };
 use snarkos_errors::network::HandshakeError;
 
@@ -49,9 +53,19 @@ pub struct Handshake {
 
 impl Handshake {
     /// Send the initial Version message to a peer
-    pub async fn send_new(local_version: &Version) -> Result<Self, HandshakeError> {
+    pub async fn send_new(
+        local_version: &Version,
+        transport: &Transport,
+        encryption: &Encryption,
+        bandwidth: &Arc<Bandwidth>,
+    ) -> Result<Self, HandshakeError> {
         // Create temporary write only channel
-        let channel = Arc::new(Channel::new_write_only(local_version.address_receiver).await?);
+        let channel = Channel::connect(local_version.address_receiver, transport, encryption).await?;
+        let channel = Arc::new(
+            channel
+                .with_network(local_version.network_id)
+                .with_bandwidth(bandwidth.clone()),
+        );
 
         // Write Version request
         channel.write(local_version).await?;
@@ -65,14 +79,19 @@ impl Handshake {
     }
 
     /// Receive the initial Version message from a new peer.
-    /// Send a Verack message + Version message
+    /// Send a Verack message + Version message.
+    /// Switch to framed messages if the peer supports them.
     pub async fn receive_new(
         channel: Channel,
         local_version: &Version,
         remote_version: &Version,
+        transport: &Transport,
+        encryption: &Encryption,
     ) -> Result<Handshake, HandshakeError> {
         // Connect to the address specified in the peer_message
-        let channel = channel.update_writer(local_version.address_receiver).await?;
+        let channel = channel
+            .connect_writer(local_version.address_receiver, transport, encryption)
+            .await?;
 
         // Write Verack response
         channel
@@ -86,6 +105,10 @@ impl Handshake {
         // Write Version request
         channel.write(local_version).await?;
 
+        if remote_version.version >= FRAMED_PROTOCOL_VERSION {
+            channel.enable_framing();
+        }
+
         Ok(Self {
             channel: Arc::new(channel),
             state: HandshakeState::Waiting,
@@ -96,6 +119,7 @@ impl Handshake {
 
     /// Receive the Version message for an existing peer handshake.
     /// Send a Verack message.
+    /// Switch to framed messages if the peer supports them.
     pub async fn receive(&mut self, version: Version) -> Result<(), HandshakeError> {
         // You are the new sender and your peer is the receiver
         let address_receiver = self.channel.address;
@@ -104,6 +128,11 @@ impl Handshake {
         self.channel
             .write(&Verack::new(version.nonce, address_receiver, address_sender))
             .await?;
+
+        if version.version >= FRAMED_PROTOCOL_VERSION {
+            self.channel.enable_framing();
+        }
+
         Ok(())
     }
 
@@ -129,7 +158,7 @@ impl Handshake {
 
     /// Updates the stored reader stream for an existing peer handshake.
     pub fn update_reader(&mut self, channel: Channel) {
-        self.channel = Arc::new(self.channel.update_reader(channel.reader))
+        self.channel = Arc::new(self.channel.update_reader_from(&channel))
     }
 
     /// Returns current handshake state.
@@ -141,7 +170,8 @@ impl Handshake {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::external::message::Message;
+    use crate::external::{message::Message, NodeIdentity, Services};
+    use snarkos_objects::BlockHeaderHash;
     use snarkos_testing::network::random_socket_address;
 
     use serial_test::serial;
@@ -152,6 +182,8 @@ mod tests {
     async fn test_handshake_full() {
         let local_address = random_socket_address();
         let remote_address = random_socket_address();
+        let identity = NodeIdentity::new(0, BlockHeaderHash([0u8; 32]), Services::FULL_NODE);
+        let remote_identity = identity.clone();
 
         // 1. Bind to remote address
 
@@ -162,8 +194,15 @@ mod tests {
 
             // 2. Local node connects to remote. Remote node sends handshake Version
 
-            let local_version = Version::new(1u64, 0u32, remote_address, local_address);
-            let mut handshake = Handshake::send_new(&local_version).await.unwrap();
+            let local_version = Version::new(&identity, 0u32, remote_address, local_address);
+            let mut handshake = Handshake::send_new(
+                &local_version,
+                &Transport::Tcp,
+                &Encryption::default(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
 
             let (reader, _socket) = local_listener.accept().await.unwrap();
             let channel = Channel::new_read_only(reader).unwrap();
@@ -185,6 +224,8 @@ mod tests {
             // 7. Local node sends handshake Verack
 
             handshake.receive(remote_version).await.unwrap();
+
+            assert!(handshake.channel.is_framed());
         });
 
         // 3. Remote node accepts Local node connection
@@ -196,12 +237,20 @@ mod tests {
         // 4. Remote node receives handshake Version.
         // Remote node sends handshake Verack, handshake Version
 
-        let local_version = Version::new(1u64, 0u32, local_address, remote_address);
+        let local_version = Version::new(&remote_identity, 0u32, local_address, remote_address);
         let remote_version = Version::deserialize(bytes).unwrap();
 
-        let mut handshake = Handshake::receive_new(channel, &local_version, &remote_version)
-            .await
-            .unwrap();
+        let mut handshake = Handshake::receive_new(
+            channel,
+            &local_version,
+            &remote_version,
+            &Transport::Tcp,
+            &Encryption::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(handshake.channel.is_framed());
 
         // 8. Remote node accepts handshake Verack
 
@@ -16,34 +16,51 @@
 
 use crate::external::{
     message_types::{GetPeers, Verack, Version},
+    Bandwidth,
     Channel,
+    Encryption,
     Handshake,
     HandshakeState,
     Message,
+    NodeIdentity,
+    Stream,
+    Transport,
 };
 use snarkos_errors::network::HandshakeError;
 
-use std::{collections::HashMap, net::SocketAddr};
-use tokio::net::TcpStream;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
 /// Stores the address and latest state of peers we are handshaking with.
+/// The channels of new handshakes account their traffic in, and are throttled by, the node bandwidth.
 #[derive(Clone, Debug)]
 pub struct Handshakes {
     handshakes: HashMap<SocketAddr, Handshake>,
+    bandwidth: Arc<Bandwidth>,
 }
 
 impl Handshakes {
     /// Construct a new store of connected peer `Handshakes`.
     pub fn new() -> Self {
+        Self::with_bandwidth(Default::default())
+    }
+
+    /// Construct a new store of connected peer `Handshakes` sharing the given node bandwidth.
+    pub fn with_bandwidth(bandwidth: Arc<Bandwidth>) -> Self {
         Self {
             handshakes: HashMap::default(),
+            bandwidth,
         }
     }
 
     /// Create a new handshake with a peer and send a handshake request to them.
     /// If the request is sent successfully, the handshake is stored and returned.
-    pub async fn send_request(&mut self, version: &Version) -> Result<(), HandshakeError> {
-        let handshake = Handshake::send_new(version).await?;
+    pub async fn send_request(
+        &mut self,
+        version: &Version,
+        transport: &Transport,
+        encryption: &Encryption,
+    ) -> Result<(), HandshakeError> {
+        let handshake = Handshake::send_new(version, transport, encryption, &self.bandwidth).await?;
 
         self.handshakes.insert(version.address_receiver.clone(), handshake);
         info!("Request handshake with: {:?}", version.address_receiver);
@@ -52,42 +69,65 @@ impl Handshakes {
     }
 
     /// Receive the first message upon accepting a peer connection.
+    /// The connection is encrypted first if the peer requests it, and rejected if it is unencrypted
+    /// but encryption is required.
     /// If the message is a Version:
-    ///     1. Create a new handshake.
-    ///     2. Send a handshake response.
-    ///     3. If the response is sent successfully, store the handshake.
-    ///     4. Return the handshake, your address as seen by sender, and the version message.
+    ///     1. Reject the peer if `accept_requests` is false, or if it runs on a different network or chain.
+    ///     2. Create a new handshake.
+    ///     3. Send a handshake response.
+    ///     4. If the response is sent successfully, store the handshake.
+    ///     5. Return the handshake, your address as seen by sender, and the peer's version message.
     /// If the message is a Verack:
-    ///     1. Get the existing handshake.
+    ///     1. Get the existing handshake this node requested from the sender address.
     ///     2. Mark the handshake as accepted.
     ///     3. Send a request for peers.
     ///     4. Return the accepted handshake and your address as seen by sender.
     pub async fn receive_any(
         &mut self,
-        version: u64,
+        identity: &NodeIdentity,
+        transport: &Transport,
+        encryption: &Encryption,
         height: u32,
         peer_address: SocketAddr,
-        reader: TcpStream,
+        reader: Stream,
+        accept_requests: bool,
     ) -> Result<(Handshake, SocketAddr, Option<Version>), HandshakeError> {
-        let channel = Channel::new_read_only(reader)?;
+        let channel = Channel::accept(reader, encryption)
+            .await?
+            .with_network(identity.network_id)
+            .with_bandwidth(self.bandwidth.clone());
 
         // Read the first message or error
         let (name, bytes) = channel.read().await?;
 
         // Create and insert a new handshake when the channel contains a version message.
         if Version::name() == name {
+            if !accept_requests {
+                warn!(
+                    "Rejected a handshake request from {} as the inbound slots are full",
+                    peer_address
+                );
+                return Err(HandshakeError::InboundSlotsFull(peer_address));
+            }
+
             let remote_version = Version::deserialize(bytes)?;
 
+            if let Err(error) = identity.check_compatibility(remote_version.network_id, &remote_version.genesis_hash) {
+                warn!("Rejected handshake with {}: {}", peer_address, error);
+                return Err(error);
+            }
+
             // Peer address and specified port from the version message
             let remote_address = SocketAddr::new(peer_address.ip(), remote_version.address_sender.port());
             let local_address = remote_version.address_receiver;
 
-            let local_version = Version::new(version, height, remote_address, local_address);
-            let handshake = Handshake::receive_new(channel, &local_version, &remote_version).await?;
+            let local_version = Version::new(identity, height, remote_address, local_address);
+            let handshake =
+                Handshake::receive_new(channel, &local_version, &remote_version, transport, encryption).await?;
 
             self.handshakes.insert(remote_address, handshake.clone());
 
-            Ok((handshake, local_address, Some(local_version)))
+            Ok((handshake, local_address, Some(remote_version)))
         }
         // Establish the channel when the channel contains a verack message.
         else if Verack::name() == name {
@@ -158,6 +198,11 @@ impl Handshakes {
         self.handshakes.get(&address)
     }
 
+    /// Removes the handshake at a peer address.
+    pub fn remove(&mut self, address: &SocketAddr) -> Option<Handshake> {
+        self.handshakes.remove(address)
+    }
+
     /// Returns a mutable reference to the handshake at a peer address.
     fn get_mut(&mut self, address: &SocketAddr) -> Option<&mut Handshake> {
         self.handshakes.get_mut(&address)
@@ -167,12 +212,18 @@ impl Handshakes {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::external::Message;
+    use crate::external::{Message, Services, StaticKeypair};
+    use snarkos_errors::network::ConnectError;
+    use snarkos_objects::BlockHeaderHash;
     use snarkos_testing::network::random_socket_address;
 
     use serial_test::serial;
     use tokio::net::TcpListener;
 
+    fn test_identity(network_id: u8) -> NodeIdentity {
+        NodeIdentity::new(network_id, BlockHeaderHash([0u8; 32]), Services::FULL_NODE)
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_handshakes() {
@@ -187,10 +238,13 @@ mod tests {
 
             // 2. Local node sends handshake request
 
-            let local_version = Version::new(1u64, 0u32, remote_address, local_address);
+            let local_version = Version::new(&test_identity(0), 0u32, remote_address, local_address);
 
             let mut handshake = Handshakes::new();
-            handshake.send_request(&local_version).await.unwrap();
+            handshake
+                .send_request(&local_version, &Transport::Tcp, &Encryption::default())
+                .await
+                .unwrap();
 
             // 5. Check local node handshake state
 
@@ -225,7 +279,20 @@ mod tests {
         // 4. Remote node sends handshake response, handshake request
 
         let mut handshakes = Handshakes::new();
-        let (handshake, _, _) = handshakes.receive_any(1u64, 0u32, local_address, reader).await.unwrap();
+        let (handshake, _, remote_version) = handshakes
+            .receive_any(
+                &test_identity(0),
+                &Transport::Tcp,
+                &Encryption::default(),
+                0u32,
+                local_address,
+                reader.into(),
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(local_address.port(), remote_version.unwrap().address_sender.port());
 
         assert_eq!(HandshakeState::Waiting, handshakes.get_state(local_address).unwrap());
 
@@ -238,4 +305,161 @@ mod tests {
 
         assert_eq!(HandshakeState::Accepted, handshakes.get_state(local_address).unwrap())
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_handshakes_reject_different_network() {
+        let local_address = random_socket_address();
+        let remote_address = random_socket_address();
+
+        // 1. Bind to remote address
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        // 2. Local node on another network sends handshake request
+        tokio::spawn(async move {
+            let local_version = Version::new(&test_identity(1), 0u32, remote_address, local_address);
+
+            let mut handshake = Handshakes::new();
+            handshake
+                .send_request(&local_version, &Transport::Tcp, &Encryption::default())
+                .await
+                .unwrap();
+        });
+
+        // 3. Remote node rejects the request
+        let (reader, _socket) = remote_listener.accept().await.unwrap();
+
+        let mut handshakes = Handshakes::new();
+        match handshakes
+            .receive_any(
+                &test_identity(0),
+                &Transport::Tcp,
+                &Encryption::default(),
+                0u32,
+                local_address,
+                reader.into(),
+                true,
+            )
+            .await
+        {
+            Err(HandshakeError::NetworkMismatch(1, 0)) => {}
+            result => panic!("expected the handshake to be rejected, got {:?}", result),
+        }
+
+        assert!(handshakes.get_state(local_address).is_none());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_handshakes_encrypted() {
+        let local_address = random_socket_address();
+        let remote_address = random_socket_address();
+
+        let local_encryption = Encryption::new(StaticKeypair::generate().unwrap(), true);
+        let remote_encryption = Encryption::new(StaticKeypair::generate().unwrap(), true);
+
+        // 1. Bind to remote address
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut local_listener = TcpListener::bind(local_address).await.unwrap();
+
+            // 2. Local node sends an encrypted handshake request
+
+            let local_version = Version::new(&test_identity(0), 0u32, remote_address, local_address);
+
+            let mut handshakes = Handshakes::new();
+            handshakes
+                .send_request(&local_version, &Transport::Tcp, &local_encryption)
+                .await
+                .unwrap();
+
+            // 5. Local node accepts the encrypted handshake response
+
+            let (reader, _socket) = local_listener.accept().await.unwrap();
+            let (handshake, _, _) = handshakes
+                .receive_any(
+                    &test_identity(0),
+                    &Transport::Tcp,
+                    &local_encryption,
+                    0u32,
+                    remote_address,
+                    reader.into(),
+                    true,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(HandshakeState::Accepted, handshake.get_state());
+            assert!(handshake.channel.reader_session.is_some());
+        });
+
+        // 3. Remote node accepts Local node connection
+
+        let (reader, _socket) = remote_listener.accept().await.unwrap();
+
+        // 4. Remote node sends an encrypted handshake response, handshake request
+
+        let mut handshakes = Handshakes::new();
+        let (handshake, _, remote_version) = handshakes
+            .receive_any(
+                &test_identity(0),
+                &Transport::Tcp,
+                &remote_encryption,
+                0u32,
+                local_address,
+                reader.into(),
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert!(remote_version.is_some());
+        assert!(handshake.channel.reader_session.is_some());
+        assert!(handshake.channel.writer_session.is_some());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_handshakes_reject_unencrypted() {
+        let local_address = random_socket_address();
+        let remote_address = random_socket_address();
+
+        // 1. Bind to remote address
+        let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+        // 2. Local node sends an unencrypted handshake request
+        tokio::spawn(async move {
+            let local_version = Version::new(&test_identity(0), 0u32, remote_address, local_address);
+
+            let mut handshake = Handshakes::new();
+            handshake
+                .send_request(&local_version, &Transport::Tcp, &Encryption::default())
+                .await
+                .unwrap();
+        });
+
+        // 3. Remote node requiring encryption rejects the request
+        let (reader, _socket) = remote_listener.accept().await.unwrap();
+        let encryption = Encryption::new(StaticKeypair::generate().unwrap(), true);
+
+        let mut handshakes = Handshakes::new();
+        match handshakes
+            .receive_any(
+                &test_identity(0),
+                &Transport::Tcp,
+                &encryption,
+                0u32,
+                local_address,
+                reader.into(),
+                true,
+            )
+            .await
+        {
+            Err(HandshakeError::ConnectError(ConnectError::EncryptionRequired(_))) => {}
+            result => panic!("expected the handshake to be rejected, got {:?}", result),
+        }
+
+        assert!(handshakes.get_state(local_address).is_none());
+    }
 }
@@ -15,8 +15,9 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::external::{
-    message_types::{GetBlock, GetSync},
+    message_types::{GetBlock, GetHeaders, GetSync},
     Channel,
+    HEADERS_PROTOCOL_VERSION,
 };
 use snarkos_errors::network::SendError;
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
@@ -24,9 +25,18 @@ use snarkos_objects::BlockHeaderHash;
 use snarkos_storage::Ledger;
 
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use std::{collections::HashMap, fmt, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::time::delay_for;
 
+/// The maximum number of blocks requested from a single peer at once.
+pub const MAX_BLOCK_REQUESTS_PER_PEER: usize = 8;
+
+/// The number of seconds after which a block request is retried with another peer.
+pub const BLOCK_REQUEST_TIMEOUT_SECS: i64 = 5;
+
+/// The maximum number of sync blocks held in memory until their parent block is synced.
+pub const MAX_BUFFERED_BLOCKS: usize = 512;
+
 #[derive(Clone, PartialEq)]
 pub enum SyncState {
     Idle,
@@ -34,10 +44,92 @@ pub enum SyncState {
     Syncing(DateTime<Utc>, u32),
 }
 
-/// Manages syncing chain state with a sync node.
-/// 1. The server_node sends a GetSync message to a sync_node.
-/// 2. The sync_node responds with a Sync message with block_headers the server_node is missing.
-/// 3. The server_node sends a GetBlock message for each BlockHeaderHash in the message.
+/// A block requested from a peer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockRequest {
+    /// The address of the peer the block was requested from
+    pub peer: SocketAddr,
+    /// The time of the latest request
+    pub requested_at: DateTime<Utc>,
+    /// The number of times the block was requested
+    pub attempts: u32,
+}
+
+/// The progress of a sync towards the highest block height advertised by the peers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncProgress {
+    /// The latest block height of this node
+    pub current_height: u32,
+    /// The highest block height advertised by a peer
+    pub target_height: u32,
+    /// The average number of blocks synced per second since the sync started
+    pub blocks_per_second: f64,
+    /// The estimated number of seconds until the sync is complete, None if no blocks were synced yet
+    pub eta_secs: Option<u64>,
+}
+
+impl SyncProgress {
+    /// Construct a new `SyncProgress` from the heights at the start of the sync and now.
+    pub fn new(start_height: u32, current_height: u32, target_height: u32, elapsed: ChronoDuration) -> Self {
+        let target_height = target_height.max(current_height);
+        let synced_blocks = current_height.saturating_sub(start_height);
+        let elapsed_secs = elapsed.num_milliseconds() as f64 / 1000.;
+
+        let blocks_per_second = match elapsed_secs > 0. {
+            true => synced_blocks as f64 / elapsed_secs,
+            false => 0.,
+        };
+
+        let eta_secs = match blocks_per_second > 0. {
+            true => Some(((target_height - current_height) as f64 / blocks_per_second).ceil() as u64),
+            false => None,
+        };
+
+        Self {
+            current_height,
+            target_height,
+            blocks_per_second,
+            eta_secs,
+        }
+    }
+
+    /// Returns the percentage of the target height that is synced.
+    pub fn percentage(&self) -> f64 {
+        match self.target_height {
+            0 => 100.,
+            target_height => self.current_height as f64 * 100. / target_height as f64,
+        }
+    }
+}
+
+impl fmt::Display for SyncProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "block {} of {} ({:.2}%) at {:.2} blocks/s",
+            self.current_height,
+            self.target_height,
+            self.percentage(),
+            self.blocks_per_second
+        )?;
+
+        match self.eta_secs {
+            Some(eta_secs) => write!(f, ", ETA {}m {}s", eta_secs / 60, eta_secs % 60),
+            None => write!(f, ", ETA unknown"),
+        }
+    }
+}
+
+/// Manages syncing chain state with the connected peers.
+/// 1. The server_node sends a GetSync message to a sync_node,
+///    or a GetHeaders message if the sync_node supports headers-first sync.
+/// 2. The sync_node responds with a Sync message with block_headers the server_node is missing,
+///    or with a Headers message whose header chain is validated before any block is requested.
+/// 3. The server_node splits the missing block headers among all peers with a greater height
+///    and sends them GetBlock messages, up to `MAX_BLOCK_REQUESTS_PER_PEER` at a time.
+/// 4. Requests that time out are sent again to a different peer.
+/// 5. Blocks that arrive before their parent are buffered until the parent is synced,
+///    and requested again if their parent is no longer requested.
 pub struct SyncHandler {
     /// The address of the sync node
     pub sync_node: SocketAddr,
@@ -45,8 +137,18 @@ pub struct SyncHandler {
     pub sync_state: SyncState,
     /// Block headers of blocks that need to be downloaded
     pub block_headers: Vec<BlockHeaderHash>,
-    /// Pending blocks - Blocks that have been requested and the time of the request
-    pub pending_blocks: HashMap<BlockHeaderHash, DateTime<Utc>>,
+    /// Pending blocks - Blocks that have been requested, and the peer and time of the request
+    pub pending_blocks: HashMap<BlockHeaderHash, BlockRequest>,
+    /// The latest block heights advertised by the connected peers
+    pub peer_heights: HashMap<SocketAddr, u32>,
+    /// The network protocol versions of the connected peers
+    pub peer_versions: HashMap<SocketAddr, u64>,
+    /// Buffered blocks - The parent hash and serialized block of sync blocks keyed by their hash
+    pub buffered_blocks: HashMap<BlockHeaderHash, (BlockHeaderHash, Vec<u8>)>,
+    /// The hashes of the buffered blocks keyed by their parent hash
+    buffered_children: HashMap<BlockHeaderHash, Vec<BlockHeaderHash>>,
+    /// The time and block height at the start of the current sync
+    sync_start: Option<(DateTime<Utc>, u32)>,
 }
 
 impl SyncHandler {
@@ -55,8 +157,13 @@ impl SyncHandler {
         Self {
             block_headers: vec![],
             pending_blocks: HashMap::new(),
+            peer_heights: HashMap::new(),
+            peer_versions: HashMap::new(),
+            buffered_blocks: HashMap::new(),
+            buffered_children: HashMap::new(),
             sync_node,
             sync_state: SyncState::Idle,
+            sync_start: None,
         }
     }
 
@@ -70,104 +177,320 @@ impl SyncHandler {
 
     /// Returns if the time of the block request, or None if the block was not requested.
     pub fn is_pending(&self, block_header_hash: &BlockHeaderHash) -> Option<DateTime<Utc>> {
-        self.pending_blocks.get(block_header_hash).map(|time| time.clone())
+        self.pending_blocks
+            .get(block_header_hash)
+            .map(|request| request.requested_at.clone())
+    }
+
+    /// Returns true if the block is queued, requested or buffered by the sync handler.
+    pub fn is_requested(&self, block_header_hash: &BlockHeaderHash) -> bool {
+        self.pending_blocks.contains_key(block_header_hash)
+            || self.block_headers.contains(block_header_hash)
+            || self.buffered_blocks.contains_key(block_header_hash)
     }
 
     /// Remove the blocks that are now included in the chain.
+    /// The buffered blocks whose parent is no longer requested are queued to be requested again.
     pub fn clear_pending<T: Transaction, P: LoadableMerkleParameters>(&mut self, storage: Arc<Ledger<T, P>>) {
-        for (block_hash, _time_sent) in &self.pending_blocks.clone() {
-            if !storage.block_hash_exists(&block_hash) {
+        for (block_hash, _request) in &self.pending_blocks.clone() {
+            if storage.block_hash_exists(&block_hash) {
                 self.pending_blocks.remove(block_hash);
             }
         }
+
+        loop {
+            let orphaned_parents: Vec<BlockHeaderHash> = self
+                .buffered_children
+                .keys()
+                .filter(|parent| !self.is_requested(parent))
+                .cloned()
+                .collect();
+
+            if orphaned_parents.is_empty() {
+                break;
+            }
+
+            for parent in orphaned_parents {
+                for (block_hash, _block) in self.take_buffered_children(&parent) {
+                    if !storage.block_hash_exists(&block_hash) {
+                        self.block_headers.insert(0, block_hash);
+                    }
+                }
+            }
+        }
     }
 
-    /// Set the SyncState to syncing and update the latest block height.
-    pub fn update_syncing(&mut self, block_height: u32) {
+    /// Set the SyncState to syncing at the given date and update the latest block height.
+    pub fn update_syncing(&mut self, block_height: u32, now: DateTime<Utc>) {
         match self.sync_state {
             SyncState::Idle => {
                 info!("Syncing blocks");
-                self.sync_state = SyncState::Syncing(Utc::now(), block_height)
+                self.sync_state = SyncState::Syncing(now, block_height);
+                self.sync_start = Some((now, block_height));
             }
-            SyncState::Syncing(_date_time, _old_height) => {
-                self.sync_state = SyncState::Syncing(Utc::now(), block_height)
+            SyncState::Syncing(_date_time, _old_height) => self.sync_state = SyncState::Syncing(now, block_height),
+        }
+    }
+
+    /// Stores the latest block height advertised by a peer.
+    pub fn update_peer_height(&mut self, address: SocketAddr, height: u32) {
+        self.peer_heights.insert(address, height);
+    }
+
+    /// Stores the network protocol version of a peer.
+    pub fn update_peer_version(&mut self, address: SocketAddr, version: u64) {
+        self.peer_versions.insert(address, version);
+    }
+
+    /// Returns true if the peer serves block headers for headers-first sync.
+    pub fn supports_headers(&self, address: &SocketAddr) -> bool {
+        self.peer_versions
+            .get(address)
+            .map_or(false, |version| *version >= HEADERS_PROTOCOL_VERSION)
+    }
+
+    /// Forgets a disconnected peer and queues the blocks requested from it again.
+    pub fn remove_peer(&mut self, address: &SocketAddr) {
+        self.peer_heights.remove(address);
+        self.peer_versions.remove(address);
+
+        let mut requeued = vec![];
+        for (block_hash, request) in &self.pending_blocks {
+            if request.peer == *address {
+                requeued.push(block_hash.clone());
             }
         }
+
+        for block_hash in requeued.into_iter().rev() {
+            self.pending_blocks.remove(&block_hash);
+            self.block_headers.insert(0, block_hash);
+        }
+    }
+
+    /// Returns the highest block height advertised by a peer.
+    pub fn target_height(&self) -> Option<u32> {
+        self.peer_heights.values().max().copied()
+    }
+
+    /// Returns the progress of the current sync at the given date, or None if the node is not syncing.
+    pub fn progress(&self, current_height: u32, now: DateTime<Utc>) -> Option<SyncProgress> {
+        let (start_time, start_height) = self.sync_start?;
+        let target_height = self.target_height().unwrap_or(current_height);
+
+        Some(SyncProgress::new(
+            start_height,
+            current_height,
+            target_height,
+            now - start_time,
+        ))
     }
 
     /// Process a vector of block header hashes.
-    /// Push new hashes to the sync handler so we can ask the sync node for them.
-    pub fn receive_hashes(&mut self, hashes: Vec<BlockHeaderHash>, height: u32) {
+    /// Push new hashes to the sync handler so we can ask the peers for them.
+    pub fn receive_hashes(&mut self, hashes: Vec<BlockHeaderHash>, height: u32, now: DateTime<Utc>) {
         if hashes.len() > 0 {
             for block_hash in hashes {
-                if !self.block_headers.contains(&block_hash) && self.pending_blocks.get(&block_hash).is_none() {
+                if !self.is_requested(&block_hash) {
                     self.block_headers.push(block_hash.clone());
                 }
-                self.update_syncing(height);
+                self.update_syncing(height, now);
             }
         } else if self.pending_blocks.is_empty() {
             info!("Sync state is set to Idle");
             self.sync_state = SyncState::Idle;
+            self.sync_start = None;
+            self.buffered_blocks.clear();
+            self.buffered_children.clear();
+        }
+    }
+
+    /// Holds a sync block until its parent is synced.
+    /// Returns false if the buffer is full, in which case the block stays pending and is requested again.
+    pub fn buffer_block(&mut self, parent: BlockHeaderHash, block_hash: BlockHeaderHash, block: Vec<u8>) -> bool {
+        if self.buffered_blocks.len() >= MAX_BUFFERED_BLOCKS {
+            return false;
+        }
+
+        self.pending_blocks.remove(&block_hash);
+        if self
+            .buffered_blocks
+            .insert(block_hash.clone(), (parent.clone(), block))
+            .is_none()
+        {
+            self.buffered_children.entry(parent).or_default().push(block_hash);
+        }
+
+        true
+    }
+
+    /// Removes and returns the hashes and serialized blocks of the buffered blocks building on the given parent.
+    pub fn take_buffered_children(&mut self, parent: &BlockHeaderHash) -> Vec<(BlockHeaderHash, Vec<u8>)> {
+        let children = self.buffered_children.remove(parent).unwrap_or_default();
+
+        children
+            .into_iter()
+            .filter_map(|block_hash| {
+                let (_parent, block) = self.buffered_blocks.remove(&block_hash)?;
+                Some((block_hash, block))
+            })
+            .collect()
+    }
+
+    /// Assigns the timed out and queued block requests to the given peers.
+    /// Timed out requests are retried with a different peer if possible, and queued blocks
+    /// are spread over the least loaded peers. Returns the requests that should be sent.
+    pub fn schedule_requests(
+        &mut self,
+        peers: &[SocketAddr],
+        now: DateTime<Utc>,
+    ) -> Vec<(SocketAddr, BlockHeaderHash)> {
+        let mut requests = vec![];
+
+        if peers.is_empty() {
+            return requests;
+        }
+
+        let mut in_flight: HashMap<SocketAddr, usize> = HashMap::new();
+        for request in self.pending_blocks.values() {
+            *in_flight.entry(request.peer).or_insert(0) += 1;
+        }
+
+        // Retry the timed out requests with a different peer.
+
+        let timeout = ChronoDuration::seconds(BLOCK_REQUEST_TIMEOUT_SECS);
+        let mut timed_out: Vec<(BlockHeaderHash, BlockRequest)> = self
+            .pending_blocks
+            .iter()
+            .filter(|(_, request)| now - request.requested_at > timeout)
+            .map(|(block_hash, request)| (block_hash.clone(), request.clone()))
+            .collect();
+        timed_out.sort_by_key(|(_, request)| request.requested_at);
+
+        for (block_hash, request) in timed_out {
+            if let Some(count) = in_flight.get_mut(&request.peer) {
+                *count -= 1;
+            }
+
+            let peer = match least_loaded_peer(peers, &in_flight, Some(request.peer)) {
+                Some(peer) => peer,
+                None => break,
+            };
+
+            debug!(
+                "Block request timed out, requesting {} from {} (attempt {})",
+                hex::encode(&block_hash.0),
+                peer,
+                request.attempts + 1
+            );
+
+            *in_flight.entry(peer).or_insert(0) += 1;
+            self.pending_blocks.insert(block_hash.clone(), BlockRequest {
+                peer,
+                requested_at: now,
+                attempts: request.attempts + 1,
+            });
+            requests.push((peer, block_hash));
         }
+
+        // Spread the queued blocks over the peers with free request slots.
+
+        while !self.block_headers.is_empty() {
+            let peer = match least_loaded_peer(peers, &in_flight, None) {
+                Some(peer) => peer,
+                None => break,
+            };
+
+            let block_hash = self.block_headers.remove(0);
+            if self.pending_blocks.contains_key(&block_hash) {
+                continue;
+            }
+
+            *in_flight.entry(peer).or_insert(0) += 1;
+            self.pending_blocks.insert(block_hash.clone(), BlockRequest {
+                peer,
+                requested_at: now,
+                attempts: 1,
+            });
+            requests.push((peer, block_hash));
+        }
+
+        requests
     }
 
-    /// Finish syncing or ask for the next block from the sync node.
+    /// Ask the sync node over the given channel for the blocks following the block locator hashes.
+    /// Sync nodes supporting headers-first sync are asked for the block headers, others for the block hashes.
+    pub async fn request_sync(
+        &self,
+        channel: &Channel,
+        block_locator_hashes: Vec<BlockHeaderHash>,
+    ) -> Result<(), SendError> {
+        if self.supports_headers(&self.sync_node) {
+            channel.write(&GetHeaders::new(block_locator_hashes)).await?;
+        } else {
+            channel.write(&GetSync::new(block_locator_hashes)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Finish syncing or ask the peers for the next blocks.
+    /// Blocks are requested from the sync node and from every peer advertising a greater height,
+    /// and the requests older than `BLOCK_REQUEST_TIMEOUT_SECS` at the given date are sent to another peer.
     pub async fn increment<T: Transaction, P: LoadableMerkleParameters>(
         &mut self,
-        channel: Arc<Channel>,
+        channels: &[Arc<Channel>],
         storage: Arc<Ledger<T, P>>,
+        now: DateTime<Utc>,
     ) -> Result<(), SendError> {
         if let SyncState::Syncing(date_time, height) = self.sync_state {
-            if storage.get_latest_block_height() > height {
+            let latest_height = storage.get_latest_block_height();
+
+            if latest_height > height {
                 debug!(
                     "Synced {} Block(s) in {:.2} seconds",
-                    storage.get_latest_block_height() - height,
-                    (Utc::now() - date_time).num_milliseconds() as f64 / 1000.
+                    latest_height - height,
+                    (now - date_time).num_milliseconds() as f64 / 1000.
                 );
-                self.update_syncing(storage.get_latest_block_height());
-            }
+                self.update_syncing(latest_height, now);
 
-            // Sync up to 3 blocks at once
-            for _ in 0..3 {
-                if self.block_headers.is_empty() {
-                    break;
+                if let Some(progress) = self.progress(latest_height, now) {
+                    info!("Sync progress: {}", progress);
                 }
+            }
 
-                let block_header_hash = self.block_headers.remove(0);
+            self.clear_pending(Arc::clone(&storage));
+            self.block_headers
+                .retain(|block_hash| !storage.block_hash_exists(block_hash));
 
-                // If block is not pending, then ask the sync node for the block.
-                let should_request = match self.pending_blocks.get(&block_header_hash) {
-                    Some(request_time) => {
-                        // Request the block again if the block was not downloaded in 5 seconds
-                        Utc::now() - request_time.clone() > ChronoDuration::seconds(5)
-                    }
-                    None => !storage.block_hash_exists(&block_header_hash),
-                };
+            let peers: Vec<SocketAddr> = channels
+                .iter()
+                .map(|channel| channel.address)
+                .filter(|address| {
+                    *address == self.sync_node
+                        || self
+                            .peer_heights
+                            .get(address)
+                            .map_or(false, |peer_height| *peer_height > latest_height)
+                })
+                .collect();
 
-                if should_request {
-                    channel.write(&GetBlock::new(block_header_hash.clone())).await?;
-                    self.pending_blocks.insert(block_header_hash, Utc::now());
+            for (peer, block_header_hash) in self.schedule_requests(&peers, now) {
+                if let Some(channel) = channels.iter().find(|channel| channel.address == peer) {
+                    channel.write(&GetBlock::new(block_header_hash)).await?;
                 }
             }
 
             // Request more block headers
 
-            if self.pending_blocks.is_empty() {
+            if self.pending_blocks.is_empty() && self.block_headers.is_empty() {
                 delay_for(Duration::from_millis(500)).await;
 
-                if let Ok(block_locator_hashes) = storage.get_block_locator_hashes() {
-                    channel.write(&GetSync::new(block_locator_hashes)).await?;
-                }
-            } else {
-                for (block_header_hash, request_time) in &self.pending_blocks.clone() {
-                    if Utc::now() - request_time.clone() > ChronoDuration::seconds(5) {
-                        channel.write(&GetBlock::new(block_header_hash.clone())).await?;
-                        self.pending_blocks.insert(block_header_hash.clone(), Utc::now());
+                if let Some(channel) = channels.iter().find(|channel| channel.address == self.sync_node) {
+                    if let Ok(block_locator_hashes) = storage.get_block_locator_hashes() {
+                        self.request_sync(channel, block_locator_hashes).await?;
                     }
                 }
             }
-
-            self.clear_pending(Arc::clone(&storage));
         } else {
             self.clear_pending(Arc::clone(&storage));
         }
@@ -175,3 +498,192 @@ impl SyncHandler {
         Ok(())
     }
 }
+
+/// Returns the peer with the fewest requests in flight that has a free request slot,
+/// preferring peers other than `excluded`.
+fn least_loaded_peer(
+    peers: &[SocketAddr],
+    in_flight: &HashMap<SocketAddr, usize>,
+    excluded: Option<SocketAddr>,
+) -> Option<SocketAddr> {
+    let load = |peer: &SocketAddr| *in_flight.get(peer).unwrap_or(&0);
+    let available = |peer: &&SocketAddr| load(*peer) < MAX_BLOCK_REQUESTS_PER_PEER;
+
+    peers
+        .iter()
+        .filter(available)
+        .filter(|peer| Some(**peer) != excluded)
+        .min_by_key(|peer| load(*peer))
+        .or_else(|| peers.iter().filter(available).min_by_key(|peer| load(*peer)))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_testing::{consensus::FIXTURE_VK, storage::kill_storage_sync};
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn block_hash(byte: u8) -> BlockHeaderHash {
+        BlockHeaderHash([byte; 32])
+    }
+
+    #[test]
+    fn test_schedule_requests_across_peers() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes((0..20).map(block_hash).collect(), 0, Utc::now());
+
+        let requests = sync_handler.schedule_requests(&[peer(1), peer(2)], Utc::now());
+
+        // Both peers fill their request slots
+        assert_eq!(2 * MAX_BLOCK_REQUESTS_PER_PEER, requests.len());
+        assert_eq!(
+            MAX_BLOCK_REQUESTS_PER_PEER,
+            requests.iter().filter(|(address, _)| *address == peer(1)).count()
+        );
+        assert_eq!(20 - 2 * MAX_BLOCK_REQUESTS_PER_PEER, sync_handler.block_headers.len());
+
+        // Blocks are requested in order
+        assert_eq!(block_hash(0), requests[0].1);
+        assert_eq!(block_hash(1), requests[1].1);
+    }
+
+    #[test]
+    fn test_retry_timed_out_request_with_other_peer() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes(vec![block_hash(0)], 0, Utc::now());
+
+        let start = Utc::now();
+        let requests = sync_handler.schedule_requests(&[peer(1), peer(2)], start);
+        assert_eq!(vec![(peer(1), block_hash(0))], requests);
+
+        // The request is not retried before it times out
+        let requests = sync_handler.schedule_requests(&[peer(1), peer(2)], start + ChronoDuration::seconds(1));
+        assert!(requests.is_empty());
+
+        let later = start + ChronoDuration::seconds(BLOCK_REQUEST_TIMEOUT_SECS + 1);
+        let requests = sync_handler.schedule_requests(&[peer(1), peer(2)], later);
+        assert_eq!(vec![(peer(2), block_hash(0))], requests);
+        assert_eq!(2, sync_handler.pending_blocks[&block_hash(0)].attempts);
+    }
+
+    #[test]
+    fn test_remove_peer_requeues_requests() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.update_peer_height(peer(2), 10);
+        sync_handler.receive_hashes(vec![block_hash(0), block_hash(1)], 0, Utc::now());
+        sync_handler.schedule_requests(&[peer(2)], Utc::now());
+
+        sync_handler.remove_peer(&peer(2));
+
+        assert!(sync_handler.pending_blocks.is_empty());
+        assert_eq!(2, sync_handler.block_headers.len());
+        assert_eq!(None, sync_handler.target_height());
+    }
+
+    #[test]
+    fn test_clear_pending() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let genesis_hash = storage.get_block_hash(0).unwrap();
+
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes(vec![genesis_hash.clone(), block_hash(1)], 0, Utc::now());
+        sync_handler.schedule_requests(&[peer(1)], Utc::now());
+
+        sync_handler.clear_pending(storage.clone());
+
+        // Only the block included in the chain is no longer pending
+        assert!(sync_handler.is_pending(&genesis_hash).is_none());
+        assert!(sync_handler.is_pending(&block_hash(1)).is_some());
+
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_supports_headers() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.update_peer_version(peer(1), HEADERS_PROTOCOL_VERSION - 1);
+        sync_handler.update_peer_version(peer(2), HEADERS_PROTOCOL_VERSION);
+
+        assert!(!sync_handler.supports_headers(&peer(1)));
+        assert!(sync_handler.supports_headers(&peer(2)));
+        assert!(!sync_handler.supports_headers(&peer(3)));
+
+        sync_handler.remove_peer(&peer(2));
+        assert!(!sync_handler.supports_headers(&peer(2)));
+    }
+
+    #[test]
+    fn test_buffer_block() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes(vec![block_hash(1), block_hash(2)], 0, Utc::now());
+        sync_handler.schedule_requests(&[peer(1)], Utc::now());
+
+        // Block 2 arrives before its parent, block 1
+        assert!(sync_handler.buffer_block(block_hash(1), block_hash(2), vec![2]));
+        assert!(sync_handler.is_pending(&block_hash(2)).is_none());
+        assert!(sync_handler.is_requested(&block_hash(2)));
+
+        assert!(sync_handler.take_buffered_children(&block_hash(0)).is_empty());
+        assert_eq!(
+            vec![(block_hash(2), vec![2])],
+            sync_handler.take_buffered_children(&block_hash(1))
+        );
+        assert!(!sync_handler.is_requested(&block_hash(2)));
+    }
+
+    #[test]
+    fn test_buffer_sibling_blocks() {
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes(vec![block_hash(1), block_hash(2), block_hash(3)], 0, Utc::now());
+        sync_handler.schedule_requests(&[peer(1)], Utc::now());
+
+        // Blocks 2 and 3 both build on block 1
+        assert!(sync_handler.buffer_block(block_hash(1), block_hash(2), vec![2]));
+        assert!(sync_handler.buffer_block(block_hash(1), block_hash(3), vec![3]));
+
+        assert_eq!(
+            vec![(block_hash(2), vec![2]), (block_hash(3), vec![3])],
+            sync_handler.take_buffered_children(&block_hash(1))
+        );
+        assert!(sync_handler.buffered_blocks.is_empty());
+    }
+
+    #[test]
+    fn test_requeue_orphaned_buffered_blocks() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+
+        let mut sync_handler = SyncHandler::new(peer(1));
+        sync_handler.receive_hashes(vec![block_hash(1), block_hash(2), block_hash(3)], 0, Utc::now());
+        sync_handler.schedule_requests(&[peer(1)], Utc::now());
+
+        assert!(sync_handler.buffer_block(block_hash(1), block_hash(2), vec![2]));
+        assert!(sync_handler.buffer_block(block_hash(2), block_hash(3), vec![3]));
+
+        // Block 1 is no longer requested, so its buffered child is requested again,
+        // and the block building on the child stays buffered until the child is synced
+        sync_handler.pending_blocks.remove(&block_hash(1));
+        sync_handler.clear_pending(storage.clone());
+
+        assert_eq!(vec![block_hash(2)], sync_handler.block_headers);
+        assert!(sync_handler.is_pending(&block_hash(2)).is_none());
+        assert!(sync_handler.buffered_blocks.contains_key(&block_hash(3)));
+
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_sync_progress() {
+        let progress = SyncProgress::new(100, 200, 1100, ChronoDuration::seconds(10));
+
+        assert_eq!(10., progress.blocks_per_second);
+        assert_eq!(Some(90), progress.eta_secs);
+        assert!((progress.percentage() - 18.18).abs() < 0.01);
+
+        let progress = SyncProgress::new(100, 100, 1100, ChronoDuration::seconds(10));
+        assert_eq!(None, progress.eta_secs);
+    }
+}
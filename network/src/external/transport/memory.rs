@@ -0,0 +1,512 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    net::{Shutdown, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+    time::{delay_until, Instant},
+};
+
+/// The rules applied to the messages sent over the link between two nodes of a `MemoryNetwork`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkRules {
+    /// The time a message takes to reach the peer
+    pub latency: Duration,
+    /// The probability of a message being lost, between 0 and 1
+    pub drop_rate: f64,
+}
+
+impl LinkRules {
+    /// Construct new `LinkRules` delaying messages by the latency and losing them at the drop rate.
+    pub fn new(latency: Duration, drop_rate: f64) -> Self {
+        Self { latency, drop_rate }
+    }
+}
+
+impl Default for LinkRules {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(0), 0.)
+    }
+}
+
+/// The listeners and link rules of a `MemoryNetwork`.
+struct NetworkState {
+    listeners: HashMap<SocketAddr, mpsc::UnboundedSender<MemoryStream>>,
+    default_rules: LinkRules,
+    link_rules: HashMap<(SocketAddr, SocketAddr), LinkRules>,
+    partitions: HashMap<SocketAddr, usize>,
+    rng: StdRng,
+}
+
+impl NetworkState {
+    /// Returns true if the nodes are not separated by a partition.
+    fn is_reachable(&self, a: SocketAddr, b: SocketAddr) -> bool {
+        match (self.partitions.get(&a), self.partitions.get(&b)) {
+            (Some(partition_a), Some(partition_b)) => partition_a == partition_b,
+            _ => true,
+        }
+    }
+
+    /// Returns the rules of the link between two nodes.
+    fn rules(&self, a: SocketAddr, b: SocketAddr) -> &LinkRules {
+        self.link_rules.get(&link(a, b)).unwrap_or(&self.default_rules)
+    }
+}
+
+/// Returns the key of the link between two nodes, which is the same in both directions.
+fn link(a: SocketAddr, b: SocketAddr) -> (SocketAddr, SocketAddr) {
+    (a.min(b), a.max(b))
+}
+
+/// An in-memory network connecting the nodes of a simulation without binding any ports.
+/// Each message flushed to a stream is delivered to the peer once the latency of their link has passed
+/// Each message written to a stream is delivered to the peer once the latency of their link has passed
+/// on the tokio clock, so a network of nodes can be simulated with the clock paused and advanced by hand.
+/// Messages are lost at the drop rate of their link, or if their nodes are split by a partition.
+/// Connection requests across a partition are refused.
+///
+/// Lost messages are chosen by a seeded random number generator,
+/// so a simulation loses the same messages every time it is run.
+///
+/// The nodes of a memory network read the time from its simulated clock,
+/// which starts at the time the network is created and advances with the tokio clock.
+pub struct MemoryNetwork {
+    state: Mutex<NetworkState>,
+    /// The time the network was created, and the instant of the tokio clock at that time
+    start: (DateTime<Utc>, Instant),
+}
+
+impl MemoryNetwork {
+    /// Construct a new `MemoryNetwork` losing messages with the given random seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Mutex::new(NetworkState {
+                listeners: HashMap::new(),
+                default_rules: LinkRules::default(),
+                link_rules: HashMap::new(),
+                partitions: HashMap::new(),
+                rng: StdRng::seed_from_u64(seed),
+            }),
+            start: (Utc::now(), Instant::now()),
+        }
+    }
+
+    /// Returns the current time of the simulated clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        let (start_time, start_instant) = self.start;
+        let elapsed = Instant::now().saturating_duration_since(start_instant);
+
+        start_time + chrono::Duration::from_std(elapsed).unwrap_or_else(|_| chrono::Duration::zero())
+    }
+
+    /// Sets the rules of all links without rules of their own.
+    pub fn set_default_rules(&self, rules: LinkRules) {
+        self.state().default_rules = rules;
+    }
+
+    /// Sets the rules of the link between two nodes, in both directions.
+    pub fn set_link_rules(&self, a: SocketAddr, b: SocketAddr, rules: LinkRules) {
+        self.state().link_rules.insert(link(a, b), rules);
+    }
+
+    /// Splits the network into the given groups of nodes.
+    /// Nodes in different groups can't reach each other, nodes outside of all groups can reach every node.
+    pub fn partition(&self, groups: &[Vec<SocketAddr>]) {
+        let mut state = self.state();
+
+        state.partitions.clear();
+        for (partition, group) in groups.iter().enumerate() {
+            for address in group {
+                state.partitions.insert(*address, partition);
+            }
+        }
+    }
+
+    /// Removes all partitions.
+    pub fn heal(&self) {
+        self.state().partitions.clear();
+    }
+
+    /// Returns true if the nodes are not split by a partition.
+    pub fn is_reachable(&self, a: SocketAddr, b: SocketAddr) -> bool {
+        self.state().is_reachable(a, b)
+    }
+
+    /// Returns a listener accepting the connections made to the given address.
+    pub fn bind(self: Arc<Self>, address: SocketAddr) -> io::Result<MemoryListener> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        {
+            let mut state = self.state();
+            if state.listeners.contains_key(&address) {
+                return Err(io::ErrorKind::AddrInUse.into());
+            }
+            state.listeners.insert(address, sender);
+        }
+
+        Ok(MemoryListener {
+            address,
+            network: self,
+            receiver,
+        })
+    }
+
+    /// Returns a stream from the node at the local address to the node listening at the remote address.
+    pub fn connect(self: Arc<Self>, local_address: SocketAddr, remote_address: SocketAddr) -> io::Result<MemoryStream> {
+        let state = self.state();
+
+        if !state.is_reachable(local_address, remote_address) {
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        }
+
+        let listener = match state.listeners.get(&remote_address) {
+            Some(listener) => listener,
+            None => return Err(io::ErrorKind::ConnectionRefused.into()),
+        };
+
+        let (sender, peer_receiver) = deliver();
+        let (peer_sender, receiver) = deliver();
+
+        let stream = MemoryStream::new(self.clone(), local_address, remote_address, sender, receiver);
+        let peer_stream = MemoryStream::new(self.clone(), remote_address, local_address, peer_sender, peer_receiver);
+
+        match listener.send(peer_stream) {
+            Ok(()) => Ok(stream),
+            Err(_) => Err(io::ErrorKind::ConnectionRefused.into()),
+        }
+    }
+
+    /// Returns the time after which a message sent from one node to another is delivered,
+    /// or None if the message is lost.
+    fn route(&self, from: SocketAddr, to: SocketAddr) -> Option<Duration> {
+        let mut state = self.state();
+
+        if !state.is_reachable(from, to) {
+            return None;
+        }
+
+        let rules = state.rules(from, to).clone();
+        match rules.drop_rate > 0. && state.rng.gen::<f64>() < rules.drop_rate {
+            true => None,
+            false => Some(rules.latency),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<NetworkState> {
+        self.state.lock().expect("memory network lock poisoned")
+    }
+}
+
+impl fmt::Debug for MemoryNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemoryNetwork")
+    }
+}
+
+/// Returns the ends of a one-directional link, delivering each message at the time it was sent with.
+fn deliver() -> (
+    mpsc::UnboundedSender<(Instant, Vec<u8>)>,
+    mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    let (sender, mut in_flight) = mpsc::unbounded_channel::<(Instant, Vec<u8>)>();
+    let (delivered, receiver) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some((delivery_time, message)) = in_flight.recv().await {
+            delay_until(delivery_time).await;
+
+            if delivered.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    (sender, receiver)
+}
+
+/// Accepts the connections made to an address of a `MemoryNetwork`.
+#[derive(Debug)]
+pub struct MemoryListener {
+    address: SocketAddr,
+    network: Arc<MemoryNetwork>,
+    receiver: mpsc::UnboundedReceiver<MemoryStream>,
+}
+
+impl MemoryListener {
+    /// Returns the next stream connected to this listener.
+    pub async fn accept(&mut self) -> io::Result<MemoryStream> {
+        match self.receiver.recv().await {
+            Some(stream) => Ok(stream),
+            None => Err(io::ErrorKind::NotConnected.into()),
+        }
+    }
+}
+
+impl Drop for MemoryListener {
+    fn drop(&mut self) {
+        self.network.state().listeners.remove(&self.address);
+    }
+}
+
+/// A stream between two nodes of a `MemoryNetwork`.
+/// The bytes written since the previous flush are sent to the peer as a single message,
+/// which is delivered or lost as a whole according to the rules of their link.
+#[derive(Debug)]
+pub struct MemoryStream {
+    network: Arc<MemoryNetwork>,
+    local_address: SocketAddr,
+    peer_address: SocketAddr,
+    sender: Option<mpsc::UnboundedSender<(Instant, Vec<u8>)>>,
+    receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+    /// The bytes of the latest delivered message that have not been read yet
+    buffer: Vec<u8>,
+    /// The bytes written since the latest flush
+    unsent: Vec<u8>,
+    /// The delivery time of the latest message sent, so messages are delivered in order
+    last_delivery: Instant,
+}
+
+impl MemoryStream {
+    fn new(
+        network: Arc<MemoryNetwork>,
+        local_address: SocketAddr,
+        peer_address: SocketAddr,
+        sender: mpsc::UnboundedSender<(Instant, Vec<u8>)>,
+        receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+    ) -> Self {
+        Self {
+            network,
+            local_address,
+            peer_address,
+            sender: Some(sender),
+            receiver,
+            buffer: vec![],
+            unsent: vec![],
+            last_delivery: Instant::now(),
+        }
+    }
+
+    /// Returns the address of the peer.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_address
+    }
+
+    /// Receives bytes from the peer without consuming them.
+    pub async fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.buffer.is_empty() {
+            match self.receiver.recv().await {
+                Some(message) => self.buffer = message,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.buffer.len());
+        buf[..len].copy_from_slice(&self.buffer[..len]);
+
+        Ok(len)
+    }
+
+    /// Sends the bytes written since the latest flush to the peer as a single message.
+    fn send_unsent(&mut self) -> io::Result<()> {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return Err(io::ErrorKind::BrokenPipe.into()),
+        };
+
+        if self.unsent.is_empty() {
+            return Ok(());
+        }
+
+        let message = std::mem::take(&mut self.unsent);
+
+        if let Some(latency) = self.network.route(self.local_address, self.peer_address) {
+            let delivery_time = self.last_delivery.max(Instant::now() + latency);
+            self.last_delivery = delivery_time;
+
+            if sender.send((delivery_time, message)).is_err() {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops sending or receiving messages.
+    /// The bytes written since the latest flush are sent before the write half is shut down.
+    pub fn shutdown(&mut self, how: Shutdown) {
+        if how != Shutdown::Read {
+            let _ = self.send_unsent();
+            self.sender = None;
+        }
+        if how != Shutdown::Write {
+            self.receiver.close();
+        }
+    }
+}
+
+impl AsyncRead for MemoryStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        while this.buffer.is_empty() {
+            match this.receiver.poll_recv(cx) {
+                Poll::Ready(Some(message)) => this.buffer = message,
+                Poll::Ready(None) => return Poll::Ready(Ok(0)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let len = buf.len().min(this.buffer.len());
+        buf[..len].copy_from_slice(&this.buffer[..len]);
+        this.buffer.drain(..len);
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl AsyncWrite for MemoryStream {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        if this.sender.is_none() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+
+        this.unsent.extend_from_slice(buf);
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.get_mut().send_unsent())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().shutdown(Shutdown::Write);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        time::{advance, pause, timeout},
+    };
+
+    fn address(id: u8) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, id], 4130))
+    }
+
+    #[tokio::test]
+    async fn test_latency() {
+        pause();
+
+        let network = Arc::new(MemoryNetwork::new(0));
+        network.set_default_rules(LinkRules::new(Duration::from_secs(1), 0.));
+
+        let mut listener = network.clone().bind(address(1)).unwrap();
+        let mut stream = network.clone().connect(address(2), address(1)).unwrap();
+        let mut peer_stream = listener.accept().await.unwrap();
+
+        assert_eq!(address(2), peer_stream.peer_addr());
+
+        stream.write_all(b"ping").await.unwrap();
+        stream.flush().await.unwrap();
+        let mut message = [0u8; 4];
+
+        // The message is still in flight
+        advance(Duration::from_millis(500)).await;
+        assert!(timeout(Duration::from_millis(0), peer_stream.read_exact(&mut message))
+            .await
+            .is_err());
+
+        // The message arrived
+        advance(Duration::from_millis(500)).await;
+        peer_stream.read_exact(&mut message).await.unwrap();
+
+        assert_eq!(b"ping", &message);
+    }
+
+    #[tokio::test]
+    async fn test_now() {
+        pause();
+
+        let network = MemoryNetwork::new(0);
+        let start = network.now();
+
+        advance(Duration::from_secs(60)).await;
+
+        assert_eq!(chrono::Duration::seconds(60), network.now() - start);
+    }
+
+    #[tokio::test]
+    async fn test_drop_rate() {
+        let network = Arc::new(MemoryNetwork::new(0));
+
+        let mut listener = network.clone().bind(address(1)).unwrap();
+        let mut stream = network.clone().connect(address(2), address(1)).unwrap();
+        let mut peer_stream = listener.accept().await.unwrap();
+
+        // A message written in several parts is lost as a whole
+        network.set_link_rules(address(1), address(2), LinkRules::new(Duration::from_millis(0), 1.));
+        stream.write_all(b"lo").await.unwrap();
+        stream.write_all(b"st").await.unwrap();
+        stream.flush().await.unwrap();
+
+        network.set_link_rules(address(2), address(1), LinkRules::default());
+        stream.write_all(b"se").await.unwrap();
+        stream.write_all(b"nt").await.unwrap();
+        stream.flush().await.unwrap();
+
+        let mut message = [0u8; 4];
+        peer_stream.read_exact(&mut message).await.unwrap();
+
+        assert_eq!(b"sent", &message);
+    }
+
+    #[tokio::test]
+    async fn test_partition() {
+        let network = Arc::new(MemoryNetwork::new(0));
+        let _listener = network.clone().bind(address(1)).unwrap();
+
+        network.partition(&[vec![address(1)], vec![address(2)]]);
+
+        assert!(!network.is_reachable(address(1), address(2)));
+        assert!(network.is_reachable(address(1), address(3)));
+        assert!(network.clone().connect(address(2), address(1)).is_err());
+
+        network.heal();
+
+        assert!(network.is_reachable(address(1), address(2)));
+        assert!(network.clone().connect(address(2), address(1)).is_ok());
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+pub mod memory;
+pub use memory::*;
+
+use chrono::{DateTime, Utc};
+use std::{
+    io,
+    net::{Ipv4Addr, Shutdown, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+};
+
+/// The transport a node uses to listen for and connect to its peers.
+#[derive(Clone, Debug)]
+pub enum Transport {
+    /// Connections over tcp
+    Tcp,
+    /// Connections over a simulated in-memory network, made from the given address of this node
+    Memory(Arc<MemoryNetwork>, SocketAddr),
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Transport::Tcp
+    }
+}
+
+impl Transport {
+    /// Returns a listener accepting the connections of peers at the given address.
+    /// A tcp listener accepts connections on all interfaces at the port of the address.
+    pub async fn bind(&self, address: SocketAddr) -> io::Result<Listener> {
+        match self {
+            Transport::Tcp => {
+                let address = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), address.port());
+                Ok(Listener::Tcp(TcpListener::bind(address).await?))
+            }
+            Transport::Memory(network, _) => Ok(Listener::Memory(network.clone().bind(address)?)),
+        }
+    }
+
+    /// Returns the current time of the node.
+    /// The time of a memory network is simulated, and only advances with the tokio clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Transport::Tcp => Utc::now(),
+            Transport::Memory(network, _) => network.now(),
+        }
+    }
+
+    /// Returns a new stream connected to the peer listening at the given address.
+    pub async fn connect(&self, address: SocketAddr) -> io::Result<Stream> {
+        match self {
+            Transport::Tcp => Ok(Stream::Tcp(TcpStream::connect(address).await?)),
+            Transport::Memory(network, local_address) => {
+                Ok(Stream::Memory(network.clone().connect(*local_address, address)?))
+            }
+        }
+    }
+}
+
+/// Accepts the connections of peers.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    Memory(MemoryListener),
+}
+
+impl Listener {
+    /// Returns the next stream connected by a peer, and the address of the peer.
+    pub async fn accept(&mut self) -> io::Result<(Stream, SocketAddr)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, address) = listener.accept().await?;
+                Ok((Stream::Tcp(stream), address))
+            }
+            Listener::Memory(listener) => {
+                let stream = listener.accept().await?;
+                let address = stream.peer_addr();
+                Ok((Stream::Memory(stream), address))
+            }
+        }
+    }
+}
+
+/// A stream connecting this node with a peer.
+#[derive(Debug)]
+pub enum Stream {
+    Tcp(TcpStream),
+    Memory(MemoryStream),
+}
+
+impl Stream {
+    /// Returns the address of the peer.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Stream::Tcp(stream) => stream.peer_addr(),
+            Stream::Memory(stream) => Ok(stream.peer_addr()),
+        }
+    }
+
+    /// Receives bytes from the peer without consuming them.
+    pub async fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.peek(buf).await,
+            Stream::Memory(stream) => stream.peek(buf).await,
+        }
+    }
+
+    /// Shuts down the read half, the write half, or both halves of the stream.
+    pub fn shutdown(&mut self, how: Shutdown) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(how),
+            Stream::Memory(stream) => {
+                stream.shutdown(how);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<TcpStream> for Stream {
+    fn from(stream: TcpStream) -> Self {
+        Stream::Tcp(stream)
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Stream::Memory(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Stream::Memory(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Stream::Memory(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Stream::Memory(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
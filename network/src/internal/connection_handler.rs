@@ -19,18 +19,19 @@ use crate::{
         message_types::{GetMemoryPool, GetPeers, Version},
         protocol::sync::SyncState,
     },
+    internal::connect_peer_internal,
     Server,
 };
 
-use chrono::{Duration as ChronoDuration, Utc};
-use std::time::Duration;
+use chrono::Duration as ChronoDuration;
+use std::{net::SocketAddr, time::Duration};
 use tokio::{task, time::delay_for};
 
 impl Server {
     /// Manages the number of active connections according to the connection frequency.
-    /// 1. Get more connected peers if we are under the minimum number specified by the network context.
-    ///     1.1 Ask our connected peers for their peers.
-    ///     1.2 Ask our gossiped peers to handshake and become connected.
+    /// 1. Get more connected peers.
+    ///     1.1 Ask our connected peers for their peers if we are under the minimum number specified by the context.
+    ///     1.2 Ask disconnected and gossiped peers of distinct groups to handshake until our outbound slots are full.
     /// 2. Maintain connected peers by sending ping messages.
     /// 3. Purge peers that have not responded in connection_frequency x 5 seconds.
     /// 4. Reselect a sync node if we purged it.
@@ -38,6 +39,7 @@ impl Server {
     /// All errors encountered by the connection handler will be logged to the console but will not stop the thread.
     pub async fn connection_handler(&self) {
         let context = self.context.clone();
+        let identity = self.identity.clone();
         let memory_pool_lock = self.memory_pool_lock.clone();
         let sync_handler_lock = self.sync_handler_lock.clone();
         let storage = self.storage.clone();
@@ -62,6 +64,9 @@ impl Server {
                     let local_address = *context.local_address.read().await;
                     peer_book.forget_peer(local_address);
 
+                    // Lift the bans that have expired.
+                    peer_book.remove_expired_bans();
+
                     // We have less peers than our minimum peer requirement. Look for more peers.
                     if peer_book.connected_total() < context.min_peers {
                         // Ask our connected peers.
@@ -79,35 +84,80 @@ impl Server {
                                 }
                             }
                         }
+                    }
 
-                        // Try and connect to our gossiped peers.
-                        for (remote_address, _last_seen) in peer_book.get_gossiped() {
-                            if remote_address != local_address && !peer_book.connected_contains(&remote_address) {
-                                // Create a non-blocking handshake request
-                                {
-                                    let new_context = context.clone();
-                                    let latest_block_height = storage.get_latest_block_height();
+                    // Give up on the outbound handshakes that did not complete in five frequency loops,
+                    // and allow dialing the peers that were dialed before then again.
+                    let response_timeout = ChronoDuration::milliseconds((connection_frequency * 5) as i64);
 
-                                    task::spawn(async move {
-                                        // TODO (raychu86) Establish a formal node version
-                                        let version =
-                                            Version::new(1u64, latest_block_height, remote_address, local_address);
+                    let expired_outbound =
+                        peer_book.remove_expired_outbound(context.transport.now() - response_timeout);
+                    if !expired_outbound.is_empty() {
+                        // The handshake lock is taken before the peer book lock, so the expired handshakes
+                        // are removed once the peer book is released, before their peers can be dialed again.
+                        let new_context = context.clone();
+                        task::spawn(async move {
+                            let mut handshakes = new_context.handshakes.write().await;
+                            for address in expired_outbound {
+                                handshakes.remove(&address);
+                            }
+                        });
+                    }
 
-                                        let mut handshakes = new_context.handshakes.write().await; // Acquire the handshake lock
-                                        if let Err(_) = handshakes.send_request(&version).await {
-                                            debug!("Could not connect to gossiped peer {}", remote_address);
-                                        }
-                                    });
+                    // Fill our free outbound slots with peers of distinct groups.
+                    let free_outbound_slots = context
+                        .max_outbound_peers
+                        .saturating_sub(peer_book.connected_outbound_total());
+
+                    for remote_address in peer_book.select_outbound(free_outbound_slots as usize) {
+                        if remote_address != local_address {
+                            // Create a non-blocking handshake request
+                            let new_context = context.clone();
+                            let new_identity = identity.clone();
+                            let latest_block_height = storage.get_latest_block_height();
+
+                            task::spawn(async move {
+                                if let Err(_) = connect_peer_internal(
+                                    new_context,
+                                    &new_identity,
+                                    latest_block_height,
+                                    remote_address,
+                                )
+                                .await
+                                {
+                                    debug!("Could not connect to peer {}", remote_address);
                                 }
-                            }
+                            });
+                        }
 
-                            peer_book.remove_gossiped(remote_address);
+                        peer_book.remove_gossiped(remote_address);
+                    }
+
+                    // Try and connect to the peers requested by the node operator.
+                    for remote_address in peer_book.take_requested() {
+                        if remote_address != local_address && !peer_book.connected_contains(&remote_address) {
+                            let new_context = context.clone();
+                            let new_identity = identity.clone();
+                            let latest_block_height = storage.get_latest_block_height();
+
+                            task::spawn(async move {
+                                if let Err(error) = connect_peer_internal(
+                                    new_context,
+                                    &new_identity,
+                                    latest_block_height,
+                                    remote_address,
+                                )
+                                .await
+                                {
+                                    warn!("Could not connect to requested peer {} ({})", remote_address, error);
+                                }
+                            });
                         }
                     }
 
                     // Send a ping protocol request to each of our connected peers to maintain the connection.
                     for (address, last_seen) in peer_book.get_connected() {
-                        let time_since_last_seen = (Utc::now() - last_seen).num_milliseconds();
+                        let time_since_last_seen = (context.transport.now() - last_seen).num_milliseconds();
                         if address != local_address
                             && time_since_last_seen.is_positive()
                             && time_since_last_seen as u64 > (connection_frequency * 3)
@@ -129,18 +179,35 @@ impl Server {
                     }
 
                     // Purge peers that haven't responded in five frequency loops.
-                    let response_timeout = ChronoDuration::milliseconds((connection_frequency * 5) as i64);
-
                     for (address, last_seen) in peer_book.get_connected() {
-                        if Utc::now() - last_seen.clone() > response_timeout {
+                        if context.transport.now() - last_seen.clone() > response_timeout {
                             peer_book.disconnect_peer(address);
                         }
                     }
 
+                    // Forget the inventory known to disconnected peers and the inventory requests that timed out.
+                    {
+                        let mut known_inventory = context.known_inventory.write().await;
+                        known_inventory.retain(|address| peer_book.connected_contains(address));
+                        known_inventory.remove_expired_requests(context.transport.now());
+                    }
+
+                    // Forget the heights of disconnected peers and request their pending blocks from other peers.
                     // If we have disconnected from our sync node,
                     // then set our sync state to idle and find a new sync node.
                     {
                         if let Ok(mut sync_handler) = sync_handler_lock.try_lock() {
+                            let disconnected_peers: Vec<SocketAddr> = sync_handler
+                                .peer_heights
+                                .keys()
+                                .filter(|address| !peer_book.connected_contains(address))
+                                .cloned()
+                                .collect();
+
+                            for address in disconnected_peers {
+                                sync_handler.remove_peer(&address);
+                            }
+
                             if peer_book.disconnected_contains(&sync_handler.sync_node) {
                                 if let Some(peer) = peer_book.get_connected().iter().max_by(|a, b| a.1.cmp(&b.1)) {
                                     sync_handler.sync_state = SyncState::Idle;
@@ -150,7 +217,7 @@ impl Server {
                         }
                     }
 
-                    // Store connected peers in database.
+                    // Store connected peers and bans in database.
                     peer_book
                         .store(&storage)
                         .unwrap_or_else(|error| debug!("Failed to store connected peers in database {}", error));
@@ -168,7 +235,7 @@ impl Server {
                                     // If they are behind, they will attempt to sync.
                                     if let Some(handshake) = context.handshakes.read().await.get(&remote_address) {
                                         let version = Version::from(
-                                            1u64, // TODO (raychu86) Establish a formal node version
+                                            &identity,
                                             storage.get_latest_block_height(),
                                             remote_address,
                                             local_address,
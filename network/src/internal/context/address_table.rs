@@ -0,0 +1,188 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::internal::IpSubnet;
+
+use chrono::{DateTime, Utc};
+use rand::{seq::IteratorRandom, Rng};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+};
+
+/// The maximum number of gossiped peers stored in the new table.
+pub const NEW_TABLE_CAPACITY: usize = 1024;
+
+/// The maximum number of gossiped peers of a single group stored in the new table.
+pub const NEW_TABLE_GROUP_CAPACITY: usize = 64;
+
+/// The maximum number of previously connected peers stored in the tried table.
+pub const TRIED_TABLE_CAPACITY: usize = 256;
+
+/// The maximum number of previously connected peers of a single group stored in the tried table.
+pub const TRIED_TABLE_GROUP_CAPACITY: usize = 16;
+
+/// Stores a bounded number of peers and the date they were last seen, bucketed by the group of their ip.
+///
+/// Once the bucket of a group is full, a new peer of that group evicts a random peer of the same group,
+/// so a single operator can only displace its own peers. Once the table is full, a new peer evicts
+/// a random peer of the table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressTable {
+    capacity: usize,
+    group_capacity: usize,
+    addresses: HashMap<SocketAddr, DateTime<Utc>>,
+    groups: HashMap<IpSubnet, HashSet<SocketAddr>>,
+}
+
+impl AddressTable {
+    /// Construct a new `AddressTable` storing up to `capacity` peers, and up to `group_capacity` peers per group.
+    pub fn new(capacity: usize, group_capacity: usize) -> Self {
+        Self {
+            capacity,
+            group_capacity,
+            addresses: HashMap::default(),
+            groups: HashMap::default(),
+        }
+    }
+
+    /// Insert or update a new date for an address, evicting another address if needed.
+    /// Returns true if the new date is stored.
+    pub fn insert_or_update(&mut self, address: SocketAddr, date: DateTime<Utc>) -> bool {
+        if let Some(stored_date) = self.addresses.get_mut(&address) {
+            if *stored_date > date {
+                return false;
+            }
+
+            *stored_date = date;
+            return true;
+        }
+
+        if self.capacity == 0 || self.group_capacity == 0 {
+            return false;
+        }
+
+        let mut rng = rand::thread_rng();
+        let group = IpSubnet::group(&address.ip());
+
+        let evicted = match self.groups.get(&group) {
+            Some(bucket) if bucket.len() >= self.group_capacity => bucket.iter().choose(&mut rng).copied(),
+            _ if self.addresses.len() >= self.capacity => self.addresses.keys().choose(&mut rng).copied(),
+            _ => None,
+        };
+
+        if let Some(evicted) = evicted {
+            self.remove(&evicted);
+        }
+
+        self.addresses.insert(address, date);
+        self.groups.entry(group).or_default().insert(address);
+
+        true
+    }
+
+    /// Returns true if address is stored in the table.
+    pub fn contains(&self, address: &SocketAddr) -> bool {
+        self.addresses.contains_key(address)
+    }
+
+    /// Remove an address and return its last seen date.
+    pub fn remove(&mut self, address: &SocketAddr) -> Option<DateTime<Utc>> {
+        let date = self.addresses.remove(address)?;
+
+        let group = IpSubnet::group(&address.ip());
+        if let Some(bucket) = self.groups.get_mut(&group) {
+            bucket.remove(address);
+
+            if bucket.is_empty() {
+                self.groups.remove(&group);
+            }
+        }
+
+        Some(date)
+    }
+
+    /// Returns a random stored address accepted by the filter.
+    pub fn select<F: Fn(&SocketAddr) -> bool, R: Rng>(&self, filter: F, rng: &mut R) -> Option<SocketAddr> {
+        self.addresses
+            .keys()
+            .filter(|address| filter(address))
+            .choose(rng)
+            .copied()
+    }
+
+    /// Returns the number of stored peers.
+    pub fn length(&self) -> u16 {
+        self.addresses.len() as u16
+    }
+
+    /// Returns copy of addresses
+    pub fn get_addresses(&self) -> HashMap<SocketAddr, DateTime<Utc>> {
+        self.addresses.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+
+    #[test]
+    fn test_group_eviction() {
+        let mut table = AddressTable::new(8, 2);
+        let now = Utc::now();
+
+        table.insert_or_update("10.0.0.1:4131".parse().unwrap(), now);
+        table.insert_or_update("10.0.0.2:4131".parse().unwrap(), now);
+        table.insert_or_update("10.1.0.1:4131".parse().unwrap(), now);
+
+        // A third peer of the 10.0.0.0/16 group evicts one of the peers of its group.
+        let address = "10.0.255.1:4131".parse().unwrap();
+        assert!(table.insert_or_update(address, now));
+
+        assert_eq!(3, table.length());
+        assert!(table.contains(&address));
+        assert!(table.contains(&"10.1.0.1:4131".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_table_eviction() {
+        let mut table = AddressTable::new(4, 4);
+        let now = Utc::now();
+
+        for index in 0..10u8 {
+            let address = SocketAddr::from(([10, index, 0, 1], 4131));
+
+            assert!(table.insert_or_update(address, now));
+            assert!(table.contains(&address));
+        }
+
+        assert_eq!(4, table.length());
+    }
+
+    #[test]
+    fn test_update_date() {
+        let mut table = AddressTable::new(4, 4);
+        let address = "10.0.0.1:4131".parse().unwrap();
+        let now = Utc::now();
+
+        assert!(table.insert_or_update(address, now));
+        assert!(!table.insert_or_update(address, now - Duration::minutes(1)));
+        assert_eq!(Some(now), table.remove(&address));
+        assert_eq!(0, table.length());
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::network::ServerError;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+/// An ip address range given by a network address and a prefix length.
+/// A single ip address is a subnet with the maximum prefix length.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct IpSubnet {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpSubnet {
+    /// Construct a new `IpSubnet` containing all addresses that share the first `prefix_len` bits of `address`.
+    pub fn new(address: IpAddr, prefix_len: u8) -> Result<Self, ServerError> {
+        let max_prefix_len = Self::max_prefix_len(&address);
+        if prefix_len > max_prefix_len {
+            return Err(ServerError::InvalidSubnet(format!("{}/{}", address, prefix_len)));
+        }
+
+        let address = match address {
+            IpAddr::V4(address) => IpAddr::from((u32::from(address) & Self::mask(prefix_len, 32) as u32).to_be_bytes()),
+            IpAddr::V6(address) => IpAddr::from((u128::from(address) & Self::mask(prefix_len, 128)).to_be_bytes()),
+        };
+
+        Ok(Self { address, prefix_len })
+    }
+
+    /// Returns the group of an ip address, its /16 subnet for IPv4 or its /32 subnet for IPv6.
+    /// Addresses in the same group are likely to be run by the same operator.
+    pub fn group(address: &IpAddr) -> Self {
+        let prefix_len = match address {
+            IpAddr::V4(_) => 16,
+            IpAddr::V6(_) => 32,
+        };
+
+        // The prefix length never exceeds the maximum prefix length of the address.
+        Self::new(*address, prefix_len).unwrap()
+    }
+
+    /// Returns true if the subnet contains the ip address.
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        match (self.address, address) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                Self::new(*address, self.prefix_len).map_or(false, |subnet| subnet == *self)
+            }
+            _ => false,
+        }
+    }
+
+    fn max_prefix_len(address: &IpAddr) -> u8 {
+        match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        }
+    }
+
+    fn mask(prefix_len: u8, bits: u8) -> u128 {
+        match prefix_len {
+            0 => 0,
+            _ => (u128::MAX >> (128 - bits)) & !((1u128 << (bits - prefix_len)).wrapping_sub(1)),
+        }
+    }
+}
+
+impl From<IpAddr> for IpSubnet {
+    fn from(address: IpAddr) -> Self {
+        Self {
+            address,
+            prefix_len: Self::max_prefix_len(&address),
+        }
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = ServerError;
+
+    fn from_str(subnet: &str) -> Result<Self, Self::Err> {
+        let invalid_subnet = || ServerError::InvalidSubnet(subnet.to_string());
+
+        match subnet.find('/') {
+            Some(index) => {
+                let address = IpAddr::from_str(&subnet[..index]).map_err(|_| invalid_subnet())?;
+                let prefix_len = u8::from_str(&subnet[index + 1..]).map_err(|_| invalid_subnet())?;
+
+                Self::new(address, prefix_len).map_err(|_| invalid_subnet())
+            }
+            None => match IpAddr::from_str(subnet) {
+                Ok(address) => Ok(Self::from(address)),
+                Err(_) => SocketAddr::from_str(subnet)
+                    .map(|address| Self::from(address.ip()))
+                    .map_err(|_| invalid_subnet()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for IpSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+/// Stores banned subnets and the date their bans expire.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BanList {
+    bans: HashMap<IpSubnet, DateTime<Utc>>,
+}
+
+impl BanList {
+    /// Construct a new `BanList`.
+    pub fn new() -> Self {
+        Self {
+            bans: HashMap::default(),
+        }
+    }
+
+    /// Ban a subnet until the given date. Extends an existing ban if it expires earlier.
+    pub fn ban(&mut self, subnet: IpSubnet, until: DateTime<Utc>) {
+        let banned_until = self.bans.entry(subnet).or_insert(until);
+        if *banned_until < until {
+            *banned_until = until;
+        }
+    }
+
+    /// Lift the ban of a subnet. Returns true if the subnet was banned.
+    pub fn unban(&mut self, subnet: &IpSubnet) -> bool {
+        self.bans.remove(subnet).is_some()
+    }
+
+    /// Returns true if the address belongs to a subnet with an active ban.
+    pub fn is_banned(&self, address: &IpAddr) -> bool {
+        let now = Utc::now();
+
+        self.bans
+            .iter()
+            .any(|(subnet, until)| *until > now && subnet.contains(address))
+    }
+
+    /// Remove the bans that have expired.
+    pub fn remove_expired(&mut self) {
+        let now = Utc::now();
+
+        self.bans.retain(|_, until| *until > now);
+    }
+
+    /// Returns copy of the active bans.
+    pub fn get_bans(&self) -> HashMap<IpSubnet, DateTime<Utc>> {
+        let now = Utc::now();
+
+        self.bans
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(subnet, until)| (*subnet, *until))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+
+    #[test]
+    fn test_subnet_contains() {
+        let subnet = IpSubnet::from_str("10.1.0.0/16").unwrap();
+
+        assert!(subnet.contains(&"10.1.255.3".parse().unwrap()));
+        assert!(!subnet.contains(&"10.2.0.1".parse().unwrap()));
+        assert!(!subnet.contains(&"::1".parse().unwrap()));
+
+        let address = IpSubnet::from_str("127.0.0.1:4131").unwrap();
+
+        assert_eq!(address, IpSubnet::from_str("127.0.0.1/32").unwrap());
+        assert!(address.contains(&"127.0.0.1".parse().unwrap()));
+        assert!(!address.contains(&"127.0.0.2".parse().unwrap()));
+
+        assert!(IpSubnet::from_str("fe80::/10")
+            .unwrap()
+            .contains(&"fe80::1".parse().unwrap()));
+        assert!(IpSubnet::from_str("10.0.0.0/33").is_err());
+        assert!(IpSubnet::from_str("not a subnet").is_err());
+    }
+
+    #[test]
+    fn test_subnet_group() {
+        let group = IpSubnet::group(&"10.1.2.3".parse().unwrap());
+
+        assert_eq!(IpSubnet::from_str("10.1.0.0/16").unwrap(), group);
+        assert_eq!(group, IpSubnet::group(&"10.1.255.255".parse().unwrap()));
+        assert_ne!(group, IpSubnet::group(&"10.2.2.3".parse().unwrap()));
+        assert_eq!(
+            IpSubnet::from_str("2001:db8::/32").unwrap(),
+            IpSubnet::group(&"2001:db8:1::1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_ban_list() {
+        let mut ban_list = BanList::new();
+        let subnet = IpSubnet::from_str("192.168.0.0/24").unwrap();
+        let address = "192.168.0.10".parse().unwrap();
+
+        ban_list.ban(subnet, Utc::now() + Duration::minutes(1));
+
+        assert!(ban_list.is_banned(&address));
+        assert!(!ban_list.is_banned(&"192.168.1.10".parse().unwrap()));
+        assert_eq!(1, ban_list.get_bans().len());
+
+        assert!(ban_list.unban(&subnet));
+        assert!(!ban_list.is_banned(&address));
+
+        ban_list.ban(subnet, Utc::now() - Duration::minutes(1));
+        assert!(!ban_list.is_banned(&address));
+
+        ban_list.remove_expired();
+        assert!(ban_list.get_bans().is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message_types::CompactBlock;
+use snarkos_objects::{BlockHeader, BlockHeaderHash};
+use snarkos_utilities::variable_length_integer::variable_length_integer;
+
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, net::SocketAddr};
+
+/// The maximum number of compact blocks waiting for their missing transactions.
+pub const MAX_PENDING_COMPACT_BLOCKS: usize = 16;
+
+/// A block being rebuilt from a `CompactBlock` message and the transactions in the memory pool.
+#[derive(Clone, Debug)]
+pub struct PartialBlock {
+    /// The header of the block
+    pub header: BlockHeader,
+    /// The serialized block transactions, None for the transactions that are still missing
+    transactions: Vec<Option<Vec<u8>>>,
+}
+
+impl PartialBlock {
+    /// Construct a new `PartialBlock` from a compact block.
+    /// `find_transaction` returns the serialized transaction with the given short id, if it is known.
+    /// Returns None if the prefilled transactions of the compact block are inconsistent.
+    pub fn new<F: Fn(u64) -> Option<Vec<u8>>>(compact_block: CompactBlock, find_transaction: F) -> Option<Self> {
+        let num_transactions = compact_block.short_ids.len() + compact_block.prefilled_transactions.len();
+        let mut transactions: Vec<Option<Vec<u8>>> = vec![None; num_transactions];
+
+        for prefilled in compact_block.prefilled_transactions {
+            let slot = transactions.get_mut(prefilled.index as usize)?;
+            if slot.is_some() {
+                return None;
+            }
+            *slot = Some(prefilled.bytes);
+        }
+
+        // The short ids fill the remaining slots in block order.
+        let mut short_ids = compact_block.short_ids.into_iter();
+        for slot in transactions.iter_mut().filter(|slot| slot.is_none()) {
+            *slot = find_transaction(short_ids.next()?);
+        }
+
+        Some(Self {
+            header: compact_block.header,
+            transactions,
+        })
+    }
+
+    /// Returns the indexes of the transactions that are still missing, in ascending order.
+    pub fn missing(&self) -> Vec<u32> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, transaction)| transaction.is_none())
+            .map(|(index, _)| index as u32)
+            .collect()
+    }
+
+    /// Fills in the missing transactions, given in the order of `missing`.
+    /// Returns false if the number of transactions does not match the number of missing transactions.
+    pub fn fill(&mut self, transactions: Vec<Vec<u8>>) -> bool {
+        if transactions.len() != self.missing().len() {
+            return false;
+        }
+
+        let mut transactions = transactions.into_iter();
+        for slot in self.transactions.iter_mut().filter(|slot| slot.is_none()) {
+            *slot = transactions.next();
+        }
+
+        true
+    }
+
+    /// Returns the serialized block, or None if transactions are still missing.
+    pub fn to_block_bytes(&self) -> Option<Vec<u8>> {
+        let mut block_bytes = self.header.serialize().to_vec();
+        block_bytes.extend(variable_length_integer(self.transactions.len() as u64));
+
+        for transaction in &self.transactions {
+            block_bytes.extend(transaction.as_ref()?);
+        }
+
+        Some(block_bytes)
+    }
+}
+
+/// Stores the compact blocks waiting for the transactions requested with `GetBlockTxn` messages,
+/// and the peers the transactions were requested from.
+#[derive(Debug, Default)]
+pub struct CompactBlocks {
+    pending: HashMap<BlockHeaderHash, (PartialBlock, SocketAddr, DateTime<Utc>)>,
+}
+
+impl CompactBlocks {
+    /// Construct a new empty store of `CompactBlocks`.
+    pub fn new() -> Self {
+        Self { pending: HashMap::new() }
+    }
+
+    /// Stores a partial block until its missing transactions arrive from the given peer.
+    /// The oldest partial block is dropped if `MAX_PENDING_COMPACT_BLOCKS` are already pending.
+    pub fn insert(&mut self, block_hash: BlockHeaderHash, peer: SocketAddr, partial_block: PartialBlock) {
+        if self.pending.len() >= MAX_PENDING_COMPACT_BLOCKS && !self.pending.contains_key(&block_hash) {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, (_, _, received_at))| *received_at)
+                .map(|(block_hash, _)| block_hash.clone());
+
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+
+        self.pending.insert(block_hash, (partial_block, peer, Utc::now()));
+    }
+
+    /// Removes and returns the partial block with the given hash, if its transactions were requested from the peer.
+    pub fn remove(&mut self, block_hash: &BlockHeaderHash, peer: &SocketAddr) -> Option<PartialBlock> {
+        match self.pending.get(block_hash) {
+            Some((_, requested_from, _)) if requested_from == peer => {
+                self.pending.remove(block_hash).map(|(partial_block, _, _)| partial_block)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::message_types::PrefilledTransaction;
+    use snarkos_testing::consensus::DATA;
+
+    fn compact_block() -> CompactBlock {
+        let prefilled = PrefilledTransaction {
+            index: 0,
+            bytes: vec![0u8; 4],
+        };
+
+        CompactBlock::new(DATA.block_1.header.clone(), vec![1, 2], vec![prefilled])
+    }
+
+    #[test]
+    fn test_partial_block() {
+        let mut partial_block = PartialBlock::new(compact_block(), |short_id| match short_id {
+            2 => Some(vec![2u8; 4]),
+            _ => None,
+        })
+        .unwrap();
+
+        assert_eq!(vec![1], partial_block.missing());
+        assert!(partial_block.to_block_bytes().is_none());
+
+        assert!(!partial_block.fill(vec![]));
+        assert!(partial_block.fill(vec![vec![1u8; 4]]));
+        assert!(partial_block.missing().is_empty());
+
+        let block_bytes = partial_block.to_block_bytes().unwrap();
+        let header_size = BlockHeader::size();
+
+        assert_eq!(&DATA.block_1.header.serialize()[..], &block_bytes[..header_size]);
+        assert_eq!(
+            &[3, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2][..],
+            &block_bytes[header_size..]
+        );
+    }
+
+    #[test]
+    fn test_partial_block_inconsistent_prefilled_transactions() {
+        let mut compact_block = compact_block();
+        compact_block.prefilled_transactions[0].index = 3;
+
+        assert!(PartialBlock::new(compact_block, |_| None).is_none());
+    }
+
+    #[test]
+    fn test_compact_blocks() {
+        let peer: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+        let other_peer: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+        let mut compact_blocks = CompactBlocks::new();
+
+        for i in 0..=MAX_PENDING_COMPACT_BLOCKS {
+            let partial_block = PartialBlock::new(compact_block(), |_| None).unwrap();
+            compact_blocks.insert(BlockHeaderHash([i as u8; 32]), peer, partial_block);
+        }
+
+        assert_eq!(MAX_PENDING_COMPACT_BLOCKS, compact_blocks.pending.len());
+
+        // Only the peer the transactions were requested from can complete the block
+        let newest = BlockHeaderHash([MAX_PENDING_COMPACT_BLOCKS as u8; 32]);
+        assert!(compact_blocks.remove(&newest, &other_peer).is_none());
+        assert!(compact_blocks.remove(&newest, &peer).is_some());
+        assert!(compact_blocks.remove(&newest, &peer).is_none());
+    }
+}
@@ -36,10 +36,20 @@ impl Connections {
         self.channels.get(address).cloned()
     }
 
+    /// Returns the channels of all connected peers.
+    pub fn channels(&self) -> Vec<Arc<Channel>> {
+        self.channels.values().cloned().collect()
+    }
+
     /// Stores a new channel at the peer address it is connected to.
     pub fn store_channel(&mut self, channel: &Arc<Channel>) {
         self.channels.insert(channel.address, channel.clone());
     }
 
+    /// Removes and returns the channel stored at address if any.
+    pub fn remove(&mut self, address: &SocketAddr) -> Option<Arc<Channel>> {
+        self.channels.remove(address)
+    }
+
     // TODO (raychu86) Clean up connections if peers are disconnected
 }
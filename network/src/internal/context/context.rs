@@ -15,13 +15,16 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    external::{Handshakes, Pings},
-    internal::{Connections, PeerBook},
+    external::{Bandwidth, Encryption, Handshakes, Pings, Transport},
+    internal::{CompactBlocks, Connections, KnownInventory, PeerBook},
 };
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::RwLock;
 
+/// The maximum number of outbound connections of a node, unless it allows less than twice as many peers.
+pub const MAX_OUTBOUND_PEERS: u16 = 8;
+
 /// The network context for this node.
 /// All variables are public to allow server components to acquire read/write access.
 pub struct Context {
@@ -37,6 +40,12 @@ pub struct Context {
     /// Maximum number of peers to connect to
     pub max_peers: u16,
 
+    /// Maximum number of peers this node sends a handshake request to
+    pub max_outbound_peers: u16,
+
+    /// Maximum number of peers this node accepts a handshake request from
+    pub max_inbound_peers: u16,
+
     /// If enabled, node will not connect to bootnodes on startup.
     pub is_bootnode: bool,
 
@@ -46,6 +55,15 @@ pub struct Context {
     /// If enabled, node will operate as a miner
     pub is_miner: bool,
 
+    /// Static keypair and policy for encrypted peer connections
+    pub encryption: Encryption,
+
+    /// Transport used to listen for and connect to peers
+    pub transport: Transport,
+
+    /// Bytes sent to and received from all peers, and the rate limits throttling them
+    pub bandwidth: Arc<Bandwidth>,
+
     /// Manages connected, gossiped, and disconnected peers
     pub peer_book: RwLock<PeerBook>,
 
@@ -57,10 +75,17 @@ pub struct Context {
 
     /// Ping/pongs with connected peers
     pub pings: RwLock<Pings>,
+
+    /// Transactions and blocks the connected peers are known to have
+    pub known_inventory: RwLock<KnownInventory>,
+
+    /// Compact blocks waiting for their missing transactions
+    pub compact_blocks: RwLock<CompactBlocks>,
 }
 
 impl Context {
     /// Construct a new network `Context`.
+    /// Up to half of the `max_peers` connection slots are reserved for outbound connections.
     pub fn new(
         local_address: SocketAddr,
         memory_pool_interval: u8,
@@ -70,18 +95,28 @@ impl Context {
         bootnodes: Vec<String>,
         is_miner: bool,
     ) -> Self {
+        let max_outbound_peers = (max_peers / 2).min(MAX_OUTBOUND_PEERS);
+        let bandwidth = Arc::new(Bandwidth::default());
+
         Self {
             local_address: RwLock::new(local_address),
             memory_pool_interval,
             min_peers,
             max_peers,
+            max_outbound_peers,
+            max_inbound_peers: max_peers - max_outbound_peers,
             is_bootnode,
             bootnodes,
             is_miner,
+            encryption: Encryption::default(),
+            transport: Transport::default(),
+            bandwidth: bandwidth.clone(),
             connections: RwLock::new(Connections::new()),
             peer_book: RwLock::new(PeerBook::new()),
-            handshakes: RwLock::new(Handshakes::new()),
+            handshakes: RwLock::new(Handshakes::with_bandwidth(bandwidth)),
             pings: RwLock::new(Pings::new()),
+            known_inventory: RwLock::new(KnownInventory::new()),
+            compact_blocks: RwLock::new(CompactBlocks::new()),
         }
     }
 }
@@ -0,0 +1,195 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::message_types::InventoryItem;
+
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+};
+
+/// The maximum number of inventory items remembered per peer.
+/// The items announced the longest time ago are forgotten first.
+pub const MAX_KNOWN_INVENTORY: usize = 10_000;
+
+/// The number of seconds after which an item requested with `GetData` may be requested from another peer.
+pub const INVENTORY_REQUEST_TIMEOUT_SECS: i64 = 30;
+
+/// The inventory items a single peer is known to have.
+#[derive(Debug, Default)]
+struct PeerInventory {
+    items: HashSet<InventoryItem>,
+    order: VecDeque<InventoryItem>,
+}
+
+/// Stores the transactions and blocks each connected peer is known to have,
+/// so they are not announced back to the peers that sent or announced them.
+/// Also tracks the announced items requested from peers, so each item is only downloaded once.
+#[derive(Debug, Default)]
+pub struct KnownInventory {
+    peers: HashMap<SocketAddr, PeerInventory>,
+    requested: HashMap<InventoryItem, (SocketAddr, DateTime<Utc>)>,
+}
+
+impl KnownInventory {
+    /// Construct a new empty `KnownInventory`.
+    pub fn new() -> Self {
+        Self {
+            peers: HashMap::new(),
+            requested: HashMap::new(),
+        }
+    }
+
+    /// Returns true if the peer is known to have the item.
+    pub fn contains(&self, address: &SocketAddr, item: &InventoryItem) -> bool {
+        self.peers
+            .get(address)
+            .map_or(false, |inventory| inventory.items.contains(item))
+    }
+
+    /// Remembers that the peer has the item.
+    /// Returns false if the item was already known.
+    pub fn insert(&mut self, address: SocketAddr, item: InventoryItem) -> bool {
+        let inventory = self.peers.entry(address).or_default();
+
+        if !inventory.items.insert(item.clone()) {
+            return false;
+        }
+
+        inventory.order.push_back(item);
+
+        if inventory.order.len() > MAX_KNOWN_INVENTORY {
+            if let Some(oldest) = inventory.order.pop_front() {
+                inventory.items.remove(&oldest);
+            }
+        }
+
+        true
+    }
+
+    /// Forgets the inventory of a disconnected peer.
+    pub fn remove_peer(&mut self, address: &SocketAddr) {
+        self.peers.remove(address);
+    }
+
+    /// Forgets the inventory of the peers for which the predicate returns false.
+    pub fn retain<F: Fn(&SocketAddr) -> bool>(&mut self, predicate: F) {
+        self.peers.retain(|address, _| predicate(address));
+    }
+
+    /// Marks an announced item as requested from the peer.
+    /// Returns false if the item was already requested less than `INVENTORY_REQUEST_TIMEOUT_SECS` ago.
+    pub fn request(&mut self, address: SocketAddr, item: InventoryItem, now: DateTime<Utc>) -> bool {
+        if let Some((_, requested_at)) = self.requested.get(&item) {
+            if now - *requested_at < Duration::seconds(INVENTORY_REQUEST_TIMEOUT_SECS) {
+                return false;
+            }
+        }
+
+        self.requested.insert(item, (address, now));
+        true
+    }
+
+    /// Forgets the request of an item the peer it was requested from does not have,
+    /// so the item can be requested from the next peer announcing it.
+    /// Returns false if the item was not requested from the peer.
+    pub fn remove_request(&mut self, address: &SocketAddr, item: &InventoryItem) -> bool {
+        match self.requested.get(item) {
+            Some((requested_from, _)) if requested_from == address => {
+                self.requested.remove(item);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forgets the requests that timed out.
+    pub fn remove_expired_requests(&mut self, now: DateTime<Utc>) {
+        self.requested
+            .retain(|_, (_, requested_at)| now - *requested_at < Duration::seconds(INVENTORY_REQUEST_TIMEOUT_SECS));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_inventory() {
+        let peer_1: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+        let peer_2: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+        let item = InventoryItem::Transaction(vec![1u8; 32]);
+
+        let mut known_inventory = KnownInventory::new();
+
+        assert!(known_inventory.insert(peer_1, item.clone()));
+        assert!(!known_inventory.insert(peer_1, item.clone()));
+        assert!(known_inventory.contains(&peer_1, &item));
+        assert!(!known_inventory.contains(&peer_2, &item));
+
+        known_inventory.remove_peer(&peer_1);
+        assert!(!known_inventory.contains(&peer_1, &item));
+    }
+
+    #[test]
+    fn test_request_inventory() {
+        let peer: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+        let item = InventoryItem::Transaction(vec![1u8; 32]);
+        let mut known_inventory = KnownInventory::new();
+
+        let now = Utc::now();
+        assert!(known_inventory.request(peer, item.clone(), now));
+        assert!(!known_inventory.request(peer, item.clone(), now + Duration::seconds(1)));
+
+        let later = now + Duration::seconds(INVENTORY_REQUEST_TIMEOUT_SECS);
+        assert!(known_inventory.request(peer, item.clone(), later));
+
+        known_inventory.remove_expired_requests(later + Duration::seconds(INVENTORY_REQUEST_TIMEOUT_SECS));
+        assert!(known_inventory.request(peer, item, later + Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_remove_request() {
+        let peer_1: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+        let peer_2: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+        let item = InventoryItem::Transaction(vec![1u8; 32]);
+        let mut known_inventory = KnownInventory::new();
+
+        let now = Utc::now();
+        assert!(known_inventory.request(peer_1, item.clone(), now));
+
+        // Only the peer the item was requested from can report it as not found
+        assert!(!known_inventory.remove_request(&peer_2, &item));
+        assert!(!known_inventory.request(peer_2, item.clone(), now));
+
+        assert!(known_inventory.remove_request(&peer_1, &item));
+        assert!(known_inventory.request(peer_2, item, now));
+    }
+
+    #[test]
+    fn test_known_inventory_forgets_oldest_items() {
+        let peer: SocketAddr = "127.0.0.1:4130".parse().unwrap();
+        let mut known_inventory = KnownInventory::new();
+
+        for i in 0..=MAX_KNOWN_INVENTORY as u32 {
+            known_inventory.insert(peer, InventoryItem::Transaction(i.to_le_bytes().to_vec()));
+        }
+
+        assert!(!known_inventory.contains(&peer, &InventoryItem::Transaction(0u32.to_le_bytes().to_vec())));
+        assert!(known_inventory.contains(&peer, &InventoryItem::Transaction(1u32.to_le_bytes().to_vec())));
+    }
+}
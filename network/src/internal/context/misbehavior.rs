@@ -0,0 +1,80 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+/// The score every peer starts with.
+pub const INITIAL_PEER_SCORE: i32 = 100;
+
+/// Peers with a score at or below this threshold are disconnected and banned.
+pub const BAN_SCORE_THRESHOLD: i32 = 0;
+
+/// The number of seconds a misbehaving peer is banned for.
+pub const MISBEHAVIOR_BAN_DURATION_SECS: i64 = 24 * 60 * 60;
+
+/// An offense a peer can commit against this node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Misbehavior {
+    /// The peer sent a message that could not be deserialized.
+    MalformedMessage,
+
+    /// The peer sent a block or compact block that is too large or fails its merkle roots or proof of work.
+    InvalidBlock,
+
+    /// The peer sent a transaction that failed verification.
+    InvalidTransaction,
+
+    /// The peer sent a sync block this node did not request.
+    UnsolicitedSyncBlock,
+
+    /// The peer sent a message exceeding the maximum message size.
+    OversizedMessage,
+
+    /// The peer sent more bytes than the inbound byte budget of its connection.
+    ExceededInboundBudget,
+
+    /// The peer sent a block header chain that does not link up, or fails its timestamps, difficulty or proof of work.
+    InvalidHeaders,
+}
+
+impl Misbehavior {
+    /// Returns the amount the score of a peer is decremented by for this offense.
+    pub fn penalty(&self) -> i32 {
+        match self {
+            Misbehavior::MalformedMessage => 20,
+            Misbehavior::InvalidBlock => 25,
+            Misbehavior::InvalidTransaction => 10,
+            Misbehavior::UnsolicitedSyncBlock => 20,
+            Misbehavior::OversizedMessage => 50,
+            Misbehavior::ExceededInboundBudget => 50,
+            Misbehavior::InvalidHeaders => 25,
+        }
+    }
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Misbehavior::MalformedMessage => write!(f, "malformed message"),
+            Misbehavior::InvalidBlock => write!(f, "invalid block"),
+            Misbehavior::InvalidTransaction => write!(f, "invalid transaction"),
+            Misbehavior::UnsolicitedSyncBlock => write!(f, "unsolicited sync block"),
+            Misbehavior::OversizedMessage => write!(f, "oversized message"),
+            Misbehavior::ExceededInboundBudget => write!(f, "exceeded inbound budget"),
+            Misbehavior::InvalidHeaders => write!(f, "invalid block headers"),
+        }
+    }
+}
@@ -17,11 +17,29 @@
 pub mod address_book;
 pub use address_book::*;
 
+pub mod address_table;
+pub use address_table::*;
+
+pub mod ban_list;
+pub use ban_list::*;
+
+pub mod compact_blocks;
+pub use compact_blocks::*;
+
 pub mod connections;
 pub use connections::*;
 
 pub mod context;
 pub use context::*;
 
+pub mod known_inventory;
+pub use known_inventory::*;
+
+pub mod misbehavior;
+pub use misbehavior::*;
+
 pub mod peer_book;
 pub use peer_book::*;
+
+pub mod peer_info;
+pub use peer_info::*;
@@ -14,13 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::internal::address_book::AddressBook;
+use crate::{
+    external::Version,
+    internal::{
+        address_book::AddressBook,
+        AddressTable,
+        BanList,
+        IpSubnet,
+        Misbehavior,
+        PeerInfo,
+        BAN_SCORE_THRESHOLD,
+        INITIAL_PEER_SCORE,
+        NEW_TABLE_CAPACITY,
+        NEW_TABLE_GROUP_CAPACITY,
+        TRIED_TABLE_CAPACITY,
+        TRIED_TABLE_GROUP_CAPACITY,
+    },
+};
 use snarkos_errors::network::ServerError;
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
 use snarkos_storage::Ledger;
 
 use chrono::{DateTime, Utc};
-use std::{collections::HashMap, net::SocketAddr};
+use rand::Rng;
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+};
 
 /// Stores connected, disconnected, and known peers.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -28,19 +48,43 @@ pub struct PeerBook {
     /// Connected peers
     connected: AddressBook,
 
-    /// Disconnected peers
-    disconnected: AddressBook,
+    /// Pending and connected peers this node sent a handshake request to, with the date of the request
+    outbound: HashMap<SocketAddr, DateTime<Utc>>,
 
-    /// Gossiped but unconnected peers
-    gossiped: AddressBook,
+    /// Peers this node sent a handshake request to recently, with the date of the request
+    dialed: HashMap<SocketAddr, DateTime<Utc>>,
+
+    /// Disconnected peers, stored in the bounded "tried" table
+    disconnected: AddressTable,
+
+    /// Gossiped but unconnected peers, stored in the bounded "new" table
+    gossiped: AddressTable,
+
+    /// Metadata reported by peers
+    peer_info: HashMap<SocketAddr, PeerInfo>,
+
+    /// Misbehavior scores of peers
+    scores: HashMap<SocketAddr, i32>,
+
+    /// Banned subnets
+    banned: BanList,
+
+    /// Peers the node operator requested a connection to
+    requested: HashSet<SocketAddr>,
 }
 
 impl PeerBook {
     pub fn new() -> Self {
         Self {
             connected: AddressBook::new(),
-            disconnected: AddressBook::new(),
-            gossiped: AddressBook::new(),
+            outbound: HashMap::default(),
+            dialed: HashMap::default(),
+            disconnected: AddressTable::new(TRIED_TABLE_CAPACITY, TRIED_TABLE_GROUP_CAPACITY),
+            gossiped: AddressTable::new(NEW_TABLE_CAPACITY, NEW_TABLE_GROUP_CAPACITY),
+            peer_info: HashMap::default(),
+            scores: HashMap::default(),
+            banned: BanList::new(),
+            requested: HashSet::default(),
         }
     }
 
@@ -49,11 +93,54 @@ impl PeerBook {
         self.connected.get_addresses()
     }
 
+    /// Returns the connected peers this node sent a handshake request to.
+    pub fn get_outbound(&self) -> HashSet<SocketAddr> {
+        self.outbound
+            .keys()
+            .filter(|address| self.connected.contains(address))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns copy of disconnected peers.
+    pub fn get_disconnected(&self) -> HashMap<SocketAddr, DateTime<Utc>> {
+        self.disconnected.get_addresses()
+    }
+
     /// Returns copy of gossiped peers.
     pub fn get_gossiped(&self) -> HashMap<SocketAddr, DateTime<Utc>> {
         self.gossiped.get_addresses()
     }
 
+    /// Returns the metadata reported by a peer.
+    pub fn get_peer_info(&self, address: &SocketAddr) -> PeerInfo {
+        self.peer_info.get(address).cloned().unwrap_or_default()
+    }
+
+    /// Updates the metadata reported by a peer in its latest version message.
+    pub fn update_peer_info(&mut self, address: SocketAddr, version: &Version) {
+        let peer_info = self.peer_info.entry(address).or_default();
+        peer_info.version = Some(version.version);
+        peer_info.height = Some(version.height);
+        peer_info.user_agent = Some(version.user_agent.clone());
+        peer_info.services = Some(version.services);
+    }
+
+    /// Records the static key a peer authenticated its encrypted connection with.
+    /// Returns false if the peer authenticated with a different key before, in which case the known key is kept.
+    /// The keys are only known until the peer is forgotten or the node restarts.
+    pub fn verify_public_key(&mut self, address: SocketAddr, public_key: Vec<u8>) -> bool {
+        let peer_info = self.peer_info.entry(address).or_default();
+
+        match &peer_info.public_key {
+            Some(known_key) => *known_key == public_key,
+            None => {
+                peer_info.public_key = Some(public_key);
+                true
+            }
+        }
+    }
+
     /// Returns true if address is a connected peer.
     pub fn connected_contains(&self, address: &SocketAddr) -> bool {
         self.connected.contains(address)
@@ -76,16 +163,58 @@ impl PeerBook {
         self.connected.insert_or_update(address, date)
     }
 
+    /// Mark a peer as an outbound connection, as this node sends it a handshake request at the given date.
+    pub fn set_outbound(&mut self, address: SocketAddr, date: DateTime<Utc>) {
+        self.outbound.insert(address, date);
+        self.dialed.insert(address, date);
+    }
+
+    /// Unmark a peer as an outbound connection, if the handshake with it failed.
+    pub fn remove_outbound(&mut self, address: &SocketAddr) {
+        self.outbound.remove(address);
+    }
+
+    /// Unmark the pending outbound peers whose handshake request was sent before the given date,
+    /// and allow dialing the peers that were dialed before the given date again.
+    /// The peers whose handshake did not complete in time are returned, and can only be dialed again
+    /// from the next call, so their expired handshakes can be dropped first.
+    pub fn remove_expired_outbound(&mut self, date: DateTime<Utc>) -> Vec<SocketAddr> {
+        let expired: Vec<SocketAddr> = self
+            .outbound
+            .iter()
+            .filter(|(address, requested_at)| **requested_at < date && !self.connected.contains(address))
+            .map(|(address, _)| *address)
+            .collect();
+
+        for address in &expired {
+            self.outbound.remove(address);
+        }
+        self.dialed
+            .retain(|address, dialed_at| *dialed_at >= date || expired.contains(address));
+
+        expired
+    }
+
     /// Move a peer from connected/disconnected to gossiped peers.
     pub fn update_gossiped(&mut self, address: SocketAddr, date: DateTime<Utc>) -> bool {
         self.connected.remove(&address);
+        self.outbound.remove(&address);
         self.disconnected.remove(&address);
         self.gossiped.insert_or_update(address, date)
     }
 
+    /// Move a peer from connected/gossiped to disconnected peers with the given last seen date.
+    pub fn update_disconnected(&mut self, address: SocketAddr, date: DateTime<Utc>) -> bool {
+        self.connected.remove(&address);
+        self.outbound.remove(&address);
+        self.gossiped.remove(&address);
+        self.disconnected.insert_or_update(address, date)
+    }
+
     /// Move a peer from connected peers to disconnected peers.
     pub fn disconnect_peer(&mut self, address: SocketAddr) -> bool {
         self.connected.remove(&address);
+        self.outbound.remove(&address);
         self.gossiped.remove(&address);
         self.disconnected.insert_or_update(address, Utc::now())
     }
@@ -93,8 +222,71 @@ impl PeerBook {
     /// Forget a peer.
     pub fn forget_peer(&mut self, address: SocketAddr) {
         self.connected.remove(&address);
+        self.outbound.remove(&address);
         self.gossiped.remove(&address);
         self.disconnected.remove(&address);
+        self.peer_info.remove(&address);
+        self.scores.remove(&address);
+    }
+
+    /// Returns the misbehavior score of a peer.
+    pub fn get_score(&self, address: &SocketAddr) -> i32 {
+        *self.scores.get(address).unwrap_or(&INITIAL_PEER_SCORE)
+    }
+
+    /// Decrement the score of a peer by the penalty of its offense.
+    /// Returns true if the score fell to the ban threshold, in which case the score is reset.
+    pub fn penalize(&mut self, address: SocketAddr, misbehavior: Misbehavior) -> bool {
+        let score = self.scores.entry(address).or_insert(INITIAL_PEER_SCORE);
+        *score -= misbehavior.penalty();
+
+        if *score <= BAN_SCORE_THRESHOLD {
+            self.scores.remove(&address);
+            return true;
+        }
+
+        false
+    }
+
+    /// Ban a subnet until the given date and forget its gossiped peers.
+    pub fn ban(&mut self, subnet: IpSubnet, until: DateTime<Utc>) {
+        self.banned.ban(subnet, until);
+
+        for (address, _last_seen) in self.get_gossiped() {
+            if subnet.contains(&address.ip()) {
+                self.gossiped.remove(&address);
+            }
+        }
+    }
+
+    /// Lift the ban of a subnet. Returns true if the subnet was banned.
+    pub fn unban(&mut self, subnet: &IpSubnet) -> bool {
+        self.banned.unban(subnet)
+    }
+
+    /// Returns true if the address is banned.
+    pub fn is_banned(&self, address: &IpAddr) -> bool {
+        self.banned.is_banned(address)
+    }
+
+    /// Remove the bans that have expired.
+    pub fn remove_expired_bans(&mut self) {
+        self.banned.remove_expired()
+    }
+
+    /// Returns copy of the active bans.
+    pub fn get_banned(&self) -> HashMap<IpSubnet, DateTime<Utc>> {
+        self.banned.get_bans()
+    }
+
+    /// Request a connection to a peer. Returns false if the connection was already requested.
+    pub fn request_connection(&mut self, address: SocketAddr) -> bool {
+        self.requested.insert(address)
+    }
+
+    /// Returns the peers a connection was requested to and clears the requests.
+    pub fn take_requested(&mut self) -> HashSet<SocketAddr> {
+        std::mem::take(&mut self.requested)
     }
 
     /// Remove_gossiped peer
@@ -107,11 +299,199 @@ impl PeerBook {
         self.connected.length()
     }
 
-    /// Writes connected peers to storage.
+    /// Returns the number of connected peers this node sent a handshake request to.
+    pub fn connected_outbound_total(&self) -> u16 {
+        self.get_outbound().len() as u16
+    }
+
+    /// Returns the number of connected peers that sent this node a handshake request.
+    pub fn connected_inbound_total(&self) -> u16 {
+        self.connected_total() - self.connected_outbound_total()
+    }
+
+    /// Returns up to `count` unconnected peers to send a handshake request to,
+    /// picked at random from the disconnected and gossiped peers that were not dialed recently.
+    /// At most one peer is picked per group, and none from the groups of the connected outbound peers,
+    /// so the outbound connections of this node are spread across operators.
+    pub fn select_outbound(&self, count: usize) -> Vec<SocketAddr> {
+        let mut rng = rand::thread_rng();
+        let mut groups: HashSet<IpSubnet> = self
+            .get_outbound()
+            .iter()
+            .map(|address| IpSubnet::group(&address.ip()))
+            .collect();
+        let mut selected = Vec::with_capacity(count);
+
+        while selected.len() < count {
+            let is_candidate = |address: &SocketAddr| {
+                !self.connected.contains(address)
+                    && !self.dialed.contains_key(address)
+                    && !self.is_banned(&address.ip())
+                    && !groups.contains(&IpSubnet::group(&address.ip()))
+            };
+
+            // Prefer the peers this node was connected to before for half of the picks.
+            let address = match rng.gen_bool(0.5) {
+                true => self
+                    .disconnected
+                    .select(&is_candidate, &mut rng)
+                    .or_else(|| self.gossiped.select(&is_candidate, &mut rng)),
+                false => self
+                    .gossiped
+                    .select(&is_candidate, &mut rng)
+                    .or_else(|| self.disconnected.select(&is_candidate, &mut rng)),
+            };
+
+            match address {
+                Some(address) => {
+                    groups.insert(IpSubnet::group(&address.ip()));
+                    selected.push(address);
+                }
+                None => break,
+            }
+        }
+
+        selected
+    }
+
+    /// Writes connected peers, outbound peers as anchors and active bans to storage.
     pub fn store<T: Transaction, P: LoadableMerkleParameters>(
         &self,
         storage: &Ledger<T, P>,
     ) -> Result<(), ServerError> {
-        Ok(storage.store_to_peer_book(bincode::serialize(&self.get_connected())?)?)
+        storage.store_to_peer_book(bincode::serialize(&self.get_connected())?)?;
+        storage.store_anchor_peers(bincode::serialize(&self.get_outbound())?)?;
+        Ok(storage.store_banned_peers(bincode::serialize(&self.get_banned())?)?)
+    }
+
+    /// Restores the active bans from storage.
+    pub fn load_bans<T: Transaction, P: LoadableMerkleParameters>(
+        &mut self,
+        storage: &Ledger<T, P>,
+    ) -> Result<(), ServerError> {
+        let stored_bans: HashMap<IpSubnet, DateTime<Utc>> = bincode::deserialize(&storage.get_banned_peers()?)?;
+
+        for (subnet, banned_until) in stored_bans {
+            self.ban(subnet, banned_until);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::{NodeIdentity, Services};
+    use snarkos_objects::BlockHeaderHash;
+
+    #[test]
+    fn test_penalize() {
+        let mut peer_book = PeerBook::new();
+        let address: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+
+        assert_eq!(INITIAL_PEER_SCORE, peer_book.get_score(&address));
+
+        assert!(!peer_book.penalize(address, Misbehavior::MalformedMessage));
+        assert_eq!(
+            INITIAL_PEER_SCORE - Misbehavior::MalformedMessage.penalty(),
+            peer_book.get_score(&address)
+        );
+
+        assert!(!peer_book.penalize(address, Misbehavior::InvalidBlock));
+        assert!(!peer_book.penalize(address, Misbehavior::InvalidBlock));
+        assert!(!peer_book.penalize(address, Misbehavior::InvalidBlock));
+
+        assert!(peer_book.penalize(address, Misbehavior::InvalidBlock));
+        assert_eq!(INITIAL_PEER_SCORE, peer_book.get_score(&address));
+    }
+
+    #[test]
+    fn test_select_outbound() {
+        let mut peer_book = PeerBook::new();
+        let outbound: SocketAddr = "10.0.0.1:4131".parse().unwrap();
+
+        peer_book.update_connected(outbound, Utc::now());
+        peer_book.set_outbound(outbound, Utc::now());
+
+        peer_book.update_gossiped("10.0.0.2:4131".parse().unwrap(), Utc::now());
+        for index in 0..4 {
+            peer_book.update_gossiped(SocketAddr::from(([10, 1, 0, index], 4131)), Utc::now());
+        }
+        peer_book.update_disconnected("10.2.0.1:4131".parse().unwrap(), Utc::now());
+
+        assert_eq!(1, peer_book.connected_outbound_total());
+        assert_eq!(0, peer_book.connected_inbound_total());
+
+        // Only one peer of each group without an outbound connection is picked.
+        let selected = peer_book.select_outbound(8);
+        let groups: HashSet<IpSubnet> = selected.iter().map(|address| IpSubnet::group(&address.ip())).collect();
+
+        assert_eq!(2, selected.len());
+        assert_eq!(2, groups.len());
+        assert!(!groups.contains(&IpSubnet::group(&outbound.ip())));
+    }
+
+    #[test]
+    fn test_remove_expired_outbound() {
+        let mut peer_book = PeerBook::new();
+        let connected: SocketAddr = "10.0.0.1:4131".parse().unwrap();
+        let pending: SocketAddr = "10.1.0.1:4131".parse().unwrap();
+        let start = Utc::now();
+
+        peer_book.update_connected(connected, start);
+        peer_book.set_outbound(connected, start);
+        peer_book.update_disconnected(pending, start);
+        peer_book.set_outbound(pending, start);
+
+        // Peers dialed recently are not dialed again.
+        assert!(peer_book.select_outbound(8).is_empty());
+        assert!(peer_book.remove_expired_outbound(start).is_empty());
+
+        // The handshake with the pending peer timed out, and the peer can be dialed again from the next call.
+        let later = start + chrono::Duration::seconds(1);
+        assert_eq!(vec![pending], peer_book.remove_expired_outbound(later));
+        assert!(peer_book.select_outbound(8).is_empty());
+
+        assert!(peer_book.remove_expired_outbound(later).is_empty());
+        assert_eq!(vec![pending], peer_book.select_outbound(8));
+        assert_eq!(1, peer_book.connected_outbound_total());
+    }
+
+    #[test]
+    fn test_update_peer_info() {
+        let mut peer_book = PeerBook::new();
+        let address: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+
+        let mut services = Services::FULL_NODE;
+        services.insert(Services::MINER);
+        let identity = NodeIdentity::new(0, BlockHeaderHash([0u8; 32]), services);
+
+        assert_eq!(PeerInfo::default(), peer_book.get_peer_info(&address));
+
+        peer_book.update_peer_info(address, &Version::new(&identity, 10, address, address));
+
+        let peer_info = peer_book.get_peer_info(&address);
+        assert_eq!(Some(identity.version), peer_info.version);
+        assert_eq!(Some(10), peer_info.height);
+        assert_eq!(Some(identity.user_agent), peer_info.user_agent);
+        assert_eq!(Some(services), peer_info.services);
+    }
+
+    #[test]
+    fn test_verify_public_key() {
+        let mut peer_book = PeerBook::new();
+        let address: SocketAddr = "127.0.0.1:4131".parse().unwrap();
+
+        assert!(peer_book.verify_public_key(address, vec![1u8; 32]));
+        assert!(peer_book.verify_public_key(address, vec![1u8; 32]));
+
+        // A known peer can't change its key
+        assert!(!peer_book.verify_public_key(address, vec![2u8; 32]));
+        assert_eq!(Some(vec![1u8; 32]), peer_book.get_peer_info(&address).public_key);
+
+        // A forgotten peer is known by its next key
+        peer_book.forget_peer(address);
+        assert!(peer_book.verify_public_key(address, vec![2u8; 32]));
     }
 }
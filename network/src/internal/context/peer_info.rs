@@ -0,0 +1,36 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::external::Services;
+
+/// Stores the metadata a peer has reported about itself.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PeerInfo {
+    /// The network version of the peer
+    pub version: Option<u64>,
+
+    /// The latest block height of the peer
+    pub height: Option<u32>,
+
+    /// The software the peer is running
+    pub user_agent: Option<String>,
+
+    /// The services the peer offers
+    pub services: Option<Services>,
+
+    /// The static key the peer authenticated its encrypted connections with
+    pub public_key: Option<Vec<u8>>,
+}
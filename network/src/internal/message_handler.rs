@@ -16,27 +16,32 @@
 
 use crate::{
     external::{
-        message::{Message, MessageName},
+        message::{Message, MessageName, MAX_HEADERS, MAX_SYNC_BLOCK_HASHES},
         message_types::*,
         propagate_block,
         protocol::SyncState,
         Channel,
         Pings,
     },
-    internal::process_transaction_internal,
+    internal::{
+        disconnect_peer_internal,
+        penalize_peer_internal,
+        process_transaction_internal,
+        Misbehavior,
+        PartialBlock,
+    },
     Server,
 };
-use snarkos_consensus::memory_pool::Entry;
+use snarkos_consensus::memory_pool::{Admission, Entry, Rejection};
 use snarkos_dpc::base_dpc::instantiated::Tx;
-use snarkos_errors::network::ServerError;
-use snarkos_objects::{Block as BlockStruct, BlockHeaderHash};
+use snarkos_errors::{consensus::ConsensusError, network::ServerError};
+use snarkos_objects::{merkle_root, Block as BlockStruct, BlockHeader, BlockHeaderHash};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
     to_bytes,
 };
 
-use chrono::Utc;
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, future::Future, net::SocketAddr, sync::Arc};
 
 impl Server {
     /// This method handles all messages sent from connected peers.
@@ -45,137 +50,156 @@ impl Server {
     /// the message name, bytes, associated channel, and a tokio oneshot sender.
     ///
     /// The oneshot sender lets the connection thread know when the message is handled.
-    pub async fn message_handler(&mut self) {
-        // TODO (raychu86) Create a macro to the handle the error messages.
+    ///
+    /// Once the `shutdown` future resolves, the receiver is closed and the messages
+    /// that were already received are handled before returning.
+    pub async fn message_handler<F: Future<Output = ()>>(&mut self, shutdown: F) {
+        tokio::pin!(shutdown);
+        let mut shutting_down = false;
+
         // TODO (howardwu): Come back and add error handlers to these.
-        while let Some((tx, name, bytes, mut channel)) = self.receiver.recv().await {
-            if name == Block::name() {
-                if let Ok(block) = Block::deserialize(bytes) {
-                    if let Err(err) = self.receive_block_message(block, channel.clone(), true).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
+        loop {
+            let message = match shutting_down {
+                // Drain the messages that were received before the shutdown.
+                true => self.receiver.recv().await,
+                false => tokio::select! {
+                    message = self.receiver.recv() => message,
+                    _ = &mut shutdown => {
+                        info!("Shutting down the message handler");
+                        self.receiver.close();
+                        shutting_down = true;
+                        continue;
                     }
+                },
+            };
+
+            let (tx, name, bytes, mut channel) = match message {
+                Some(message) => message,
+                None => break,
+            };
+
+            // Drop the messages sent by banned peers.
+            if self.context.peer_book.read().await.is_banned(&channel.address.ip()) {
+                debug!("Ignoring a {} message from banned peer {}", name, channel.address);
+                if let Err(error) = tx.send(channel) {
+                    warn!("Error resetting connection thread ({:?})", error);
+                }
+                continue;
+            }
+
+            let result = if name == Block::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(block) => self.receive_block_message(block, channel.clone(), true).await,
+                    None => Ok(()),
+                }
+            } else if name == BlockTxn::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(blocktxn) => self.receive_block_txn(blocktxn, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == CompactBlock::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(compactblock) => self.receive_compact_block(compactblock, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == GetBlock::name() {
-                if let Ok(getblock) = GetBlock::deserialize(bytes) {
-                    if let Err(err) = self.receive_get_block(getblock, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getblock) => self.receive_get_block(getblock, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == GetBlockTxn::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getblocktxn) => self.receive_get_block_txn(getblocktxn, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == GetData::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getdata) => self.receive_get_data(getdata, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == GetHeaders::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getheaders) => self.receive_get_headers(getheaders, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == GetMemoryPool::name() {
-                if let Ok(getmemorypool) = GetMemoryPool::deserialize(bytes) {
-                    if let Err(err) = self.receive_get_memory_pool(getmemorypool, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getmemorypool) => self.receive_get_memory_pool(getmemorypool, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == GetPeers::name() {
-                if let Ok(getpeers) = GetPeers::deserialize(bytes) {
-                    if let Err(err) = self.receive_get_peers(getpeers, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getpeers) => self.receive_get_peers(getpeers, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == GetSync::name() {
-                if let Ok(getsync) = GetSync::deserialize(bytes) {
-                    if let Err(err) = self.receive_get_sync(getsync, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(getsync) => self.receive_get_sync(getsync, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == Headers::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(headers) => self.receive_headers(headers, channel.clone()).await,
+                    None => Ok(()),
+                }
+            } else if name == Inv::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(inv) => self.receive_inv(inv, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == MemoryPool::name() {
-                if let Ok(mempool) = MemoryPool::deserialize(bytes) {
-                    if let Err(err) = self.receive_memory_pool(mempool).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(mempool) => self.receive_memory_pool(mempool).await,
+                    None => Ok(()),
+                }
+            } else if name == NotFound::name() {
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(notfound) => self.receive_not_found(notfound, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == Peers::name() {
-                if let Ok(peers) = Peers::deserialize(bytes) {
-                    if let Err(err) = self.receive_peers(peers, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(peers) => self.receive_peers(peers, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == Ping::name() {
-                if let Ok(ping) = Ping::deserialize(bytes) {
-                    if let Err(err) = self.receive_ping(ping, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(ping) => self.receive_ping(ping, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == Pong::name() {
-                if let Ok(pong) = Pong::deserialize(bytes) {
-                    if let Err(err) = self.receive_pong(pong, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(pong) => self.receive_pong(pong, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == Sync::name() {
-                if let Ok(sync) = Sync::deserialize(bytes) {
-                    if let Err(err) = self.receive_sync(sync).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(sync) => self.receive_sync(sync).await,
+                    None => Ok(()),
                 }
             } else if name == SyncBlock::name() {
-                if let Ok(block) = Block::deserialize(bytes) {
-                    if let Err(err) = self.receive_block_message(block, channel.clone(), false).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(block) => self.receive_block_message(block, channel.clone(), false).await,
+                    None => Ok(()),
                 }
             } else if name == Transaction::name() {
-                if let Ok(transaction) = Transaction::deserialize(bytes) {
-                    if let Err(err) = self.receive_transaction(transaction, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(transaction) => self.receive_transaction(transaction, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == Version::name() {
-                if let Ok(version) = Version::deserialize(bytes) {
+                match self.deserialize_message(bytes, &channel).await {
                     // TODO (raychu86) Does `receive_version` need to return a channel?
-                    match self.receive_version(version, channel.clone()).await {
-                        Ok(returned_channel) => channel = returned_channel,
-                        Err(err) => error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        ),
-                    }
+                    Some(version) => self
+                        .receive_version(version, channel.clone())
+                        .await
+                        .map(|returned_channel| channel = returned_channel),
+                    None => Ok(()),
                 }
             } else if name == Verack::name() {
-                if let Ok(verack) = Verack::deserialize(bytes) {
-                    if let Err(err) = self.receive_verack(verack, channel.clone()).await {
-                        error!(
-                            "Message handler errored when receiving a {} message from {}. {}",
-                            name, channel.address, err
-                        );
-                    }
+                match self.deserialize_message(bytes, &channel).await {
+                    Some(verack) => self.receive_verack(verack, channel.clone()).await,
+                    None => Ok(()),
                 }
             } else if name == MessageName::from("disconnect") {
                 info!("Disconnected from peer {:?}", channel.address);
@@ -183,8 +207,17 @@ impl Server {
                     let mut peer_book = self.context.peer_book.write().await;
                     peer_book.disconnect_peer(channel.address);
                 }
+                Ok(())
             } else {
                 debug!("Message name not recognized {:?}", name.to_string());
+                Ok(())
+            };
+
+            if let Err(err) = result {
+                error!(
+                    "Message handler errored when receiving a {} message from {}. {}",
+                    name, channel.address, err
+                );
             }
 
             if let Err(error) = tx.send(channel) {
@@ -193,6 +226,17 @@ impl Server {
         }
     }
 
+    /// Deserializes a message sent by a peer, penalizing the peer if the message is malformed.
+    async fn deserialize_message<M: Message>(&self, bytes: Vec<u8>, channel: &Channel) -> Option<M> {
+        match M::deserialize(bytes) {
+            Ok(message) => Some(message),
+            Err(_) => {
+                penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::MalformedMessage).await;
+                None
+            }
+        }
+    }
+
     /// A peer has sent us a new block to process.
     async fn receive_block_message(
         &mut self,
@@ -200,38 +244,100 @@ impl Server {
         channel: Arc<Channel>,
         propagate: bool,
     ) -> Result<(), ServerError> {
-        let block = BlockStruct::deserialize(&message.data)?;
+        let block = match BlockStruct::deserialize(&message.data) {
+            Ok(block) => block,
+            Err(error) => {
+                penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::MalformedMessage).await;
+                return Err(error.into());
+            }
+        };
+        let block_hash = block.header.get_hash();
 
         info!(
             "Received a block from epoch {} with hash {:?}",
             block.header.time,
-            hex::encode(block.header.get_hash().0)
+            hex::encode(block_hash.0)
         );
 
+        // Sync blocks are only accepted if they were requested by the sync handler.
+        // Requested blocks arriving before their parent are buffered until the parent is synced.
+        if !propagate && !self.storage.block_hash_exists(&block_hash) {
+            let mut sync_handler = self.sync_handler_lock.lock().await;
+
+            if !sync_handler.is_requested(&block_hash) {
+                penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::UnsolicitedSyncBlock).await;
+                return Ok(());
+            }
+
+            let parent_hash = block.header.previous_block_hash.clone();
+            if !self.storage.block_hash_exists(&parent_hash)
+                && sync_handler.is_requested(&parent_hash)
+                && sync_handler.buffer_block(parent_hash, block_hash.clone(), message.data.clone())
+            {
+                debug!("Buffered a sync block until its parent is synced");
+                return Ok(());
+            }
+        }
+
         // Verify the block and insert it into the storage.
-        if !self.storage.block_hash_exists(&block.header.get_hash()) {
+        if !self.storage.block_hash_exists(&block_hash) {
             {
                 let mut memory_pool = self.memory_pool_lock.lock().await;
-                let inserted = self
+                let result = self
                     .consensus
-                    .receive_block(&self.parameters, &self.storage, &mut memory_pool, &block)
-                    .is_ok();
+                    .receive_block(&self.parameters, &self.storage, &mut memory_pool, &block);
+
+                // Only penalize the peer for a block that is invalid regardless of the ledger state,
+                // as an honest peer may relay a block that does not extend the chain of this node.
+                let is_invalid = match &result {
+                    Err(ConsensusError::BlockTooLarge(_, _)) => true,
+                    Err(ConsensusError::InvalidBlock(_)) => self.consensus.verify_block_integrity(&block).is_err(),
+                    _ => false,
+                };
+                if is_invalid {
+                    penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::InvalidBlock).await;
+                }
+
+                let inserted = result.is_ok();
 
                 if inserted && propagate {
                     // This is a new block, send it to our peers.
 
-                    propagate_block(self.context.clone(), message.data, channel.address).await?;
+                    propagate_block(self.context.clone(), block_hash, message.data, channel.address).await?;
                 } else if !propagate {
-                    if let Ok(mut sync_handler) = self.sync_handler_lock.try_lock() {
-                        sync_handler.clear_pending(Arc::clone(&self.storage));
-
-                        if sync_handler.sync_state != SyncState::Idle {
-                            // We are currently syncing with a node, ask for the next block.
-                            if let Some(channel) = self.context.connections.read().await.get(&sync_handler.sync_node) {
-                                sync_handler.increment(channel, Arc::clone(&self.storage)).await?;
+                    let mut sync_handler = self.sync_handler_lock.lock().await;
+
+                    // Process the buffered blocks building on the new block, and on the processed blocks.
+                    if inserted {
+                        let mut parents = vec![block_hash];
+                        while let Some(parent_hash) = parents.pop() {
+                            for (child_hash, child) in sync_handler.take_buffered_children(&parent_hash) {
+                                let processed = match BlockStruct::deserialize(&child) {
+                                    Ok(child) => self
+                                        .consensus
+                                        .receive_block(&self.parameters, &self.storage, &mut memory_pool, &child)
+                                        .is_ok(),
+                                    Err(_) => false,
+                                };
+
+                                match processed {
+                                    true => parents.push(child_hash),
+                                    false => debug!("Failed to process a buffered sync block"),
+                                }
                             }
                         }
                     }
+
+                    // The blocks left buffered without a requested parent are requested again.
+                    sync_handler.clear_pending(Arc::clone(&self.storage));
+
+                    if sync_handler.sync_state != SyncState::Idle {
+                        // We are currently syncing, ask the peers for the next blocks.
+                        let channels = self.context.connections.read().await.channels();
+                        sync_handler
+                            .increment(&channels, Arc::clone(&self.storage), self.context.transport.now())
+                            .await?;
+                    }
                 }
             }
         }
@@ -239,6 +345,137 @@ impl Server {
         Ok(())
     }
 
+    /// A peer has sent us the missing transactions of a compact block.
+    async fn receive_block_txn(&mut self, message: BlockTxn, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let partial_block = self
+            .context
+            .compact_blocks
+            .write()
+            .await
+            .remove(&message.block_hash, &channel.address);
+
+        match partial_block {
+            Some(mut partial_block) => {
+                if partial_block.fill(message.transactions) {
+                    self.process_partial_block(partial_block, channel).await
+                } else {
+                    self.request_full_block(message.block_hash, channel).await
+                }
+            }
+            None => {
+                debug!("Received transactions of a compact block not requested from {}", channel.address);
+                Ok(())
+            }
+        }
+    }
+
+    /// A peer has sent us a compact block.
+    /// Verify the proof of work of its header, rebuild it from our memory pool
+    /// and request the transactions we are missing from the peer.
+    async fn receive_compact_block(&mut self, message: CompactBlock, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let block_hash = message.header.get_hash();
+
+        self.context
+            .known_inventory
+            .write()
+            .await
+            .insert(channel.address, InventoryItem::Block(block_hash.clone()));
+
+        if self.storage.block_hash_exists(&block_hash) {
+            return Ok(());
+        }
+
+        // Don't spend any work on rebuilding a block that can't be valid.
+        if let Err(error) = self.consensus.verify_header_proof(&message.header) {
+            warn!("Received a compact block with an invalid header from {}: {}", channel.address, error);
+            penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::InvalidBlock).await;
+            return Ok(());
+        }
+
+        let partial_block = {
+            let memory_pool = self.memory_pool_lock.lock().await;
+
+            let transactions: HashMap<u64, &Entry<Tx>> = memory_pool
+                .transactions
+                .iter()
+                .map(|(transaction_id, entry)| (short_transaction_id(&block_hash, transaction_id), entry))
+                .collect();
+
+            PartialBlock::new(message, |short_id| {
+                transactions
+                    .get(&short_id)
+                    .and_then(|entry| to_bytes![entry.transaction].ok())
+            })
+        };
+
+        match partial_block {
+            Some(partial_block) => {
+                let missing = partial_block.missing();
+
+                if missing.is_empty() {
+                    self.process_partial_block(partial_block, channel).await
+                } else {
+                    channel.write(&GetBlockTxn::new(block_hash.clone(), missing)).await?;
+                    self.context
+                        .compact_blocks
+                        .write()
+                        .await
+                        .insert(block_hash, channel.address, partial_block);
+                    Ok(())
+                }
+            }
+            None => {
+                penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::MalformedMessage).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Process a rebuilt compact block like a full block.
+    /// If the rebuilt transactions don't match the block header, the full block is requested instead.
+    async fn process_partial_block(
+        &mut self,
+        partial_block: PartialBlock,
+        channel: Arc<Channel>,
+    ) -> Result<(), ServerError> {
+        let block_hash = partial_block.header.get_hash();
+
+        let block_bytes = match partial_block.to_block_bytes() {
+            Some(block_bytes) => block_bytes,
+            None => return self.request_full_block(block_hash, channel).await,
+        };
+
+        let matches_header = match BlockStruct::<Tx>::deserialize(&block_bytes) {
+            Ok(block) => match block.transactions.to_transaction_ids() {
+                Ok(transaction_ids) if !transaction_ids.is_empty() => {
+                    merkle_root(&transaction_ids) == block.header.merkle_root_hash.0.to_vec()
+                }
+                _ => false,
+            },
+            Err(_) => false,
+        };
+
+        if matches_header {
+            self.receive_block_message(Block::new(block_bytes), channel, true).await
+        } else {
+            debug!("Failed to rebuild a compact block from {}", channel.address);
+            self.request_full_block(block_hash, channel).await
+        }
+    }
+
+    /// Request the full block from a peer when a compact block could not be rebuilt.
+    async fn request_full_block(
+        &mut self,
+        block_hash: BlockHeaderHash,
+        channel: Arc<Channel>,
+    ) -> Result<(), ServerError> {
+        channel
+            .write(&GetData::new(vec![InventoryItem::Block(block_hash)]))
+            .await?;
+
+        Ok(())
+    }
+
     /// A peer has requested a block.
     async fn receive_get_block(&mut self, message: GetBlock, channel: Arc<Channel>) -> Result<(), ServerError> {
         if let Ok(block) = self.storage.get_block(&message.block_hash) {
@@ -248,6 +485,63 @@ impl Server {
         Ok(())
     }
 
+    /// A peer has requested the transactions of a compact block missing from its memory pool.
+    async fn receive_get_block_txn(&mut self, message: GetBlockTxn, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let transactions = match self.storage.get_block(&message.block_hash) {
+            Ok(block) => message
+                .indexes
+                .iter()
+                .map(|index| block.transactions.get(*index as usize).map(|transaction| to_bytes![transaction]))
+                .collect::<Option<Result<Vec<Vec<u8>>, _>>>(),
+            Err(_) => None,
+        };
+
+        match transactions {
+            Some(transactions) => channel.write(&BlockTxn::new(message.block_hash, transactions?)).await?,
+            None => {
+                let not_found = NotFound::new(vec![InventoryItem::Block(message.block_hash)]);
+                channel.write(&not_found).await?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A peer has requested announced transactions and blocks.
+    /// Send the items we have and a NotFound message listing the rest.
+    async fn receive_get_data(&mut self, message: GetData, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let mut not_found = vec![];
+
+        for item in message.inventory {
+            match &item {
+                InventoryItem::Transaction(transaction_id) => {
+                    let transaction_bytes = {
+                        let memory_pool = self.memory_pool_lock.lock().await;
+                        match memory_pool.transactions.get(transaction_id) {
+                            Some(entry) => Some(to_bytes![entry.transaction]?),
+                            None => None,
+                        }
+                    };
+
+                    match transaction_bytes {
+                        Some(transaction_bytes) => channel.write(&Transaction::new(transaction_bytes)).await?,
+                        None => not_found.push(item.clone()),
+                    }
+                }
+                InventoryItem::Block(block_hash) => match self.storage.get_block(block_hash) {
+                    Ok(block) => channel.write(&Block::new(block.serialize()?)).await?,
+                    Err(_) => not_found.push(item.clone()),
+                },
+            }
+        }
+
+        if !not_found.is_empty() {
+            channel.write(&NotFound::new(not_found)).await?;
+        }
+
+        Ok(())
+    }
+
     /// A peer has requested our memory pool transactions.
     async fn receive_get_memory_pool(
         &mut self,
@@ -257,9 +551,16 @@ impl Server {
         let memory_pool = self.memory_pool_lock.lock().await;
 
         let mut transactions = vec![];
+        let mut size = 0;
 
         for (_tx_id, entry) in &memory_pool.transactions {
             if let Ok(transaction_bytes) = to_bytes![entry.transaction] {
+                // Stay within the size our peers accept for a memory pool message.
+                size += 8 + transaction_bytes.len();
+                if size > self.consensus.max_block_size {
+                    break;
+                }
+
                 transactions.push(transaction_bytes);
             }
         }
@@ -311,7 +612,7 @@ impl Server {
     async fn receive_peers(&mut self, message: Peers, channel: Arc<Channel>) -> Result<(), ServerError> {
         let peer_book = &mut self.context.peer_book.write().await;
         for (addr, time) in message.addresses.iter() {
-            if &*self.context.local_address.read().await == addr {
+            if &*self.context.local_address.read().await == addr || peer_book.is_banned(&addr.ip()) {
                 continue;
             } else if peer_book.connected_contains(addr) {
                 peer_book.update_connected(addr.clone(), time.clone());
@@ -320,7 +621,7 @@ impl Server {
             }
         }
 
-        peer_book.update_connected(channel.address, Utc::now());
+        peer_book.update_connected(channel.address, self.context.transport.now());
 
         Ok(())
     }
@@ -331,7 +632,7 @@ impl Server {
         let mut peer_book = self.context.peer_book.write().await;
 
         if peer_book.connected_contains(&channel.address) {
-            peer_book.update_connected(channel.address, Utc::now());
+            peer_book.update_connected(channel.address, self.context.transport.now());
         }
 
         Pings::send_pong(message, channel).await?;
@@ -355,7 +656,7 @@ impl Server {
                     .peer_book
                     .write()
                     .await
-                    .update_connected(channel.address, Utc::now());
+                    .update_connected(channel.address, self.context.transport.now());
             }
             Err(error) => debug!(
                 "Invalid Pong message from: {:?}, Full error: {:?}",
@@ -376,9 +677,9 @@ impl Server {
                 let mut max_height = current_height;
 
                 // if the requester is behind more than 4000 blocks
-                if height + 4000 < current_height {
+                if height + MAX_SYNC_BLOCK_HASHES < current_height {
                     // send the max 4000 blocks
-                    max_height = height + 4000;
+                    max_height = height + MAX_SYNC_BLOCK_HASHES;
                 }
 
                 let mut block_hashes: Vec<BlockHeaderHash> = vec![];
@@ -404,11 +705,131 @@ impl Server {
         let height = self.storage.get_latest_block_height();
         let mut sync_handler = self.sync_handler_lock.lock().await;
 
-        sync_handler.receive_hashes(message.block_hashes, height);
+        sync_handler.receive_hashes(message.block_hashes, height, self.context.transport.now());
 
         // Received block headers
-        if let Some(channel) = self.context.connections.read().await.get(&sync_handler.sync_node) {
-            sync_handler.increment(channel, Arc::clone(&self.storage)).await?;
+        let channels = self.context.connections.read().await.channels();
+        sync_handler
+            .increment(&channels, Arc::clone(&self.storage), self.context.transport.now())
+            .await?;
+
+        Ok(())
+    }
+
+    /// A peer has requested the block headers following the latest block we share.
+    async fn receive_get_headers(&mut self, message: GetHeaders, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let latest_shared_hash = self.storage.get_latest_shared_hash(message.block_locator_hashes)?;
+        let current_height = self.storage.get_latest_block_height();
+
+        let mut headers: Vec<BlockHeader> = vec![];
+
+        if let Ok(height) = self.storage.get_block_number(&latest_shared_hash) {
+            // send at most 2000 headers
+            let max_height = current_height.min(height + MAX_HEADERS);
+
+            for block_num in height + 1..=max_height {
+                let block_hash = self.storage.get_block_hash(block_num)?;
+                headers.push(self.storage.get_block_header(&block_hash)?);
+            }
+        }
+
+        channel.write(&Headers::new(headers)).await?;
+
+        Ok(())
+    }
+
+    /// A sync node has sent us the block headers we are missing.
+    /// The header chain is verified before any of the blocks are requested.
+    async fn receive_headers(&mut self, message: Headers, channel: Arc<Channel>) -> Result<(), ServerError> {
+        match self.verify_headers(&message.headers) {
+            Ok(block_hashes) => self.receive_sync(Sync::new(block_hashes)).await,
+            Err(error) => {
+                warn!("Received an invalid header chain from {}: {}", channel.address, error);
+
+                // Only penalize the peer for a header chain that is invalid on every node,
+                // as the clock and storage of this node may differ from those of an honest peer.
+                let is_invalid = match &error {
+                    ServerError::ConsensusError(ConsensusError::FuturisticTimestamp(_, _)) => false,
+                    ServerError::ConsensusError(_) => true,
+                    _ => false,
+                };
+                if is_invalid {
+                    penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::InvalidHeaders).await;
+                }
+
+                // Stop syncing with the peer
+                self.receive_sync(Sync::new(vec![])).await
+            }
+        }
+    }
+
+    /// Verifies that the headers form a valid chain extending a block in our storage.
+    /// Returns the hashes of the blocks that are not stored yet, in chain order.
+    fn verify_headers(&self, headers: &[BlockHeader]) -> Result<Vec<BlockHeaderHash>, ServerError> {
+        let mut block_hashes = vec![];
+
+        let mut parent_header = match headers.first() {
+            Some(header) => self.storage.get_block_header(&header.previous_block_hash)?,
+            None => return Ok(block_hashes),
+        };
+
+        for header in headers {
+            let block_hash = header.get_hash();
+
+            if !self.storage.block_hash_exists(&block_hash) {
+                self.consensus.verify_header_chain(header, &parent_header)?;
+                block_hashes.push(block_hash);
+            }
+
+            parent_header = header.clone();
+        }
+
+        Ok(block_hashes)
+    }
+
+    /// A peer has announced transactions and blocks.
+    /// Request the items we don't have and haven't requested from another peer yet.
+    async fn receive_inv(&mut self, message: Inv, channel: Arc<Channel>) -> Result<(), ServerError> {
+        let mut missing = vec![];
+
+        {
+            let memory_pool = self.memory_pool_lock.lock().await;
+            let mut known_inventory = self.context.known_inventory.write().await;
+            let now = self.context.transport.now();
+
+            for item in message.inventory {
+                known_inventory.insert(channel.address, item.clone());
+
+                let stored = match &item {
+                    InventoryItem::Transaction(transaction_id) => memory_pool.transactions.contains_key(transaction_id),
+                    InventoryItem::Block(block_hash) => self.storage.block_hash_exists(block_hash),
+                };
+
+                if !stored && known_inventory.request(channel.address, item.clone(), now) {
+                    missing.push(item);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            channel.write(&GetData::new(missing)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// A peer does not have some of the transactions and blocks we requested.
+    /// Forget the requests of the items, so they can be requested from the next peer announcing them.
+    async fn receive_not_found(&mut self, message: NotFound, channel: Arc<Channel>) -> Result<(), ServerError> {
+        debug!(
+            "Peer {} does not have {} of the requested items",
+            channel.address,
+            message.inventory.len()
+        );
+
+        let mut known_inventory = self.context.known_inventory.write().await;
+        for item in &message.inventory {
+            known_inventory.remove_request(&channel.address, item);
         }
 
         Ok(())
@@ -416,7 +837,7 @@ impl Server {
 
     /// A peer has sent us a transaction.
     async fn receive_transaction(&mut self, message: Transaction, channel: Arc<Channel>) -> Result<(), ServerError> {
-        process_transaction_internal(
+        let admission = process_transaction_internal(
             self.context.clone(),
             &self.consensus,
             &self.parameters,
@@ -427,6 +848,10 @@ impl Server {
         )
         .await?;
 
+        if let Admission::Rejected(Rejection::Invalid) | Admission::Rejected(Rejection::Coinbase) = admission {
+            penalize_peer_internal(self.context.clone(), channel.address, Misbehavior::InvalidTransaction).await;
+        }
+
         Ok(())
     }
 
@@ -448,7 +873,7 @@ impl Server {
                     .peer_book
                     .write()
                     .await
-                    .update_connected(channel.address, Utc::now());
+                    .update_connected(channel.address, self.context.transport.now());
 
                 // Ask connected peer for more peers.
                 channel.write(&GetPeers).await?;
@@ -465,6 +890,7 @@ impl Server {
     }
 
     /// A connected peer has sent handshake request.
+    /// Disconnect from the peer if it runs on a different network or chain.
     /// Update peer's channel.
     /// If peer's block height is greater than ours, send a sync request.
     ///
@@ -473,6 +899,18 @@ impl Server {
     async fn receive_version(&mut self, message: Version, channel: Arc<Channel>) -> Result<Arc<Channel>, ServerError> {
         let peer_address = SocketAddr::new(channel.address.ip(), message.address_sender.port());
 
+        if let Err(error) = self.identity.check_compatibility(message.network_id, &message.genesis_hash) {
+            warn!("Disconnecting from incompatible peer {}: {}", peer_address, error);
+            disconnect_peer_internal(self.context.clone(), channel.address).await;
+            return Err(error.into());
+        }
+
+        self.context
+            .peer_book
+            .write()
+            .await
+            .update_peer_info(peer_address, &message);
+
         let peer_book = &mut self.context.peer_book.read().await;
 
         if *self.context.local_address.read().await != peer_address {
@@ -485,25 +923,28 @@ impl Server {
                     .await?;
             }
 
+            let mut sync_handler = self.sync_handler_lock.lock().await;
+            sync_handler.update_peer_height(peer_address, message.height);
+            sync_handler.update_peer_version(peer_address, message.version);
+
             // If our peer has a longer chain, send a sync message
             if message.height > self.storage.get_latest_block_height() {
                 debug!("Received a version message with a greater height {}", message.height);
                 // Update the sync node if the sync_handler is Idle and there are no requested block headers
-                if let Ok(mut sync_handler) = self.sync_handler_lock.try_lock() {
-                    if !sync_handler.is_syncing()
-                        && (sync_handler.block_headers.len() == 0 && sync_handler.pending_blocks.is_empty())
-                    {
-                        debug!("Attempting to sync with peer {}", peer_address);
-                        sync_handler.sync_node = peer_address;
-
-                        if let Ok(block_locator_hashes) = self.storage.get_block_locator_hashes() {
-                            channel.write(&GetSync::new(block_locator_hashes)).await?;
-                        }
-                    } else {
-                        if let Some(channel) = self.context.connections.read().await.get(&sync_handler.sync_node) {
-                            sync_handler.increment(channel, Arc::clone(&self.storage)).await?;
-                        }
+                if !sync_handler.is_syncing()
+                    && (sync_handler.block_headers.len() == 0 && sync_handler.pending_blocks.is_empty())
+                {
+                    debug!("Attempting to sync with peer {}", peer_address);
+                    sync_handler.sync_node = peer_address;
+
+                    if let Ok(block_locator_hashes) = self.storage.get_block_locator_hashes() {
+                        sync_handler.request_sync(&channel, block_locator_hashes).await?;
                     }
+                } else {
+                    let channels = self.context.connections.read().await.channels();
+                    sync_handler
+                        .increment(&channels, Arc::clone(&self.storage), self.context.transport.now())
+                        .await?;
                 }
             }
         }
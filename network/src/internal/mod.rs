@@ -23,9 +23,12 @@ pub use context::*;
 pub mod message_handler;
 pub use message_handler::*;
 
-use crate::{external::propagate_transaction, internal::Context};
+use crate::{
+    external::{propagate_transaction, Channel, NodeIdentity, Version},
+    internal::Context,
+};
 use snarkos_consensus::{
-    memory_pool::{Entry, MemoryPool},
+    memory_pool::{Admission, Entry, MemoryPool, Rejection},
     ConsensusParameters,
     MerkleTreeLedger,
 };
@@ -33,13 +36,18 @@ use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
 };
-use snarkos_errors::network::SendError;
+use snarkos_errors::network::{HandshakeError, SendError, ServerError};
 use snarkos_utilities::bytes::FromBytes;
 
-use std::{net::SocketAddr, sync::Arc};
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    net::{Shutdown, SocketAddr},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 
 /// Verify a transaction, add it to the memory pool, propagate it to peers.
+/// Returns whether the transaction was added to the memory pool, or why it was rejected.
 pub async fn process_transaction_internal(
     context: Arc<Context>,
     consensus: &ConsensusParameters,
@@ -48,18 +56,25 @@ pub async fn process_transaction_internal(
     memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
     transaction_bytes: Vec<u8>,
     transaction_sender: SocketAddr,
-) -> Result<(), SendError> {
+) -> Result<Admission, SendError> {
     if let Ok(transaction) = Tx::read(&transaction_bytes[..]) {
         let mut memory_pool = memory_pool_lock.lock().await;
 
+        // A transaction that does not fit the ledger state of this node is not invalid on every node,
+        // as an honest peer may relay it before receiving a block that spends its records.
+        if let Some(rejection) = consensus.verify_transaction_ledger_state(&transaction, &storage) {
+            debug!("Received a transaction that does not fit the ledger ({})", rejection);
+            return Ok(Admission::Rejected(rejection));
+        }
+
         if !consensus.verify_transaction(parameters, &transaction, &storage)? {
             error!("Received a transaction that was invalid");
-            return Ok(());
+            return Ok(Admission::Rejected(Rejection::Invalid));
         }
 
         if transaction.value_balance.is_negative() {
             error!("Received a transaction that was a coinbase transaction");
-            return Ok(());
+            return Ok(Admission::Rejected(Rejection::Coinbase));
         }
 
         let entry = Entry::<Tx> {
@@ -67,13 +82,164 @@ pub async fn process_transaction_internal(
             transaction,
         };
 
-        if let Ok(inserted) = memory_pool.insert(&storage, entry) {
-            if inserted.is_some() {
+        let admission = memory_pool.submit(&storage, entry)?;
+
+        match &admission {
+            Admission::Accepted(transaction_id) | Admission::Replaced { transaction_id, .. } => {
                 info!("Transaction added to memory pool.");
-                propagate_transaction(context.clone(), transaction_bytes, transaction_sender).await?;
+                propagate_transaction(
+                    context.clone(),
+                    transaction_id.clone(),
+                    transaction_bytes,
+                    transaction_sender,
+                )
+                .await?;
             }
+            Admission::Rejected(rejection) => debug!("Transaction not added to memory pool: {}", rejection),
         }
+
+        return Ok(admission);
     }
 
-    Ok(())
+    Ok(Admission::Rejected(Rejection::Invalid))
+}
+
+/// Send a handshake request advertising the node identity to a peer that is not banned.
+/// The peer is marked as an outbound connection before the request is sent,
+/// so its connection back to this node does not take an inbound slot, and is unmarked if the handshake fails.
+pub async fn connect_peer_internal(
+    context: Arc<Context>,
+    identity: &NodeIdentity,
+    latest_block_height: u32,
+    remote_address: SocketAddr,
+) -> Result<(), ServerError> {
+    {
+        let mut peer_book = context.peer_book.write().await;
+
+        if peer_book.is_banned(&remote_address.ip()) {
+            return Err(ServerError::PeerBanned(remote_address));
+        }
+
+        peer_book.set_outbound(remote_address, context.transport.now());
+    }
+
+    let local_address = *context.local_address.read().await;
+
+    let version = Version::new(identity, latest_block_height, remote_address, local_address);
+
+    let mut result = context
+        .handshakes
+        .write()
+        .await
+        .send_request(&version, &context.transport, &context.encryption)
+        .await;
+
+    // Drop the handshake if the peer authenticated with a different key than before.
+    if result.is_ok() {
+        let mut handshakes = context.handshakes.write().await;
+
+        let known_key = match handshakes.get(&remote_address) {
+            Some(handshake) => verify_peer_key(&context, &handshake.channel).await,
+            None => true,
+        };
+
+        if !known_key {
+            if let Some(handshake) = handshakes.remove(&remote_address) {
+                if let Err(error) = handshake.channel.writer.lock().await.shutdown(Shutdown::Both) {
+                    debug!("Failed to shutdown the connection to {} ({})", remote_address, error);
+                }
+            }
+            result = Err(HandshakeError::PublicKeyMismatch(remote_address));
+        }
+    }
+
+    if result.is_err() {
+        context.peer_book.write().await.remove_outbound(&remote_address);
+    }
+
+    Ok(result?)
+}
+
+/// Records the static key a peer authenticated the encrypted sessions of its channel with.
+/// Returns false if the peer authenticated with a different key before, so it may be another node
+/// connecting from the address of a known peer, and the channel must not be used.
+pub async fn verify_peer_key(context: &Context, channel: &Channel) -> bool {
+    let sessions = channel.reader_session.iter().chain(channel.writer_session.iter());
+
+    let mut peer_book = context.peer_book.write().await;
+    for public_key in sessions.filter_map(|session| session.remote_public_key()) {
+        if !peer_book.verify_public_key(channel.address, public_key) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Disconnect from a connected peer and close its connection channel.
+/// Returns false if the peer was not connected.
+pub async fn disconnect_peer_internal(context: Arc<Context>, address: SocketAddr) -> bool {
+    {
+        let mut peer_book = context.peer_book.write().await;
+
+        if !peer_book.connected_contains(&address) {
+            return false;
+        }
+
+        peer_book.disconnect_peer(address);
+    }
+
+    context.known_inventory.write().await.remove_peer(&address);
+
+    if let Some(channel) = context.connections.write().await.remove(&address) {
+        // The reader is locked while waiting for messages, so the connection is closed through the writer,
+        // and the connection thread is signalled to stop reading and release the reader.
+        if let Err(error) = channel.writer.lock().await.shutdown(Shutdown::Both) {
+            debug!("Failed to shutdown the connection to {} ({})", address, error);
+        }
+        channel.close();
+    }
+
+    true
+}
+
+/// Ban a subnet for the given duration and disconnect from its connected peers.
+/// Returns the date the ban expires.
+pub async fn ban_subnet_internal(context: Arc<Context>, subnet: IpSubnet, duration: Duration) -> DateTime<Utc> {
+    let banned_until = Utc::now().checked_add_signed(duration).unwrap_or(chrono::MAX_DATETIME);
+
+    let connected_peers = {
+        let mut peer_book = context.peer_book.write().await;
+        peer_book.ban(subnet, banned_until);
+        peer_book.get_connected()
+    };
+
+    for (address, _last_seen) in connected_peers {
+        if subnet.contains(&address.ip()) {
+            disconnect_peer_internal(context.clone(), address).await;
+        }
+    }
+
+    banned_until
+}
+
+/// Decrement the score of a misbehaving peer and ban it once its score falls to the ban threshold.
+/// Returns true if the peer was banned.
+pub async fn penalize_peer_internal(context: Arc<Context>, address: SocketAddr, misbehavior: Misbehavior) -> bool {
+    debug!("Peer {} misbehaved ({})", address, misbehavior);
+
+    if !context.peer_book.write().await.penalize(address, misbehavior) {
+        return false;
+    }
+
+    let banned_until = ban_subnet_internal(
+        context,
+        IpSubnet::from(address.ip()),
+        Duration::seconds(MISBEHAVIOR_BAN_DURATION_SECS),
+    )
+    .await;
+
+    warn!("Banned misbehaving peer {} until {}", address, banned_until);
+
+    true
 }
@@ -15,24 +15,34 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    external::{message::MessageName, message_types::GetSync, protocol::*, Channel, Version},
-    internal::context::Context,
+    external::{
+        message::{MessageLimits, MessageName},
+        protocol::*,
+        Channel,
+        NodeIdentity,
+        Services,
+    },
+    internal::{connect_peer_internal, context::Context, penalize_peer_internal, verify_peer_key, Misbehavior},
 };
 use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
 use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
 };
-use snarkos_errors::network::ServerError;
+use snarkos_errors::network::{
+    message::{MessageError, MessageHeaderError},
+    ConnectError,
+    ServerError,
+};
 
 use chrono::{DateTime, Utc};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    future::Future,
     net::{Shutdown, SocketAddr},
     sync::Arc,
 };
 use tokio::{
-    net::TcpListener,
     sync::{mpsc, oneshot, Mutex},
     task,
 };
@@ -41,6 +51,7 @@ use tokio::{
 pub struct Server {
     pub consensus: ConsensusParameters,
     pub context: Arc<Context>,
+    pub identity: NodeIdentity,
     pub storage: Arc<MerkleTreeLedger>,
     pub parameters: PublicParameters<Components>,
     pub memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
@@ -52,6 +63,7 @@ pub struct Server {
 
 impl Server {
     /// Constructs a new `Server`.
+    /// Returns an error if the storage does not contain a genesis block.
     pub fn new(
         context: Arc<Context>,
         consensus: ConsensusParameters,
@@ -60,11 +72,26 @@ impl Server {
         memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
         sync_handler_lock: Arc<Mutex<SyncHandler>>,
         connection_frequency: u64,
-    ) -> Self {
+    ) -> Result<Self, ServerError> {
         let (sender, receiver) = mpsc::channel(1024);
-        Server {
+
+        let mut services = Services::FULL_NODE;
+        services.insert(Services::SERVES_HISTORY);
+        if context.is_miner {
+            services.insert(Services::MINER);
+        }
+        if context.is_bootnode {
+            services.insert(Services::BOOTNODE);
+        }
+
+        // The genesis hash is advertised in the handshake, so peers on another chain are rejected.
+        let genesis_hash = storage.get_block_hash(0)?;
+        let identity = NodeIdentity::new(consensus.network.id(), genesis_hash, services);
+
+        Ok(Server {
             consensus,
             context,
+            identity,
             storage,
             parameters,
             memory_pool_lock,
@@ -72,32 +99,49 @@ impl Server {
             sender,
             sync_handler_lock,
             connection_frequency,
-        }
+        })
     }
 
     /// Starts the server event loop.
     ///
-    /// 1. Initialize TCP listener at `local_address` and accept new TCP connections.
+    /// 1. Initialize the listener of the transport at `local_address` and accept new connections.
+    ///    Connection requests from peers banned in a previous run are rejected.
     /// 2. Spawn a new thread to handle new connections.
     /// 3. Start the connection handler.
     /// 4. Send a handshake request to all bootnodes.
-    /// 5. Send a handshake request to all stored peers.
+    /// 5. Send a handshake request to the anchor peers, and add the other stored peers to the disconnected peers.
     /// 6. Start the message handler.
-    pub async fn listen(mut self) -> Result<(), ServerError> {
-        // 1. Initialize TCP listener at `local_address` and accept new TCP connections.
+    ///
+    /// The server runs until the runtime it was started in is shut down.
+    /// See [`listen_until`](Server::listen_until) for a server that can be stopped gracefully.
+    pub async fn listen(self) -> Result<(), ServerError> {
+        self.listen_until(std::future::pending()).await
+    }
+
+    /// Starts the server event loop and runs it until the given `shutdown` future resolves.
+    ///
+    /// Once the `shutdown` future resolves, the message handler stops accepting new messages,
+    /// handles the messages that were already received, and the memory pool and peer book
+    /// are persisted to storage before returning.
+    pub async fn listen_until<F: Future<Output = ()>>(mut self, shutdown: F) -> Result<(), ServerError> {
+        // 1. Initialize the listener of the transport at `local_address` and accept new connections.
         let (mut listener, local_address) = {
-            let address = self.context.local_address.read().await;
-            let local_address = format!("0.0.0.0:{}", address.port()).parse::<SocketAddr>()?;
+            let local_address = *self.context.local_address.read().await;
             info!("Starting listener...");
-            (TcpListener::bind(&local_address).await?, local_address)
+            (self.context.transport.bind(local_address).await?, local_address)
         };
         info!("Listening at {:?}", local_address);
 
+        // Restore the bans of the previous run before accepting connections.
+        self.load_bans_from_storage().await;
+
         // Prepare to spawn the main loop.
         let sender = self.sender.clone();
         let storage = self.storage.clone();
         let context = self.context.clone();
+        let identity = self.identity.clone();
         let sync_handler_lock = self.sync_handler_lock.clone();
+        let limits = MessageLimits::new(self.consensus.max_block_size);
 
         // 2. Spawn a new thread to handle new connections.
         task::spawn(async move {
@@ -105,7 +149,7 @@ impl Server {
 
             loop {
                 // Listen for new peers.
-                let (reader, remote_address) = match listener.accept().await {
+                let (mut reader, remote_address) = match listener.accept().await {
                     Ok((reader, remote_address)) => {
                         info!("Received a connection request from {}", remote_address);
                         (reader, remote_address)
@@ -116,22 +160,50 @@ impl Server {
                     }
                 };
 
-                // Check if we've exceed our maximum number of allowed peers.
-                if context.peer_book.read().await.connected_total() >= context.max_peers {
-                    warn!("Rejected a connection request as this exceeds the maximum number of peers allowed");
-                    if let Err(error) = reader.shutdown(Shutdown::Write) {
+                // Reject connection requests from banned peers.
+                if context.peer_book.read().await.is_banned(&remote_address.ip()) {
+                    debug!("Rejected a connection request from banned peer {}", remote_address);
+                    if let Err(error) = reader.shutdown(Shutdown::Both) {
                         error!("Failed to shutdown peer reader ({})", error);
                     }
                     continue;
                 }
 
+                // Check if we've exceeded our maximum number of inbound peers.
+                // The peers we sent a handshake request to connect back to us with a response to the request,
+                // which is still accepted as they don't take an inbound slot.
+                let inbound_slots_full =
+                    context.peer_book.read().await.connected_inbound_total() >= context.max_inbound_peers;
+
                 // Follow handshake protocol and drop peer connection if unsuccessful.
                 let height = storage.get_latest_block_height();
                 let mut handshakes = context.handshakes.write().await; // Acquire the handshake lock
-                // TODO (raychu86) Establish a formal node version
-                if let Ok((handshake, discovered_local_address, version_message)) =
-                    handshakes.receive_any(1u64, height, remote_address, reader).await
+                if let Ok((handshake, discovered_local_address, version_message)) = handshakes
+                    .receive_any(
+                        &identity,
+                        &context.transport,
+                        &context.encryption,
+                        height,
+                        remote_address,
+                        reader,
+                        !inbound_slots_full,
+                    )
+                    .await
                 {
+                    // Reject a peer that authenticated with a different key than before,
+                    // as it may be another node connecting from the address of a known peer.
+                    if !verify_peer_key(&context, &handshake.channel).await {
+                        warn!("Rejected a handshake with {} as its key changed", handshake.channel.address);
+                        handshakes.remove(&handshake.channel.address);
+                        if let Err(error) = handshake.channel.reader.lock().await.shutdown(Shutdown::Both) {
+                            error!("Failed to shutdown peer reader ({})", error);
+                        }
+                        if let Err(error) = handshake.channel.writer.lock().await.shutdown(Shutdown::Both) {
+                            error!("Failed to shutdown peer writer ({})", error);
+                        }
+                        continue;
+                    }
+
                     // Bootstrap discovery of local node IP via VERACK responses
                     {
                         let mut local_address = context.local_address.write().await;
@@ -150,6 +222,17 @@ impl Server {
                     }
 
                     if let Some(version) = version_message {
+                        context
+                            .peer_book
+                            .write()
+                            .await
+                            .update_peer_info(handshake.channel.address, &version);
+
+                        if let Ok(mut sync_handler) = sync_handler_lock.try_lock() {
+                            sync_handler.update_peer_height(handshake.channel.address, version.height);
+                            sync_handler.update_peer_version(handshake.channel.address, version.version);
+                        }
+
                         // If our peer has a longer chain, send a sync message
                         if version.height > storage.get_latest_block_height() {
                             // Update the sync node if the sync_handler is Idle
@@ -158,11 +241,12 @@ impl Server {
                                     sync_handler.sync_node = handshake.channel.address;
 
                                     if let Ok(block_locator_hashes) = storage.get_block_locator_hashes() {
-                                        if let Err(err) =
-                                            handshake.channel.write(&GetSync::new(block_locator_hashes)).await
+                                        if let Err(err) = sync_handler
+                                            .request_sync(&handshake.channel, block_locator_hashes)
+                                            .await
                                         {
                                             error!(
-                                                "Error sending GetSync message to {}, {}",
+                                                "Error sending sync request to {}, {}",
                                                 handshake.channel.address, err
                                             );
                                         }
@@ -173,7 +257,7 @@ impl Server {
                     }
 
                     // Inner loop spawns one thread per connection to read messages
-                    Self::spawn_connection_thread(handshake.channel.clone(), sender.clone());
+                    Self::spawn_connection_thread(handshake.channel.clone(), sender.clone(), context.clone(), limits);
                 }
             }
         });
@@ -188,14 +272,25 @@ impl Server {
 
         // If the node is a bootnode, do not send requests to stored peers
         if !self.context.is_bootnode {
-            // 5. Send a handshake request to all stored peers.
-            debug!("Sending handshake request to all stored peers");
+            // 5. Send a handshake request to the anchor peers.
+            debug!("Sending handshake request to anchor peers");
             self.connect_peers_from_storage().await;
         }
 
         // 6. Start the message handler.
         debug!("Starting message handler");
-        self.message_handler().await;
+        self.message_handler(shutdown).await;
+
+        // 7. Persist the server state after the message handler has been drained.
+        self.store_state().await
+    }
+
+    /// Stores the memory pool, the connected peers and the active bans in the database.
+    pub async fn store_state(&self) -> Result<(), ServerError> {
+        info!("Storing the memory pool and peer book");
+
+        self.memory_pool_lock.lock().await.store(&self.storage)?;
+        self.context.peer_book.read().await.store(&self.storage)?;
 
         Ok(())
     }
@@ -204,9 +299,12 @@ impl Server {
     /// Each thread is given a handle to the channel and a handle to the server mpsc sender.
     /// To ensure concurrency, each connection thread sends a tokio oneshot sender handle with every message to the server mpsc receiver.
     /// The thread then waits for the oneshot receiver to receive a signal from the server before reading again.
+    /// Peers sending messages that violate the message limits are penalized and disconnected immediately.
     fn spawn_connection_thread(
         mut channel: Arc<Channel>,
         mut message_handler_sender: mpsc::Sender<(oneshot::Sender<Arc<Channel>>, MessageName, Vec<u8>, Arc<Channel>)>,
+        context: Arc<Context>,
+        limits: MessageLimits,
     ) {
         task::spawn(async move {
             // Determines the criteria for disconnecting from a peer.
@@ -249,9 +347,49 @@ impl Server {
                 // Initialize the failure indicator.
                 let mut failure = false;
 
-                // Read the next message from the channel. This is a blocking operation.
-                let (message_name, message_bytes) = match channel.read().await {
+                // Read the next message from the channel, until the peer is disconnected.
+                // This is a blocking operation.
+                let result = tokio::select! {
+                    result = channel.read_bounded(&limits) => result,
+                    _ = channel.closed() => break,
+                };
+
+                let (message_name, message_bytes) = match result {
                     Ok((message_name, message_bytes)) => (message_name, message_bytes),
+                    // The payload of a rejected message is left unread, so the connection can't be recovered.
+                    Err(ConnectError::MessageError(error @ MessageError::UnknownMessage(_)))
+                    | Err(ConnectError::MessageError(error @ MessageError::MessageTooLarge(_, _)))
+                    | Err(ConnectError::MessageError(error @ MessageError::InboundBudgetExceeded(_))) => {
+                        warn!("Rejected a message from {} ({})", channel.address, error);
+                        let misbehavior = match error {
+                            MessageError::InboundBudgetExceeded(_) => Misbehavior::ExceededInboundBudget,
+                            _ => Misbehavior::OversizedMessage,
+                        };
+                        penalize_peer_internal(context.clone(), channel.address, misbehavior).await;
+
+                        disconnect_from_peer = true;
+                        (MessageName::from("disconnect"), vec![])
+                    }
+                    // The peer is running on another network.
+                    Err(ConnectError::MessageHeaderError(error @ MessageHeaderError::NetworkMismatch(_, _))) => {
+                        warn!("Rejected a message from {} ({})", channel.address, error);
+
+                        disconnect_from_peer = true;
+                        (MessageName::from("disconnect"), vec![])
+                    }
+                    // The payload of a corrupt frame has been read, so the next message can still be read.
+                    Err(ConnectError::MessageHeaderError(error @ MessageHeaderError::InvalidChecksum(_, _))) => {
+                        warn!("Rejected a message from {} ({})", channel.address, error);
+                        let misbehavior = Misbehavior::MalformedMessage;
+                        disconnect_from_peer =
+                            penalize_peer_internal(context.clone(), channel.address, misbehavior).await;
+
+                        // Determine if we should send a disconnect message.
+                        match disconnect_from_peer {
+                            true => (MessageName::from("disconnect"), vec![]),
+                            false => continue,
+                        }
+                    }
                     Err(error) => {
                         handle_failure(&mut failure, &mut failure_count, &mut disconnect_from_peer, error).await;
 
@@ -290,23 +428,29 @@ impl Server {
                     break;
                 }
             }
+
+            // Close the connection once the reader is released.
+            if let Err(error) = channel.reader.lock().await.shutdown(Shutdown::Both) {
+                debug!("Failed to close the connection to {} ({})", channel.address, error);
+            }
         });
     }
 
     /// Send a handshake request to a node at address without blocking the server listener.
     fn send_handshake_non_blocking(&self, remote_address: SocketAddr) {
         let context = self.context.clone();
+        let identity = self.identity.clone();
         let storage = self.storage.clone();
 
         task::spawn(async move {
             let height = storage.get_latest_block_height();
-            let version = Version::new(1u64, height, remote_address, *context.local_address.read().await);
 
-            let mut handshakes = context.handshakes.write().await;
-            handshakes.send_request(&version).await.unwrap_or_else(|error| {
-                info!("Failed to connect to {:?}", error);
-                ()
-            });
+            connect_peer_internal(context, &identity, height, remote_address)
+                .await
+                .unwrap_or_else(|error| {
+                    info!("Failed to connect to {:?}", error);
+                    ()
+                });
         });
     }
 
@@ -324,18 +468,39 @@ impl Server {
         }
     }
 
-    /// Send a handshake request to every peer this server previously connected to.
+    /// Restore the peer bans that were active when this server was last stopped.
+    async fn load_bans_from_storage(&self) {
+        if let Err(error) = self.context.peer_book.write().await.load_bans(&self.storage) {
+            debug!("Failed to load banned peers from storage {}", error);
+        }
+    }
+
+    /// Send a handshake request to the outbound peers this server was connected to when it was last stopped.
+    /// The other peers it was connected to are only stored as disconnected peers, so the peers that connected
+    /// to this server can't take over its outbound connections by reconnecting first.
     async fn connect_peers_from_storage(&mut self) {
+        let local_address = *self.context.local_address.read().await;
+
         if let Ok(serialized_peers) = self.storage.get_peer_book() {
             if let Ok(stored_connected_peers) =
                 bincode::deserialize::<HashMap<SocketAddr, DateTime<Utc>>>(&serialized_peers)
             {
-                let local_address = *self.context.local_address.read().await;
-                for (saved_address, _old_time) in stored_connected_peers {
-                    // This node should not attempt to connect to itself.
+                let mut peer_book = self.context.peer_book.write().await;
+                for (saved_address, last_seen) in stored_connected_peers {
                     if local_address != saved_address {
-                        info!("Connecting to {:?} (saved peer)...", saved_address);
-                        self.send_handshake_non_blocking(saved_address);
+                        peer_book.update_disconnected(saved_address, last_seen);
+                    }
+                }
+            }
+        }
+
+        if let Ok(serialized_anchors) = self.storage.get_anchor_peers() {
+            if let Ok(anchors) = bincode::deserialize::<HashSet<SocketAddr>>(&serialized_anchors) {
+                for anchor in anchors.into_iter().take(self.context.max_outbound_peers as usize) {
+                    // This node should not attempt to connect to itself.
+                    if local_address != anchor {
+                        info!("Connecting to {:?} (anchor peer)...", anchor);
+                        self.send_handshake_non_blocking(anchor);
                     }
                 }
             }
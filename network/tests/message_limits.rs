@@ -0,0 +1,119 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod message_limits {
+    use snarkos_errors::network::{message::MessageError, ConnectError};
+    use snarkos_network::external::{
+        message::{MessageHeader, MessageLimits, MessageName},
+        message_types::Ping,
+        Channel,
+        Message,
+    };
+    use snarkos_testing::network::random_socket_address;
+
+    use serial_test::serial;
+    use std::net::SocketAddr;
+    use tokio::{
+        net::{TcpListener, TcpStream},
+        prelude::*,
+    };
+
+    /// Sends the given raw bytes to the address and returns the channel reading them.
+    async fn send_raw(address: SocketAddr, bytes: Vec<u8>) -> Channel {
+        let mut listener = TcpListener::bind(address).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream.write_all(&bytes).await.unwrap();
+
+            // Keep the connection open so the reader does not fail on a closed stream.
+            tokio::time::delay_for(std::time::Duration::from_secs(5)).await;
+        });
+
+        let (reader, _socket) = listener.accept().await.unwrap();
+
+        Channel::new_read_only(reader).unwrap()
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reject_oversized_block() {
+        // A hostile header announcing a block of u32::MAX bytes without sending the payload
+        let header = MessageHeader::new(MessageName::from("block"), u32::MAX);
+        let channel = send_raw(random_socket_address(), header.serialize().unwrap()).await;
+
+        match channel.read_bounded(&MessageLimits::new(1_000_000)).await {
+            Err(ConnectError::MessageError(MessageError::MessageTooLarge(len, max_len))) => {
+                assert_eq!(u32::MAX as usize, len);
+                assert_eq!(1_000_008, max_len);
+            }
+            result => panic!("expected the block to be rejected, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reject_oversized_ping() {
+        let header = MessageHeader::new(Ping::name(), 1_000_000);
+        let channel = send_raw(random_socket_address(), header.serialize().unwrap()).await;
+
+        match channel.read().await {
+            Err(ConnectError::MessageError(MessageError::MessageTooLarge(_, _))) => {}
+            result => panic!("expected the ping to be rejected, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reject_unknown_message() {
+        let header = MessageHeader::new(MessageName::from("hostile"), 8);
+        let channel = send_raw(random_socket_address(), header.serialize().unwrap()).await;
+
+        match channel.read().await {
+            Err(ConnectError::MessageError(MessageError::UnknownMessage(name))) => assert_eq!("hostile", name),
+            result => panic!("expected the message to be rejected, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reject_exceeded_inbound_budget() {
+        let ping = Ping::new().serialize().unwrap();
+        let header = MessageHeader::new(Ping::name(), ping.len() as u32).serialize().unwrap();
+
+        let mut bytes = vec![];
+        for _ in 0..2 {
+            bytes.extend_from_slice(&header);
+            bytes.extend_from_slice(&ping);
+        }
+
+        let channel = send_raw(random_socket_address(), bytes).await;
+
+        // The budget only fits a single ping message
+        let limits = MessageLimits {
+            max_block_size: 1_000_000,
+            inbound_byte_budget: (header.len() + ping.len()) as u64,
+        };
+
+        let (name, _bytes) = channel.read_bounded(&limits).await.unwrap();
+        assert_eq!(Ping::name(), name);
+
+        match channel.read_bounded(&limits).await {
+            Err(ConnectError::MessageError(MessageError::InboundBudgetExceeded(_))) => {}
+            result => panic!("expected the budget to be exceeded, got {:?}", result),
+        }
+    }
+}
@@ -199,10 +199,12 @@ mod server_connection_handler {
 
             let mut peer_book = context.peer_book.write().await;
 
-            // 1. Add the maximum number of connected to the local node peer book
+            // 1. Add the maximum number of outbound connected peers to the local node peer book
 
             for _x in 0..10 {
-                peer_book.update_connected(random_socket_address(), Utc::now());
+                let address = random_socket_address();
+                peer_book.update_connected(address, Utc::now());
+                peer_book.set_outbound(address, Utc::now());
             }
 
             // 2. Add peer with old date to gossiped in peer_book
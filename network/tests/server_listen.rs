@@ -23,9 +23,11 @@ mod server_listen {
     use snarkos_network::{
         external::{
             message::Message,
-            message_types::{GetPeers, GetSync, Verack},
+            message_types::{GetHeaders, GetPeers, Verack},
             protocol::SyncHandler,
+            Encryption,
             Handshakes,
+            Transport,
         },
         internal::context::Context,
         server::Server,
@@ -34,7 +36,11 @@ mod server_listen {
 
     use chrono::{DateTime, Utc};
     use serial_test::serial;
-    use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+    use std::{
+        collections::{HashMap, HashSet},
+        net::SocketAddr,
+        sync::Arc,
+    };
     use tokio::{
         net::TcpListener,
         runtime::Runtime,
@@ -74,7 +80,8 @@ mod server_listen {
             memory_pool_lock,
             sync_handler_lock,
             10000,
-        );
+        )
+        .unwrap();
 
         tx.send(()).unwrap();
 
@@ -122,6 +129,7 @@ mod server_listen {
         let storage = Arc::new(FIXTURE_VK.ledger());
         let path = storage.storage.db.path().to_owned();
         let parameters = load_verifying_parameters();
+        let identity = test_node_identity(&storage);
 
         let mut rt = Runtime::new().unwrap();
 
@@ -152,7 +160,15 @@ mod server_listen {
 
             let mut bootnode_handshakes = Handshakes::new();
             let (mut bootnode_hand, _, _) = bootnode_handshakes
-                .receive_any(1u64, 1u32, server_address, reader)
+                .receive_any(
+                    &identity,
+                    &Transport::Tcp,
+                    &Encryption::default(),
+                    1u32,
+                    server_address,
+                    reader.into(),
+                    true,
+                )
                 .await
                 .unwrap();
 
@@ -170,10 +186,10 @@ mod server_listen {
             let verack_message = Verack::deserialize(bytes).unwrap();
             bootnode_hand.accept(verack_message).await.unwrap();
 
-            // 7. Check that bootnode received GetSync message
+            // 7. Check that bootnode received GetHeaders message, as it supports headers-first sync
 
             let (name, _bytes) = bootnode_hand.channel.read().await.unwrap();
-            assert_eq!(GetSync::name(), name);
+            assert_eq!(GetHeaders::name(), name);
         });
 
         drop(rt);
@@ -182,22 +198,24 @@ mod server_listen {
 
     #[test]
     #[serial]
-    fn startup_handshake_stored_peers() {
+    fn startup_handshake_anchor_peers() {
         let storage = Arc::new(FIXTURE_VK.ledger());
         let path = storage.storage.db.path().to_owned();
         let parameters = load_verifying_parameters();
+        let identity = test_node_identity(&storage);
 
         let mut rt = Runtime::new().unwrap();
 
         rt.block_on(async move {
             let server_address = random_socket_address();
+            let bootnode_address = random_socket_address();
             let peer_address = random_socket_address();
 
             // 1. Start peer
 
             let mut peer_listener = TcpListener::bind(peer_address).await.unwrap();
 
-            // 2. Add peer to storage
+            // 2. Add peer to storage as an anchor peer
 
             let mut connected_peers = HashMap::<SocketAddr, DateTime<Utc>>::new();
             connected_peers.insert(peer_address, Utc::now());
@@ -205,12 +223,17 @@ mod server_listen {
                 .store_to_peer_book(bincode::serialize(&connected_peers).unwrap())
                 .unwrap();
 
+            let anchors: HashSet<SocketAddr> = connected_peers.keys().cloned().collect();
+            storage
+                .store_anchor_peers(bincode::serialize(&anchors).unwrap())
+                .unwrap();
+
             // 3. Start server
 
             let (tx, rx) = oneshot::channel();
 
             tokio::spawn(async move {
-                start_server(tx, server_address, peer_address, storage, parameters, false).await;
+                start_server(tx, server_address, bootnode_address, storage, parameters, false).await;
                 sleep(5000).await;
             });
 
@@ -225,7 +248,15 @@ mod server_listen {
 
             let mut peer_handshakes = Handshakes::new();
             peer_handshakes
-                .receive_any(1u64, 1u32, server_address, reader)
+                .receive_any(
+                    &identity,
+                    &Transport::Tcp,
+                    &Encryption::default(),
+                    1u32,
+                    server_address,
+                    reader.into(),
+                    true,
+                )
                 .await
                 .unwrap();
         });
@@ -233,4 +264,68 @@ mod server_listen {
         drop(rt);
         kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
     }
+
+    #[test]
+    #[serial]
+    fn shutdown_stores_state() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        let mut rt = Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let server_address = random_socket_address();
+            let bootnode_address = random_socket_address();
+            let peer_address = random_socket_address();
+
+            // 1. Start server
+
+            let server = initialize_test_server(
+                server_address,
+                bootnode_address,
+                storage.clone(),
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+
+            {
+                let mut peer_book = server.context.peer_book.write().await;
+                peer_book.update_connected(peer_address, Utc::now());
+                peer_book.set_outbound(peer_address, Utc::now());
+            }
+
+            let (tx, rx) = oneshot::channel::<()>();
+
+            let server_handle = tokio::spawn(async move {
+                server
+                    .listen_until(async move {
+                        rx.await.unwrap();
+                    })
+                    .await
+                    .unwrap();
+            });
+
+            sleep(100).await;
+
+            // 2. Shut down the server
+
+            tx.send(()).unwrap();
+            server_handle.await.unwrap();
+
+            // 3. Check that the peer book and memory pool were stored
+
+            let stored_peers =
+                bincode::deserialize::<HashMap<SocketAddr, DateTime<Utc>>>(&storage.get_peer_book().unwrap()).unwrap();
+            assert!(stored_peers.contains_key(&peer_address));
+
+            let stored_anchors =
+                bincode::deserialize::<HashSet<SocketAddr>>(&storage.get_anchor_peers().unwrap()).unwrap();
+            assert!(stored_anchors.contains(&peer_address));
+            assert!(storage.get_memory_pool().is_ok());
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
 }
@@ -154,6 +154,493 @@ mod server_message_handler {
         kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
     }
 
+    #[test]
+    #[serial]
+    fn receive_compact_block() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Send block 1 as a compact block without prefilled transactions to server from peer
+
+            let block = DATA.block_1.clone();
+            let block_hash = block.header.get_hash();
+            let short_ids = block
+                .transactions
+                .to_transaction_ids()
+                .unwrap()
+                .iter()
+                .map(|transaction_id| short_transaction_id(&block_hash, transaction_id))
+                .collect();
+            let num_transactions = block.transactions.len() as u32;
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        CompactBlock::name(),
+                        CompactBlock::new(block.header, short_ids, vec![]).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 3. Check that server requested all the transactions missing from its empty memory pool
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+            let (name, bytes) = channel.read().await.unwrap();
+
+            assert_eq!(GetBlockTxn::name(), name);
+            assert_eq!(
+                GetBlockTxn::new(block_hash, (0..num_transactions).collect())
+                    .serialize()
+                    .unwrap(),
+                bytes
+            );
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
+    #[test]
+    #[serial]
+    fn receive_compact_block_from_memory_pool() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        let storage_ref = storage.clone();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            simulate_active_node(remote_address).await;
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Insert the transactions of block 1 into server memory pool
+
+            let block = DATA.block_1.clone();
+            let mut memory_pool = server.memory_pool_lock.lock().await;
+
+            for transaction in block.transactions.iter() {
+                let entry = Entry {
+                    size: to_bytes![transaction].unwrap().len(),
+                    transaction: transaction.clone(),
+                };
+                assert!(memory_pool.insert(&server.storage, entry).is_ok());
+            }
+
+            drop(memory_pool);
+
+            // 2. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 3. Send block 1 as a compact block without prefilled transactions to server from peer
+
+            let block_hash = block.header.get_hash();
+            let short_ids = block
+                .transactions
+                .to_transaction_ids()
+                .unwrap()
+                .iter()
+                .map(|transaction_id| short_transaction_id(&block_hash, transaction_id))
+                .collect();
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        CompactBlock::name(),
+                        CompactBlock::new(block.header, short_ids, vec![]).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 4. Check that server rebuilt the block from its memory pool and inserted it into storage
+
+            assert!(storage_ref.block_hash_exists(&block_hash));
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
+    #[test]
+    #[serial]
+    fn receive_block_txn() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        let storage_ref = storage.clone();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+            let other_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+            simulate_active_node(other_address).await;
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Send block 1 as a compact block without prefilled transactions to server from peer
+
+            let block = DATA.block_1.clone();
+            let block_hash = block.header.get_hash();
+            let short_ids = block
+                .transactions
+                .to_transaction_ids()
+                .unwrap()
+                .iter()
+                .map(|transaction_id| short_transaction_id(&block_hash, transaction_id))
+                .collect();
+            let transactions: Vec<Vec<u8>> = block
+                .transactions
+                .iter()
+                .map(|transaction| to_bytes![transaction].unwrap())
+                .collect();
+
+            let (tx, rx) = oneshot::channel();
+            let mut sender = server_sender.clone();
+            let header = block.header.clone();
+            tokio::spawn(async move {
+                sender
+                    .send((
+                        tx,
+                        CompactBlock::name(),
+                        CompactBlock::new(header, short_ids, vec![]).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 3. Check that server requested the missing transactions from peer
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+            let (name, _) = channel.read().await.unwrap();
+            assert_eq!(GetBlockTxn::name(), name);
+
+            // 4. Send the missing transactions to server from another peer
+
+            let (tx, rx) = oneshot::channel();
+            let mut sender = server_sender.clone();
+            let block_txn = BlockTxn::new(block_hash.clone(), transactions.clone());
+            tokio::spawn(async move {
+                sender
+                    .send((
+                        tx,
+                        BlockTxn::name(),
+                        block_txn.serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(other_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 5. Check that server ignored the transactions it did not request from the other peer
+
+            assert!(!storage_ref.block_hash_exists(&block_hash));
+
+            // 6. Send the missing transactions to server from peer
+
+            let (tx, rx) = oneshot::channel();
+            let block_txn = BlockTxn::new(block_hash.clone(), transactions);
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        BlockTxn::name(),
+                        block_txn.serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 7. Check that server rebuilt the block and inserted it into storage
+
+            assert!(storage_ref.block_hash_exists(&block_hash));
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
+    #[test]
+    #[serial]
+    fn receive_compact_block_merkle_mismatch() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        let storage_ref = storage.clone();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Send the header of block 1 as a compact block prefilled with a transaction of block 2
+
+            let block_hash = DATA.block_1.header.get_hash();
+            let prefilled = PrefilledTransaction {
+                index: 0,
+                bytes: TRANSACTION_2.to_vec(),
+            };
+            let compact_block = CompactBlock::new(DATA.block_1.header.clone(), vec![], vec![prefilled]);
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        CompactBlock::name(),
+                        compact_block.serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 3. Check that server requested the full block instead, as the transactions don't match the header
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+            let (name, bytes) = channel.read().await.unwrap();
+
+            assert_eq!(GetData::name(), name);
+            assert_eq!(
+                GetData::new(vec![InventoryItem::Block(block_hash.clone())])
+                    .serialize()
+                    .unwrap(),
+                bytes
+            );
+            assert!(!storage_ref.block_hash_exists(&block_hash));
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
+    #[test]
+    #[serial]
+    fn receive_get_data() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        let genesis_block = storage.get_block_from_block_number(0).unwrap();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Send GetData for the genesis block and an unknown transaction to server from peer
+
+            let genesis_item = InventoryItem::Block(BlockHeaderHash::new(GENESIS_BLOCK_HEADER_HASH.to_vec()));
+            let unknown_item = InventoryItem::Transaction(vec![1u8; 32]);
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        GetData::name(),
+                        GetData::new(vec![genesis_item, unknown_item]).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 3. Check that server correctly sent the Block and NotFound messages
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+
+            let (name, bytes) = channel.read().await.unwrap();
+            assert_eq!(Block::name(), name);
+            assert_eq!(Block::new(to_bytes![genesis_block].unwrap()).serialize().unwrap(), bytes);
+
+            let (name, bytes) = channel.read().await.unwrap();
+            assert_eq!(NotFound::name(), name);
+            assert_eq!(
+                NotFound::new(vec![InventoryItem::Transaction(vec![1u8; 32])])
+                    .serialize()
+                    .unwrap(),
+                bytes
+            );
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
+    #[test]
+    #[serial]
+    fn receive_inv() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender = server.sender.clone();
+
+            // 1. Start server
+
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Announce the stored genesis block and the missing block 1 to server from peer
+
+            let genesis_item = InventoryItem::Block(BlockHeaderHash::new(GENESIS_BLOCK_HEADER_HASH.to_vec()));
+            let block_1_item = InventoryItem::Block(BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec()));
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender
+                    .send((
+                        tx,
+                        Inv::name(),
+                        Inv::new(vec![genesis_item, block_1_item]).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap();
+            });
+            rx.await.unwrap();
+
+            // 3. Check that server only requested block 1
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+            let (name, bytes) = channel.read().await.unwrap();
+
+            assert_eq!(GetData::name(), name);
+            assert_eq!(
+                GetData::new(vec![InventoryItem::Block(BlockHeaderHash::new(
+                    BLOCK_1_HEADER_HASH.to_vec()
+                ))])
+                .serialize()
+                .unwrap(),
+                bytes
+            );
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
     #[test]
     #[serial]
     fn receive_sync_block() {
@@ -297,6 +784,84 @@ mod server_message_handler {
         kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
     }
 
+    #[test]
+    #[serial]
+    fn receive_get_headers() {
+        let mut rt = Runtime::new().unwrap();
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let path = storage.storage.db.path().to_owned();
+        let parameters = load_verifying_parameters();
+
+        rt.block_on(async move {
+            let bootnode_address = random_socket_address();
+            let local_address = random_socket_address();
+            let remote_address = random_socket_address();
+
+            let mut remote_listener = TcpListener::bind(remote_address).await.unwrap();
+
+            let server = initialize_test_server(
+                local_address,
+                bootnode_address,
+                storage,
+                parameters,
+                CONNECTION_FREQUENCY_LONG,
+            );
+            let mut server_sender_ref_1 = server.sender.clone();
+            let mut server_sender_ref_2 = server.sender.clone();
+
+            // 1. Start server
+
+            simulate_active_node(bootnode_address).await;
+            start_test_server(server);
+            sleep(WAIT_PERIOD).await; // Sleep to give testing server time to spin up on a new thread
+
+            // 2. Send Block 1 to server from bootnode
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender_ref_1
+                    .send((
+                        tx,
+                        Block::name(),
+                        Block::new(BLOCK_1.to_vec()).serialize().unwrap(),
+                        Arc::new(Channel::new_write_only(bootnode_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap()
+            });
+            rx.await.unwrap();
+
+            // 3. Send GetHeaders to server from peer
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(async move {
+                server_sender_ref_2
+                    .send((
+                        tx,
+                        GetHeaders::name(),
+                        GetHeaders::new(vec![BlockHeaderHash::new(GENESIS_BLOCK_HEADER_HASH.to_vec())])
+                            .serialize()
+                            .unwrap(),
+                        Arc::new(Channel::new_write_only(remote_address).await.unwrap()),
+                    ))
+                    .await
+                    .unwrap()
+            });
+            rx.await.unwrap();
+
+            // 4. Check that server correctly sent Headers message
+
+            let channel = accept_channel(&mut remote_listener, local_address).await;
+            let (name, bytes) = channel.read().await.unwrap();
+
+            assert_eq!(Headers::name(), name);
+            assert_eq!(Headers::new(vec![DATA.block_1.header.clone()]).serialize().unwrap(), bytes);
+        });
+
+        drop(rt);
+        kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+    }
+
     #[test]
     #[serial]
     fn receive_sync() {
@@ -0,0 +1,147 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod simulation {
+    use snarkos_dpc::base_dpc::instantiated::{CommitmentMerkleParameters, Tx};
+    use snarkos_network::external::LinkRules;
+    use snarkos_objects::Block;
+    use snarkos_testing::{consensus::*, network::*, storage::*};
+    use snarkos_utilities::bytes::FromBytes;
+
+    use serial_test::serial;
+    use std::{path::PathBuf, time::Duration};
+
+    const SEED: u64 = 1231275789;
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn kill_simulation_storage(paths: Vec<PathBuf>) {
+        for path in paths {
+            kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn converges_with_latency_and_dropped_messages() {
+        let mut rt = simulation_runtime();
+
+        let paths = rt.block_on(async move {
+            let mut simulation = Simulation::new(3, SEED);
+
+            // 1. Give the last node a longer chain
+
+            simulation.nodes[2].insert_block(&DATA.block_1);
+            simulation.nodes[2].insert_block(&DATA.block_2);
+
+            // 2. Connect the nodes over a reliable network
+
+            simulation.start();
+            assert!(simulation.run_until_connected(TIMEOUT).await);
+
+            // 3. Delay all messages and drop some of them
+
+            simulation
+                .network
+                .set_default_rules(LinkRules::new(Duration::from_millis(200), 0.05));
+
+            // 4. Check that all nodes synced the longer chain
+
+            assert!(simulation.run_until_converged(TIMEOUT).await);
+            assert_eq!(vec![2, 2, 2], simulation.heights());
+
+            simulation.storage_paths()
+        });
+
+        drop(rt);
+        kill_simulation_storage(paths);
+    }
+
+    #[test]
+    #[serial]
+    fn converges_after_partition_heals() {
+        let mut rt = simulation_runtime();
+
+        let paths = rt.block_on(async move {
+            let mut simulation = Simulation::new(3, SEED);
+
+            simulation.start();
+            assert!(simulation.run_until_connected(TIMEOUT).await);
+
+            // 1. Partition the last node from the others, and extend the chain of the first node
+
+            simulation.partition(&[&[0, 1], &[2]]);
+
+            simulation.nodes[0].insert_block(&DATA.block_1);
+            simulation.nodes[0].insert_block(&DATA.block_2);
+
+            // 2. Check that only the nodes in the same partition synced the chain
+
+            assert!(
+                simulation
+                    .run_until(|simulation| simulation.nodes[1].height() == 2, TIMEOUT)
+                    .await
+            );
+            simulation.run_for(Duration::from_secs(10)).await;
+            assert_eq!(vec![2, 2, 0], simulation.heights());
+
+            // 3. Heal the partition, and reconnect the last node
+
+            simulation.heal();
+            simulation.connect(2, 0).await;
+
+            // 4. Check that all nodes converged
+
+            assert!(simulation.run_until_converged(TIMEOUT).await);
+            assert_eq!(vec![2, 2, 2], simulation.heights());
+
+            simulation.storage_paths()
+        });
+
+        drop(rt);
+        kill_simulation_storage(paths);
+    }
+
+    #[test]
+    #[serial]
+    fn converges_to_longest_fork() {
+        let mut rt = simulation_runtime();
+
+        let paths = rt.block_on(async move {
+            let mut simulation = Simulation::new(2, SEED);
+
+            // 1. Give the nodes competing chains
+
+            let alternative_block_1 = Block::<Tx>::read(&ALTERNATIVE_BLOCK_1[..]).unwrap();
+
+            simulation.nodes[0].insert_block(&alternative_block_1);
+            simulation.nodes[1].insert_block(&DATA.block_1);
+            simulation.nodes[1].insert_block(&DATA.block_2);
+
+            // 2. Check that the first node reorganized to the longer chain
+
+            simulation.start();
+
+            assert!(simulation.run_until_converged(TIMEOUT).await);
+            assert_eq!(vec![2, 2], simulation.heights());
+            assert_eq!(DATA.block_2.header.get_hash(), simulation.nodes[0].latest_block_hash());
+
+            simulation.storage_paths()
+        });
+
+        drop(rt);
+        kill_simulation_storage(paths);
+    }
+}
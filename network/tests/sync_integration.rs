@@ -16,97 +16,141 @@
 
 mod sync_integration {
     use snarkos_dpc::base_dpc::instantiated::{CommitmentMerkleParameters, Tx};
-    use snarkos_network::external::{message::Message, message_types::*, protocol::sync::*, Channel};
+    use snarkos_network::external::{
+        message::Message,
+        message_types::*,
+        protocol::sync::*,
+        MemoryNetwork,
+        HEADERS_PROTOCOL_VERSION,
+    };
     use snarkos_objects::BlockHeaderHash;
     use snarkos_testing::{consensus::*, network::*, storage::*};
 
     use serial_test::serial;
     use std::sync::Arc;
-    use tokio::{net::TcpListener, sync::oneshot};
+
+    const SEED: u64 = 1231275789;
 
     mod increment_sync_handler {
         use super::*;
 
-        #[tokio::test]
+        #[test]
         #[serial]
-        async fn sends_get_block() {
+        fn sends_get_block() {
+            let mut rt = simulation_runtime();
             let storage = Arc::new(FIXTURE_VK.ledger());
             let path = storage.storage.db.path().to_owned();
-            let bootnode_address = random_socket_address();
 
-            let mut bootnode_listener = TcpListener::bind(bootnode_address).await.unwrap();
+            rt.block_on(run_simulated(async move {
+                let network = Arc::new(MemoryNetwork::new(SEED));
+                let local_address = Simulation::node_address(0);
+
+                let mut sync_node = SimulatedPeer::bind(network.clone(), Simulation::node_address(1));
+                let channel = sync_node.connect_from(local_address).await;
 
-            let block_hash = BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec());
+                let block_hash = BlockHeaderHash::new(BLOCK_1_HEADER_HASH.to_vec());
 
-            // 1. Push hash to sync handler, set syncing to true
+                // 1. Push hash to sync handler, set syncing to true
 
-            let mut sync_handler = SyncHandler::new(bootnode_address);
-            sync_handler.receive_hashes(vec![block_hash.clone()], 1);
+                let mut sync_handler = SyncHandler::new(sync_node.address);
+                sync_handler.receive_hashes(vec![block_hash.clone()], 1, network.now());
 
-            // 2. Call increment_sync_handler internally
+                // 2. Call increment_sync_handler internally
 
-            let (tx, rx) = oneshot::channel();
-            tokio::spawn(async move {
                 sync_handler
-                    .increment(
-                        Arc::new(Channel::new_write_only(bootnode_address).await.unwrap()),
-                        storage,
-                    )
+                    .increment(&[channel], storage, network.now())
                     .await
                     .unwrap();
 
-                tx.send(()).unwrap();
-            });
-            rx.await.unwrap();
+                // 3. Check that sync node received GetBlock message
+
+                let channel = sync_node.accept().await;
+                let (name, bytes) = channel.read().await.unwrap();
+
+                assert_eq!(GetBlock::name(), name);
+                assert_eq!(GetBlock::new(block_hash).serialize().unwrap(), bytes);
+            }));
+
+            drop(rt);
+            kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
+        }
+
+        #[test]
+        #[serial]
+        fn sends_get_sync() {
+            let mut rt = simulation_runtime();
+            let storage = Arc::new(FIXTURE_VK.ledger());
+            let path = storage.storage.db.path().to_owned();
 
-            // 3. Check that bootnode received GetBlock message
+            rt.block_on(run_simulated(async move {
+                let network = Arc::new(MemoryNetwork::new(SEED));
+                let local_address = Simulation::node_address(0);
 
-            let channel = accept_channel(&mut bootnode_listener, bootnode_address).await;
-            let (name, bytes) = channel.read().await.unwrap();
+                let mut sync_node = SimulatedPeer::bind(network.clone(), Simulation::node_address(1));
+                let channel = sync_node.connect_from(local_address).await;
 
-            assert_eq!(GetBlock::name(), name);
-            assert_eq!(GetBlock::new(block_hash).serialize().unwrap(), bytes);
+                // 1. Set syncing to true
 
+                let mut sync_handler = SyncHandler::new(sync_node.address);
+                sync_handler.update_syncing(0, network.now());
+
+                // 2. Call increment_sync_handler internally
+
+                sync_handler
+                    .increment(&[channel], storage, network.now())
+                    .await
+                    .unwrap();
+
+                // 3. Check that sync node received GetSync message
+
+                let channel = sync_node.accept().await;
+                let (name, bytes) = channel.read().await.unwrap();
+
+                assert_eq!(GetSync::name(), name);
+                assert_eq!(GetSync::new(vec![]).serialize().unwrap(), bytes);
+            }));
+
+            drop(rt);
             kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
         }
 
-        #[tokio::test]
+        #[test]
         #[serial]
-        async fn sends_get_sync() {
+        fn sends_get_headers() {
+            let mut rt = simulation_runtime();
             let storage = Arc::new(FIXTURE_VK.ledger());
             let path = storage.storage.db.path().to_owned();
 
-            let bootnode_address = random_socket_address();
+            rt.block_on(run_simulated(async move {
+                let network = Arc::new(MemoryNetwork::new(SEED));
+                let local_address = Simulation::node_address(0);
+
+                let mut sync_node = SimulatedPeer::bind(network.clone(), Simulation::node_address(1));
+                let channel = sync_node.connect_from(local_address).await;
 
-            let mut bootnode_listener = TcpListener::bind(bootnode_address).await.unwrap();
+                // 1. Set syncing to true with a sync node supporting headers-first sync
 
-            // 1. Set syncing to true
+                let mut sync_handler = SyncHandler::new(sync_node.address);
+                sync_handler.update_peer_version(sync_node.address, HEADERS_PROTOCOL_VERSION);
+                sync_handler.update_syncing(0, network.now());
 
-            let mut sync_handler = SyncHandler::new(bootnode_address);
-            sync_handler.update_syncing(0);
+                // 2. Call increment_sync_handler internally
 
-            // 2. Call increment_sync_handler_internally
-            let (tx, rx) = oneshot::channel();
-            tokio::spawn(async move {
                 sync_handler
-                    .increment(
-                        Arc::new(Channel::new_write_only(bootnode_address).await.unwrap()),
-                        storage,
-                    )
+                    .increment(&[channel], storage, network.now())
                     .await
                     .unwrap();
-                tx.send(()).unwrap();
-            });
-            rx.await.unwrap();
 
-            // 3. Check that bootnode received GetSync message
+                // 3. Check that sync node received GetHeaders message
 
-            let channel = accept_channel(&mut bootnode_listener, bootnode_address).await;
-            let (name, bytes) = channel.read().await.unwrap();
+                let channel = sync_node.accept().await;
+                let (name, bytes) = channel.read().await.unwrap();
 
-            assert_eq!(GetSync::name(), name);
-            assert_eq!(GetSync::new(vec![]).serialize().unwrap(), bytes);
+                assert_eq!(GetHeaders::name(), name);
+                assert_eq!(GetHeaders::new(vec![]).serialize().unwrap(), bytes);
+            }));
 
+            drop(rt);
             kill_storage_async::<Tx, CommitmentMerkleParameters>(path);
         }
     }
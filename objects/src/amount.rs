@@ -123,6 +123,12 @@ impl FromBytes for AleoAmount {
     }
 }
 
+impl From<AleoAmount> for i64 {
+    fn from(amount: AleoAmount) -> Self {
+        amount.0
+    }
+}
+
 impl fmt::Display for AleoAmount {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0.to_string())
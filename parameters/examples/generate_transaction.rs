@@ -15,7 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use snarkos_algorithms::merkle_tree::MerkleTree;
-use snarkos_consensus::{ConsensusParameters, MerkleTreeLedger};
+use snarkos_consensus::{ChainNotifier, ConsensusParameters, MerkleTreeLedger};
 use snarkos_dpc::base_dpc::{instantiated::*, record_payload::RecordPayload, BaseDPCComponents, DPC};
 use snarkos_errors::dpc::{DPCError, LedgerError};
 use snarkos_models::{
@@ -75,9 +75,11 @@ pub fn generate(recipient: &String, value: u64, network_id: u8, file_name: &Stri
         max_block_size: 1_000_000_000usize,
         max_nonce: u32::max_value(),
         target_block_time: 10i64,
+        fixed_difficulty_target: None,
         network: Network::from_network_id(network_id),
         verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
         authorized_inner_snark_ids: vec![],
+        notifier: ChainNotifier::default(),
     };
 
     let recipient = AccountAddress::<Components>::from_str(&recipient)?;
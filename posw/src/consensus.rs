@@ -45,7 +45,10 @@ use snarkos_utilities::{
 
 use blake2::{digest::Digest, Blake2s};
 use rand::{rngs::OsRng, Rng};
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 // We need to instantiate the Merkle Tree and the Gadget, but these should not be
 // proving system specific
@@ -201,6 +204,19 @@ where
         difficulty_target: u64, // TODO: Change to Bignum?
         rng: &mut R,
         max_nonce: u32,
+    ) -> Result<(u32, Vec<u8>), PoswError> {
+        self.mine_until_cancelled(subroots, difficulty_target, rng, max_nonce, &AtomicBool::new(false))
+    }
+
+    /// Same as `mine`, but stops with `PoswError::MiningCancelled` once `cancelled` is set.
+    /// The flag is checked before every nonce attempt.
+    pub fn mine_until_cancelled<R: Rng>(
+        &self,
+        subroots: &[Vec<u8>],
+        difficulty_target: u64,
+        rng: &mut R,
+        max_nonce: u32,
+        cancelled: &AtomicBool,
     ) -> Result<(u32, Vec<u8>), PoswError> {
         let pk = self.pk.as_ref().expect("tried to mine without a PK set up");
 
@@ -208,6 +224,10 @@ where
         let mut proof;
         let mut serialized_proof;
         loop {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(PoswError::MiningCancelled);
+            }
+
             nonce = rng.gen_range(0, max_nonce);
             proof = Self::prove(&pk, nonce, subroots, rng)?;
 
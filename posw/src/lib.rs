@@ -110,6 +110,20 @@ mod tests {
         posw.verify(nonce, &proof, &pedersen_merkle_root).unwrap();
     }
 
+    #[test]
+    fn mining_cancelled() {
+        let posw = PoswMarlin::load().unwrap();
+
+        let transaction_ids = vec![vec![1u8; 32]; 8];
+        let (_, _, subroots) = txids_to_roots(&transaction_ids);
+
+        // an already set flag stops the miner before any proof is generated
+        let cancelled = std::sync::atomic::AtomicBool::new(true);
+        let result = posw.mine_until_cancelled(&subroots, 0, &mut rand::thread_rng(), std::u32::MAX, &cancelled);
+
+        assert!(matches!(result, Err(snarkos_errors::posw::PoswError::MiningCancelled)));
+    }
+
     #[test]
     fn marlin_ok() {
         let rng = &mut XorShiftRng::seed_from_u64(1234567);
@@ -30,6 +30,10 @@ pub mod rpc_impl_protected;
 #[doc(inline)]
 pub use rpc_impl_protected::*;
 
+pub mod rpc_pubsub;
+#[doc(inline)]
+pub use rpc_pubsub::*;
+
 pub mod rpc_server;
 #[doc(inline)]
 pub use rpc_server::*;
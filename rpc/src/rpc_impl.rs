@@ -19,7 +19,13 @@
 //! See [RpcFunctions](../trait.RpcFunctions.html) for documentation of public endpoints.
 
 use crate::{rpc_trait::RpcFunctions, rpc_types::*};
-use snarkos_consensus::{get_block_reward, ConsensusParameters, MemoryPool, MerkleTreeLedger};
+use snarkos_consensus::{
+    get_block_reward,
+    memory_pool::{Admission, Entry},
+    ConsensusParameters,
+    MemoryPool,
+    MerkleTreeLedger,
+};
 use snarkos_dpc::base_dpc::{
     instantiated::{Components, Tx},
     parameters::PublicParameters,
@@ -27,10 +33,10 @@ use snarkos_dpc::base_dpc::{
 use snarkos_errors::rpc::RpcError;
 use snarkos_models::objects::Transaction;
 use snarkos_network::{
-    external::SyncHandler,
+    external::{propagate_block, SyncHandler},
     internal::{context::Context, process_transaction_internal},
 };
-use snarkos_objects::BlockHeaderHash;
+use snarkos_objects::{Block, BlockHeaderHash};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
     to_bytes,
@@ -48,6 +54,9 @@ pub struct RpcImpl {
     /// Blockchain database storage.
     pub(crate) storage: Arc<MerkleTreeLedger>,
 
+    /// Primary blockchain database storage, used to insert submitted blocks.
+    pub(crate) primary_storage: Arc<MerkleTreeLedger>,
+
     /// The path to the Blockchain database storage.
     pub(crate) storage_path: PathBuf,
 
@@ -74,6 +83,7 @@ impl RpcImpl {
     /// Creates a new struct for calling public and private RPC endpoints.
     pub fn new(
         storage: Arc<MerkleTreeLedger>,
+        primary_storage: Arc<MerkleTreeLedger>,
         storage_path: PathBuf,
         parameters: PublicParameters<Components>,
         server_context: Arc<Context>,
@@ -84,6 +94,7 @@ impl RpcImpl {
     ) -> Self {
         Self {
             storage,
+            primary_storage,
             storage_path,
             parameters,
             server_context,
@@ -98,6 +109,17 @@ impl RpcImpl {
     pub fn new_secondary_storage_instance(&self) -> Result<MerkleTreeLedger, RpcError> {
         Ok(MerkleTreeLedger::open_secondary_at_path(self.storage_path.clone())?)
     }
+
+    /// Returns the details of a memory pool entry and its arrival time.
+    fn memory_pool_entry(&self, entry: &Entry<Tx>, time: i64) -> Result<MemoryPoolEntry, RpcError> {
+        let transaction = self.decode_raw_transaction(hex::encode(to_bytes![entry.transaction]?))?;
+
+        Ok(MemoryPoolEntry {
+            size: entry.size,
+            time,
+            transaction,
+        })
+    }
 }
 
 impl RpcFunctions for RpcImpl {
@@ -140,6 +162,7 @@ impl RpcFunctions for RpcImpl {
                 proof: block.header.proof.to_string(),
                 time: block.header.time,
                 difficulty_target: block.header.difficulty_target,
+                total_work: self.storage.get_cumulative_work(&block_header_hash)?.to_string(),
                 nonce: block.header.nonce,
                 transactions,
             })
@@ -248,36 +271,53 @@ impl RpcFunctions for RpcImpl {
 
     /// Send raw transaction bytes to this node to be added into the mempool.
     /// If valid, the transaction will be stored and propagated to all peers.
-    /// Returns the transaction id if valid.
-    fn send_raw_transaction(&self, transaction_bytes: String) -> Result<String, RpcError> {
+    /// Returns the transaction id and the ids of the transactions it replaced, or the reason it was rejected.
+    fn send_raw_transaction(&self, transaction_bytes: String) -> Result<SendTransactionResult, RpcError> {
         let transaction_bytes = hex::decode(transaction_bytes)?;
         let transaction = Tx::read(&transaction_bytes[..])?;
+        let transaction_id = hex::encode(transaction.transaction_id()?);
         self.storage.catch_up_secondary(false)?;
 
+        let rejected = |reject_reason: String| SendTransactionResult {
+            transaction_id: transaction_id.clone(),
+            accepted: false,
+            replaced: vec![],
+            reject_reason: Some(reject_reason),
+        };
+
         if !self
             .consensus
             .verify_transaction(&self.parameters, &transaction, &self.storage)?
         {
-            // TODO (raychu86) Add more descriptive message. (e.g. tx already exists)
-            return Ok("Transaction did not verify".into());
+            return Ok(rejected("Transaction did not verify".into()));
         }
 
-        match !self.storage.transcation_conflicts(&transaction) {
-            true => {
-                Runtime::new()?.block_on(process_transaction_internal(
-                    self.server_context.clone(),
-                    &self.consensus,
-                    &self.parameters,
-                    self.storage.clone(),
-                    self.memory_pool_lock.clone(),
-                    to_bytes![transaction]?.to_vec(),
-                    *Runtime::new()?.block_on(self.server_context.local_address.read()),
-                ))?;
-
-                Ok(hex::encode(transaction.transaction_id()?))
-            }
-            false => Ok("Transaction contains spent records".into()),
+        if self.storage.transcation_conflicts(&transaction) {
+            return Ok(rejected("Transaction contains spent records".into()));
         }
+
+        let admission = Runtime::new()?.block_on(process_transaction_internal(
+            self.server_context.clone(),
+            &self.consensus,
+            &self.parameters,
+            self.storage.clone(),
+            self.memory_pool_lock.clone(),
+            to_bytes![transaction]?.to_vec(),
+            *Runtime::new()?.block_on(self.server_context.local_address.read()),
+        ))?;
+
+        let replaced = match admission {
+            Admission::Accepted(_) => vec![],
+            Admission::Replaced { replaced, .. } => replaced,
+            Admission::Rejected(rejection) => return Ok(rejected(format!("Transaction was rejected: {}", rejection))),
+        };
+
+        Ok(SendTransactionResult {
+            transaction_id,
+            accepted: true,
+            replaced: replaced.iter().map(hex::encode).collect(),
+            reject_reason: None,
+        })
     }
 
     /// Validate and return if the transaction is valid.
@@ -355,4 +395,122 @@ impl RpcFunctions for RpcImpl {
             coinbase_value: coinbase_value.0 as u64,
         })
     }
+
+    /// Returns the number of transactions and the total size of the memory pool.
+    fn get_memory_pool_info(&self) -> Result<MemoryPoolInfo, RpcError> {
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        Ok(MemoryPoolInfo {
+            size: memory_pool.transactions.len(),
+            bytes: memory_pool.total_size,
+            max_bytes: memory_pool.max_size,
+            min_relay_fee: memory_pool.min_relay_fee,
+        })
+    }
+
+    /// Returns the transaction ids in the memory pool, by descending fee rate.
+    /// If verbose, returns the details of every memory pool entry instead.
+    fn get_raw_memory_pool(&self, verbose: Option<bool>) -> Result<RawMemoryPool, RpcError> {
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        if !verbose.unwrap_or(false) {
+            let transaction_ids = memory_pool
+                .fee_rates
+                .iter()
+                .rev()
+                .map(|(_fee_rate, transaction_id)| hex::encode(transaction_id))
+                .collect();
+
+            return Ok(RawMemoryPool::TransactionIds(transaction_ids));
+        }
+
+        let mut entries = vec![];
+
+        for (_fee_rate, transaction_id) in memory_pool.fee_rates.iter().rev() {
+            let entry = &memory_pool.transactions[transaction_id];
+            let time = memory_pool
+                .arrival_times
+                .get(transaction_id)
+                .copied()
+                .unwrap_or_default();
+            entries.push(self.memory_pool_entry(entry, time)?);
+        }
+
+        Ok(RawMemoryPool::Entries(entries))
+    }
+
+    /// Returns the details of a transaction in the memory pool from its transaction id.
+    fn get_memory_pool_entry(&self, transaction_id: String) -> Result<MemoryPoolEntry, RpcError> {
+        let transaction_id = hex::decode(transaction_id)?;
+        let memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        match memory_pool.transactions.get(&transaction_id) {
+            Some(entry) => {
+                let time = memory_pool
+                    .arrival_times
+                    .get(&transaction_id)
+                    .copied()
+                    .unwrap_or_default();
+                self.memory_pool_entry(entry, time)
+            }
+            None => Err(RpcError::Message("Transaction is not in the memory pool".into())),
+        }
+    }
+
+    /// Submit a solved block to the node.
+    /// If accepted, the block will be stored and propagated to all peers.
+    /// Returns the acceptance status of the block, or the reason it was rejected.
+    fn submit_block(&self, block_bytes: String) -> Result<SubmitBlockResult, RpcError> {
+        let block_bytes = hex::decode(block_bytes)?;
+        let block = Block::<Tx>::deserialize(&block_bytes)?;
+        let block_hash = block.header.get_hash();
+
+        let rejected = |reject_reason: String| SubmitBlockResult {
+            hash: hex::encode(&block_hash.0),
+            accepted: false,
+            is_canon: false,
+            reject_reason: Some(reject_reason),
+        };
+
+        if self.primary_storage.block_hash_exists(&block_hash) {
+            return Ok(rejected("Block already exists".into()));
+        }
+
+        // Create a temporary tokio runtime to make an asynchronous function call
+        let mut runtime = Runtime::new()?;
+
+        {
+            let mut memory_pool = runtime.block_on(self.memory_pool_lock.lock());
+
+            if let Err(error) =
+                self.consensus
+                    .receive_block(&self.parameters, &self.primary_storage, &mut memory_pool, &block)
+            {
+                return Ok(rejected(error.to_string()));
+            }
+        }
+
+        let local_address = *runtime.block_on(self.server_context.local_address.read());
+        runtime.block_on(propagate_block(
+            self.server_context.clone(),
+            block_hash.clone(),
+            block_bytes,
+            local_address,
+        ))?;
+
+        // A block that is stored as an orphan or on a side chain is not accepted into the canon chain.
+        if !self.primary_storage.is_canon(&block_hash) {
+            return Ok(match self.primary_storage.previous_block_hash_exists(&block) {
+                true => rejected("Block was stored on a side chain".into()),
+                false => rejected("Block was stored as an orphan".into()),
+            });
+        }
+
+        Ok(SubmitBlockResult {
+            hash: hex::encode(&block_hash.0),
+            accepted: true,
+            is_canon: true,
+            reject_reason: None,
+        })
+    }
 }
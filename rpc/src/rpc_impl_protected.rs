@@ -19,6 +19,7 @@
 //! See [ProtectedRpcFunctions](../trait.ProtectedRpcFunctions.html) for documentation of private endpoints.
 
 use crate::{rpc_trait::ProtectedRpcFunctions, rpc_types::*, RpcImpl};
+use snarkos_consensus::Wallet;
 use snarkos_dpc::base_dpc::{
     encrypted_record::EncryptedRecord,
     instantiated::{Components, InstantiatedDPC},
@@ -26,12 +27,13 @@ use snarkos_dpc::base_dpc::{
     record_encryption::RecordEncryption,
     record_payload::RecordPayload,
 };
-use snarkos_errors::rpc::RpcError;
+use snarkos_errors::{network::ServerError, rpc::RpcError};
 use snarkos_models::{
     algorithms::CRH,
     dpc::{DPCComponents, Record},
     objects::AccountScheme,
 };
+use snarkos_network::internal::{ban_subnet_internal, disconnect_peer_internal, IpSubnet};
 use snarkos_objects::{Account, AccountAddress, AccountPrivateKey, AccountViewKey};
 use snarkos_utilities::{
     bytes::{FromBytes, ToBytes},
@@ -39,9 +41,16 @@ use snarkos_utilities::{
 };
 
 use base64;
+use chrono::{DateTime, Duration, Utc};
 use jsonrpc_http_server::jsonrpc_core::{IoDelegate, MetaIoHandler, Params, Value};
 use rand::{thread_rng, Rng};
-use std::{str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+};
+use tokio::runtime::Runtime;
 
 type JsonRPCError = jsonrpc_core::Error;
 
@@ -175,6 +184,81 @@ impl RpcImpl {
         }
     }
 
+    /// Wrap authentication around `get_wallet_balance`
+    pub fn get_wallet_balance_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.get_wallet_balance(address) {
+            Ok(balance) => Ok(Value::from(balance)),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `get_unspent_records`
+    pub fn get_unspent_records_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.get_unspent_records(address) {
+            Ok(records) => Ok(serde_json::to_value(records).expect("unspent records serialization failed")),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `remove_memory_pool_entry`
+    pub fn remove_memory_pool_entry_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let transaction_id: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.remove_memory_pool_entry(transaction_id) {
+            Ok(removed) => Ok(Value::from(removed)),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
     /// Wrap authentication around `create_account`
     pub fn create_account_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
         self.validate_auth(meta)?;
@@ -187,6 +271,133 @@ impl RpcImpl {
         }
     }
 
+    /// Wrap authentication around `add_peer`
+    pub fn add_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.add_peer(address) {
+            Ok(requested) => Ok(Value::from(requested)),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `disconnect_peer`
+    pub fn disconnect_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let address: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.disconnect_peer(address) {
+            Ok(disconnected) => Ok(Value::from(disconnected)),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `ban_peer`
+    pub fn ban_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 2 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 2 elements",
+                value.len()
+            )));
+        }
+
+        let subnet: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        let duration_secs: u64 = serde_json::from_value(value[1].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.ban_peer(subnet, duration_secs) {
+            Ok(banned_peer) => Ok(serde_json::to_value(banned_peer).expect("banned peer serialization failed")),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `unban_peer`
+    pub fn unban_peer_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        let value = match params {
+            Params::Array(arr) => arr,
+            _ => return Err(JsonRPCError::invalid_request()),
+        };
+
+        if value.len() != 1 {
+            return Err(JsonRPCError::invalid_params(format!(
+                "invalid length {}, expected 1 element",
+                value.len()
+            )));
+        }
+
+        let subnet: String = serde_json::from_value(value[0].clone())
+            .map_err(|e| JsonRPCError::invalid_params(format!("Invalid params: {}.", e)))?;
+
+        match self.unban_peer(subnet) {
+            Ok(unbanned) => Ok(Value::from(unbanned)),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `list_peers`
+    pub fn list_peers_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        params.expect_no_params()?;
+
+        match self.list_peers() {
+            Ok(peers) => Ok(serde_json::to_value(peers).expect("peer list serialization failed")),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
+    /// Wrap authentication around `get_network_totals`
+    pub fn get_network_totals_protected(&self, params: Params, meta: Meta) -> Result<Value, JsonRPCError> {
+        self.validate_auth(meta)?;
+
+        params.expect_no_params()?;
+
+        match self.get_network_totals() {
+            Ok(totals) => Ok(serde_json::to_value(totals).expect("network totals serialization failed")),
+            Err(err) => Err(JsonRPCError::invalid_params(err.to_string())),
+        }
+    }
+
     /// Expose the protected functions as RPC enpoints
     pub fn add_protected(&self, io: &mut MetaIoHandler<Meta>) {
         let mut d = IoDelegate::<Self, Meta>::new(Arc::new(self.clone()));
@@ -198,6 +409,15 @@ impl RpcImpl {
         d.add_method_with_meta("getrecordcommitments", Self::get_record_commitments_protected);
         d.add_method_with_meta("getrawrecord", Self::get_raw_record_protected);
         d.add_method_with_meta("createaccount", Self::create_account_protected);
+        d.add_method_with_meta("getwalletbalance", Self::get_wallet_balance_protected);
+        d.add_method_with_meta("getunspentrecords", Self::get_unspent_records_protected);
+        d.add_method_with_meta("removemempoolentry", Self::remove_memory_pool_entry_protected);
+        d.add_method_with_meta("addpeer", Self::add_peer_protected);
+        d.add_method_with_meta("disconnectpeer", Self::disconnect_peer_protected);
+        d.add_method_with_meta("banpeer", Self::ban_peer_protected);
+        d.add_method_with_meta("unbanpeer", Self::unban_peer_protected);
+        d.add_method_with_meta("listpeers", Self::list_peers_protected);
+        d.add_method_with_meta("getnettotals", Self::get_network_totals_protected);
 
         io.extend_with(d)
     }
@@ -450,4 +670,150 @@ impl ProtectedRpcFunctions for RpcImpl {
             commitment_randomness,
         })
     }
+
+    /// Returns the balance of the unspent records of an account tracked by the node wallet.
+    fn get_wallet_balance(&self, address: String) -> Result<u64, RpcError> {
+        let unspent_records = self.get_unspent_records(address)?;
+
+        Ok(unspent_records.iter().map(|record| record.value).sum())
+    }
+
+    /// Returns the unspent records of an account tracked by the node wallet.
+    fn get_unspent_records(&self, address: String) -> Result<Vec<UnspentRecord>, RpcError> {
+        self.storage.catch_up_secondary(false)?;
+        let address = AccountAddress::<Components>::from_str(&address)?;
+
+        let mut unspent_records = vec![];
+        for record in Wallet::get_unspent_records(&self.storage, &address)? {
+            unspent_records.push(UnspentRecord {
+                commitment: hex::encode(to_bytes![record.commitment()]?),
+                value: record.value(),
+                record: hex::encode(to_bytes![record]?),
+            });
+        }
+
+        Ok(unspent_records)
+    }
+
+    /// Evict a transaction from the memory pool.
+    /// Returns true if the transaction was in the memory pool.
+    fn remove_memory_pool_entry(&self, transaction_id: String) -> Result<bool, RpcError> {
+        let transaction_id = hex::decode(transaction_id)?;
+        let mut memory_pool = Runtime::new()?.block_on(self.memory_pool_lock.lock());
+
+        Ok(memory_pool.remove_by_hash(&transaction_id)?.is_some())
+    }
+
+    /// Request a connection to a peer.
+    /// Returns false if the peer is already connected or a connection to it was already requested.
+    fn add_peer(&self, address: String) -> Result<bool, RpcError> {
+        let address: SocketAddr = address.parse()?;
+        let mut peer_book = Runtime::new()?.block_on(self.server_context.peer_book.write());
+
+        if peer_book.is_banned(&address.ip()) {
+            return Err(ServerError::PeerBanned(address).into());
+        }
+
+        if peer_book.connected_contains(&address) {
+            return Ok(false);
+        }
+
+        Ok(peer_book.request_connection(address))
+    }
+
+    /// Disconnect from a connected peer.
+    /// Returns true if the peer was connected.
+    fn disconnect_peer(&self, address: String) -> Result<bool, RpcError> {
+        let address: SocketAddr = address.parse()?;
+
+        Ok(Runtime::new()?.block_on(disconnect_peer_internal(self.server_context.clone(), address)))
+    }
+
+    /// Ban a subnet for the given number of seconds and disconnect from its peers.
+    fn ban_peer(&self, subnet: String, duration_secs: u64) -> Result<BannedPeer, RpcError> {
+        let subnet = IpSubnet::from_str(&subnet)?;
+        let duration = Duration::from_std(std::time::Duration::from_secs(duration_secs))
+            .map_err(|_| RpcError::Message(format!("invalid ban duration {}", duration_secs)))?;
+
+        let banned_until = Runtime::new()?.block_on(ban_subnet_internal(self.server_context.clone(), subnet, duration));
+
+        Ok(BannedPeer {
+            subnet: subnet.to_string(),
+            banned_until: banned_until.timestamp(),
+        })
+    }
+
+    /// Lift the ban of a subnet.
+    /// Returns true if the subnet was banned.
+    fn unban_peer(&self, subnet: String) -> Result<bool, RpcError> {
+        let subnet = IpSubnet::from_str(&subnet)?;
+        let mut peer_book = Runtime::new()?.block_on(self.server_context.peer_book.write());
+
+        Ok(peer_book.unban(&subnet))
+    }
+
+    /// Returns the connected, disconnected, gossiped and banned peers of this node.
+    fn list_peers(&self) -> Result<PeerList, RpcError> {
+        // Create a temporary tokio runtime to make an asynchronous function call
+        let mut runtime = Runtime::new()?;
+        let peer_book = runtime.block_on(self.server_context.peer_book.read());
+        let connections = runtime.block_on(self.server_context.connections.read());
+
+        let peer_details = |addresses: HashMap<SocketAddr, DateTime<Utc>>| {
+            let mut peers = vec![];
+
+            for (address, last_seen) in addresses {
+                let peer_info = peer_book.get_peer_info(&address);
+                let (bytes_sent, bytes_received) = match connections.get(&address) {
+                    Some(channel) => (
+                        channel.bytes_sent.load(Ordering::Relaxed),
+                        channel.bytes_received.load(Ordering::Relaxed),
+                    ),
+                    None => (0, 0),
+                };
+
+                peers.push(PeerDetails {
+                    address,
+                    last_seen: last_seen.timestamp(),
+                    version: peer_info.version,
+                    height: peer_info.height,
+                    user_agent: peer_info.user_agent,
+                    services: peer_info
+                        .services
+                        .map(|services| services.names().iter().map(|name| name.to_string()).collect()),
+                    bytes_sent,
+                    bytes_received,
+                });
+            }
+
+            peers
+        };
+
+        let mut banned = vec![];
+        for (subnet, banned_until) in peer_book.get_banned() {
+            banned.push(BannedPeer {
+                subnet: subnet.to_string(),
+                banned_until: banned_until.timestamp(),
+            });
+        }
+
+        Ok(PeerList {
+            connected: peer_details(peer_book.get_connected()),
+            disconnected: peer_details(peer_book.get_disconnected()),
+            gossiped: peer_details(peer_book.get_gossiped()),
+            banned,
+        })
+    }
+
+    /// Returns the number of bytes sent to and received from all peers since the node started.
+    fn get_network_totals(&self) -> Result<NetworkTotals, RpcError> {
+        let traffic = self.server_context.bandwidth.traffic();
+
+        Ok(NetworkTotals {
+            bytes_sent: traffic.total_sent(),
+            bytes_received: traffic.total_received(),
+            bytes_sent_per_message: traffic.sent,
+            bytes_received_per_message: traffic.received,
+        })
+    }
 }
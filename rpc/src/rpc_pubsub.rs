@@ -0,0 +1,209 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the RPC subscriptions to chain events.
+//!
+//! Subscriptions are served over WebSocket and notify the subscribers of new canon blocks (`subscribe_newBlocks`),
+//! transactions admitted to the memory pool (`subscribe_newTransactions`) and chain reorganizations
+//! (`subscribe_chainReorg`).
+
+use crate::rpc_types::*;
+use snarkos_consensus::{ChainEvent, ChainNotifier};
+
+use jsonrpc_core::{
+    futures::executor::{self, Notify},
+    MetaIoHandler,
+    Params,
+    Value,
+};
+use jsonrpc_pubsub::{PubSubHandler, Session, Sink, Subscriber, SubscriptionId};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+};
+use tokio::sync::broadcast::RecvError;
+
+type Subscriptions = Arc<Mutex<HashMap<SubscriptionId, Arc<Mutex<Sink>>>>>;
+
+/// Ignores the wakeups of the subscription sinks, as a notification that a subscriber
+/// has no room for is dropped rather than retried.
+struct NoopNotify;
+
+impl Notify for NoopNotify {
+    fn notify(&self, _id: usize) {}
+}
+
+static NOOP_NOTIFY: NoopNotify = NoopNotify;
+
+/// Manages the subscriptions to chain events and notifies the subscribers.
+#[derive(Clone, Default)]
+pub struct RpcPubSub {
+    /// The id of the next subscription.
+    next_subscription_id: Arc<AtomicU64>,
+
+    /// Subscriptions to new canon blocks.
+    new_blocks: Subscriptions,
+
+    /// Subscriptions to transactions admitted to the memory pool.
+    new_transactions: Subscriptions,
+
+    /// Subscriptions to chain reorganizations.
+    chain_reorgs: Subscriptions,
+}
+
+impl RpcPubSub {
+    /// Returns a handler exposing the subscriptions as RPC endpoints.
+    pub fn to_handler(&self) -> PubSubHandler<Arc<Session>> {
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+
+        self.add_subscription(&mut io, "newBlocks", &self.new_blocks);
+        self.add_subscription(&mut io, "newTransactions", &self.new_transactions);
+        self.add_subscription(&mut io, "chainReorg", &self.chain_reorgs);
+
+        io
+    }
+
+    /// Spawns a task that notifies the subscribers of the events sent by the chain notifier.
+    pub fn spawn_notifications(&self, notifier: &ChainNotifier) {
+        let mut receiver = match notifier.subscribe() {
+            Some(receiver) => receiver,
+            None => return,
+        };
+        let pubsub = self.clone();
+
+        tokio::task::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => pubsub.notify(event),
+                    // The subscribers miss the events that were dropped while the task was lagging behind.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Sends the notification of a chain event to its subscribers.
+    pub fn notify(&self, event: ChainEvent) {
+        let (subscriptions, notification) = match event {
+            ChainEvent::NewBlock {
+                block_hash,
+                block_height,
+            } => (
+                &self.new_blocks,
+                serde_json::to_value(NewBlockNotification {
+                    hash: hex::encode(&block_hash.0),
+                    height: block_height,
+                }),
+            ),
+            ChainEvent::NewTransaction {
+                transaction_id,
+                replaced,
+            } => (
+                &self.new_transactions,
+                serde_json::to_value(NewTransactionNotification {
+                    txid: hex::encode(&transaction_id),
+                    replaced: replaced.iter().map(hex::encode).collect(),
+                }),
+            ),
+            ChainEvent::ChainReorg {
+                shared_block_height,
+                reverted_block_hashes,
+                new_block_height,
+            } => (
+                &self.chain_reorgs,
+                serde_json::to_value(ChainReorgNotification {
+                    shared_height: shared_block_height,
+                    reverted_block_hashes: reverted_block_hashes.iter().map(|hash| hex::encode(&hash.0)).collect(),
+                    new_height: new_block_height,
+                }),
+            ),
+        };
+        let notification = notification.expect("notification serialization failed");
+
+        // Release the subscriptions lock before sending, so the subscribers can still (un)subscribe.
+        let subscribers: Vec<(SubscriptionId, Arc<Mutex<Sink>>)> = subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, sink)| (id.clone(), sink.clone()))
+            .collect();
+
+        let mut closed_subscriptions = vec![];
+        for (id, sink) in subscribers {
+            let params = vec![
+                ("subscription".to_string(), Value::from(id.clone())),
+                ("result".to_string(), notification.clone()),
+            ];
+
+            // The send never blocks, so a subscriber that is not reading its notifications
+            // misses the new ones instead of stalling the other subscribers.
+            let mut sink = sink.lock().unwrap();
+            match executor::spawn(&mut *sink).start_send_notify(
+                Params::Map(params.into_iter().collect()),
+                &&NOOP_NOTIFY,
+                0,
+            ) {
+                Ok(_) => {}
+                Err(_) => closed_subscriptions.push(id),
+            }
+        }
+
+        // Drop the subscriptions of the sessions that have been closed.
+        if !closed_subscriptions.is_empty() {
+            let mut subscriptions = subscriptions.lock().unwrap();
+            for id in closed_subscriptions {
+                subscriptions.remove(&id);
+            }
+        }
+    }
+
+    /// Registers the `subscribe_<name>` and `unsubscribe_<name>` endpoints for a subscription.
+    fn add_subscription(&self, io: &mut PubSubHandler<Arc<Session>>, name: &str, subscriptions: &Subscriptions) {
+        let next_subscription_id = self.next_subscription_id.clone();
+        let subscribers = subscriptions.clone();
+        let unsubscribers = subscriptions.clone();
+
+        io.add_subscription(
+            name,
+            (
+                &format!("subscribe_{}", name),
+                move |params: Params, _: Arc<Session>, subscriber: Subscriber| {
+                    if params != Params::None && params != Params::Array(vec![]) {
+                        let _ = subscriber.reject(jsonrpc_core::Error::invalid_params("expected no parameters"));
+                        return;
+                    }
+
+                    let id = SubscriptionId::Number(next_subscription_id.fetch_add(1, Ordering::SeqCst));
+                    if let Ok(sink) = subscriber.assign_id(id.clone()) {
+                        subscribers.lock().unwrap().insert(id, Arc::new(Mutex::new(sink)));
+                    }
+                },
+            ),
+            (
+                &format!("unsubscribe_{}", name),
+                move |id: SubscriptionId, _: Option<Arc<Session>>| match unsubscribers.lock().unwrap().remove(&id) {
+                    Some(_) => Ok(Value::Bool(true)),
+                    None => Err(jsonrpc_core::Error::invalid_params("invalid subscription id")),
+                },
+            ),
+        );
+    }
+}
@@ -20,6 +20,7 @@ use crate::{
     rpc_trait::RpcFunctions,
     rpc_types::{Meta, RpcCredentials},
     RpcImpl,
+    RpcPubSub,
 };
 use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
 use snarkos_dpc::base_dpc::{
@@ -29,15 +30,20 @@ use snarkos_dpc::base_dpc::{
 use snarkos_network::{external::SyncHandler, internal::context::Context};
 
 use jsonrpc_http_server::{cors::AccessControlAllowHeaders, hyper, ServerBuilder};
+use jsonrpc_pubsub::Session;
+use jsonrpc_ws_server::RequestContext;
 use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::sync::Mutex;
 
 /// Starts a local JSON-RPC HTTP server at rpc_port in a new thread.
+/// If a ws_port is given, also starts a JSON-RPC WebSocket server for subscriptions to chain events.
 /// Rpc failures will error on the thread level but not affect the main network server.
 /// This may be changed in the future to give the node more control of the rpc server.
 pub async fn start_rpc_server(
     rpc_port: u16,
+    ws_port: Option<u16>,
     secondary_storage: Arc<MerkleTreeLedger>,
+    storage: Arc<MerkleTreeLedger>,
     storage_path: PathBuf,
     parameters: PublicParameters<Components>,
     server_context: Arc<Context>,
@@ -54,8 +60,11 @@ pub async fn start_rpc_server(
         _ => None,
     };
 
+    let notifier = consensus.notifier.clone();
+
     let rpc_impl = RpcImpl::new(
         secondary_storage,
+        storage,
         storage_path,
         parameters,
         server_context,
@@ -86,5 +95,22 @@ pub async fn start_rpc_server(
         server.wait();
     });
 
+    if let Some(ws_port) = ws_port {
+        let ws_server: SocketAddr = format!("0.0.0.0:{}", ws_port).parse()?;
+
+        let pubsub = RpcPubSub::default();
+        pubsub.spawn_notifications(&notifier);
+
+        let ws_server =
+            jsonrpc_ws_server::ServerBuilder::with_meta_extractor(pubsub.to_handler(), |context: &RequestContext| {
+                Arc::new(Session::new(context.sender()))
+            })
+            .start(&ws_server)?;
+
+        tokio::task::spawn(async move {
+            let _ = ws_server.wait();
+        });
+    }
+
     Ok(())
 }
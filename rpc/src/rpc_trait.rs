@@ -54,7 +54,7 @@ pub trait RpcFunctions {
 
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/sendtransaction.md"))]
     #[rpc(name = "sendtransaction")]
-    fn send_raw_transaction(&self, transaction_bytes: String) -> Result<String, RpcError>;
+    fn send_raw_transaction(&self, transaction_bytes: String) -> Result<SendTransactionResult, RpcError>;
 
     #[cfg_attr(
         nightly,
@@ -78,6 +78,22 @@ pub trait RpcFunctions {
     #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getblocktemplate.md"))]
     #[rpc(name = "getblocktemplate")]
     fn get_block_template(&self) -> Result<BlockTemplate, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getmempoolinfo.md"))]
+    #[rpc(name = "getmempoolinfo")]
+    fn get_memory_pool_info(&self) -> Result<MemoryPoolInfo, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getrawmempool.md"))]
+    #[rpc(name = "getrawmempool")]
+    fn get_raw_memory_pool(&self, verbose: Option<bool>) -> Result<RawMemoryPool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/getmempoolentry.md"))]
+    #[rpc(name = "getmempoolentry")]
+    fn get_memory_pool_entry(&self, transaction_id: String) -> Result<MemoryPoolEntry, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/public_endpoints/submitblock.md"))]
+    #[rpc(name = "submitblock")]
+    fn submit_block(&self, block_bytes: String) -> Result<SubmitBlockResult, RpcError>;
 }
 
 /// Definition of private RPC endpoints that require authentication.
@@ -108,4 +124,31 @@ pub trait ProtectedRpcFunctions {
 
     #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/decryptrecord.md"))]
     fn decrypt_record(&self, decryption_input: DecryptRecordInput) -> Result<String, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getwalletbalance.md"))]
+    fn get_wallet_balance(&self, address: String) -> Result<u64, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getunspentrecords.md"))]
+    fn get_unspent_records(&self, address: String) -> Result<Vec<UnspentRecord>, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/removemempoolentry.md"))]
+    fn remove_memory_pool_entry(&self, transaction_id: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/addpeer.md"))]
+    fn add_peer(&self, address: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/disconnectpeer.md"))]
+    fn disconnect_peer(&self, address: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/banpeer.md"))]
+    fn ban_peer(&self, subnet: String, duration_secs: u64) -> Result<BannedPeer, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/unbanpeer.md"))]
+    fn unban_peer(&self, subnet: String) -> Result<bool, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/listpeers.md"))]
+    fn list_peers(&self) -> Result<PeerList, RpcError>;
+
+    #[cfg_attr(nightly, doc(include = "../documentation/private_endpoints/getnettotals.md"))]
+    fn get_network_totals(&self) -> Result<NetworkTotals, RpcError>;
 }
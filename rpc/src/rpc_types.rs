@@ -18,7 +18,7 @@
 
 use jsonrpc_http_server::jsonrpc_core::Metadata;
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr};
 
 /// Defines the authentication format for accessing private endpoints on the RPC server
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -38,6 +38,16 @@ pub struct Meta {
 
 impl Metadata for Meta {}
 
+/// Returned value for the `banpeer` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    /// The banned subnet
+    pub subnet: String,
+
+    /// Timestamp the ban expires at
+    pub banned_until: i64,
+}
+
 /// Returned value for the `getblock` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct BlockInfo {
@@ -71,6 +81,9 @@ pub struct BlockInfo {
     /// Block difficulty target
     pub difficulty_target: u64,
 
+    /// Cumulative work of the chain ending at the block, as a decimal string since it may exceed the JSON number range
+    pub total_work: String,
+
     /// Nonce
     pub nonce: u32,
 
@@ -119,6 +132,68 @@ pub struct DecryptRecordInput {
     pub account_view_key: String,
 }
 
+/// Returned value for the `getmempoolentry` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoolEntry {
+    /// Size of the transaction in bytes
+    pub size: usize,
+
+    /// Timestamp of the transaction's arrival in the memory pool
+    pub time: i64,
+
+    /// The transaction details
+    pub transaction: TransactionInfo,
+}
+
+/// Returned value for the `getmempoolinfo` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoolInfo {
+    /// Number of transactions in the memory pool
+    pub size: usize,
+
+    /// Total size of the transactions in the memory pool in bytes
+    pub bytes: usize,
+
+    /// Maximum total size of the transactions in the memory pool in bytes
+    pub max_bytes: usize,
+
+    /// Minimum fee per 1000 bytes of the transactions admitted to the memory pool
+    pub min_relay_fee: u64,
+}
+
+/// Notification for the `subscribe_chainReorg` subscription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChainReorgNotification {
+    /// Height of the block shared by the canon chain and the side chain
+    pub shared_height: u32,
+
+    /// Hashes of the reverted canon blocks
+    pub reverted_block_hashes: Vec<String>,
+
+    /// Height of the side chain that replaces the reverted blocks
+    pub new_height: u32,
+}
+
+/// Notification for the `subscribe_newBlocks` subscription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NewBlockNotification {
+    /// Block hash
+    pub hash: String,
+
+    /// Block height
+    pub height: u32,
+}
+
+/// Notification for the `subscribe_newTransactions` subscription
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NewTransactionNotification {
+    /// Transaction id
+    pub txid: String,
+
+    /// Ids of the pending transactions replaced by the transaction
+    pub replaced: Vec<String>,
+}
+
 /// Returned value for the `getnodeinfo` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct NodeInfo {
@@ -136,6 +211,66 @@ pub struct PeerInfo {
     pub peers: Vec<SocketAddr>,
 }
 
+/// Details of a peer known to this node
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PeerDetails {
+    /// The peer address
+    pub address: SocketAddr,
+
+    /// Timestamp the peer was last seen at
+    pub last_seen: i64,
+
+    /// The network version reported by the peer
+    pub version: Option<u64>,
+
+    /// The block height reported by the peer
+    pub height: Option<u32>,
+
+    /// The user agent reported by the peer
+    pub user_agent: Option<String>,
+
+    /// The services offered by the peer
+    pub services: Option<Vec<String>>,
+
+    /// Number of bytes sent to the peer over the current connection
+    pub bytes_sent: u64,
+
+    /// Number of bytes received from the peer over the current connection
+    pub bytes_received: u64,
+}
+
+/// Returned value for the `listpeers` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PeerList {
+    /// The peers connected to this node
+    pub connected: Vec<PeerDetails>,
+
+    /// The peers this node was disconnected from
+    pub disconnected: Vec<PeerDetails>,
+
+    /// The peers gossiped to this node that it is not connected to
+    pub gossiped: Vec<PeerDetails>,
+
+    /// The banned subnets
+    pub banned: Vec<BannedPeer>,
+}
+
+/// Returned value for the `getnettotals` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NetworkTotals {
+    /// Number of bytes sent to all peers since the node started
+    pub bytes_sent: u64,
+
+    /// Number of bytes received from all peers since the node started
+    pub bytes_received: u64,
+
+    /// Number of bytes sent to all peers since the node started, per message name
+    pub bytes_sent_per_message: HashMap<String, u64>,
+
+    /// Number of bytes received from all peers since the node started, per message name
+    pub bytes_received_per_message: HashMap<String, u64>,
+}
+
 /// Record payload data
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RPCRecordPayload {
@@ -143,6 +278,17 @@ pub struct RPCRecordPayload {
     pub payload: String,
 }
 
+/// Returned value for the `getrawmempool` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RawMemoryPool {
+    /// The ids of the transactions in the memory pool
+    TransactionIds(Vec<String>),
+
+    /// The entries of the memory pool, if verbose output was requested
+    Entries(Vec<MemoryPoolEntry>),
+}
+
 /// Returned value for the `decoderawrecord` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RecordInfo {
@@ -185,6 +331,38 @@ pub struct RpcAccount {
     pub address: String,
 }
 
+/// Returned value for the `sendtransaction` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SendTransactionResult {
+    /// Transaction id
+    pub transaction_id: String,
+
+    /// Flag indicating if the transaction was added to the memory pool
+    pub accepted: bool,
+
+    /// The ids of the pending transactions the transaction replaced
+    pub replaced: Vec<String>,
+
+    /// The reason the transaction was rejected
+    pub reject_reason: Option<String>,
+}
+
+/// Returned value for the `submitblock` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SubmitBlockResult {
+    /// Block hash
+    pub hash: String,
+
+    /// Flag indicating if the block was accepted into the canon chain
+    pub accepted: bool,
+
+    /// Flag indicating if the block is part of the canon chain
+    pub is_canon: bool,
+
+    /// The reason the block was rejected
+    pub reject_reason: Option<String>,
+}
+
 /// Returned value for the `gettransaction` rpc call
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TransactionInfo {
@@ -269,3 +447,16 @@ pub struct TransactionRecipient {
     /// The amount being sent
     pub amount: u64,
 }
+
+/// Returned value for the `getunspentrecords` rpc call
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UnspentRecord {
+    /// The record commitment
+    pub commitment: String,
+
+    /// The record value
+    pub value: u64,
+
+    /// The hex encoded record bytes
+    pub record: String,
+}
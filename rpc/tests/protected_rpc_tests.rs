@@ -16,13 +16,18 @@
 
 /// Tests for protected RPC endpoints
 mod protected_rpc_tests {
-    use snarkos_consensus::{memory_pool::MemoryPool, MerkleTreeLedger};
+    use snarkos_consensus::{
+        memory_pool::{Entry, MemoryPool},
+        MerkleTreeLedger,
+        Wallet,
+        WalletAccount,
+    };
     use snarkos_dpc::base_dpc::{
         instantiated::{Components, Tx},
         parameters::PublicParameters,
         record::DPCRecord,
     };
-    use snarkos_models::dpc::Record;
+    use snarkos_models::{dpc::Record, objects::Transaction};
     use snarkos_network::{external::SyncHandler, internal::context::Context};
     use snarkos_objects::{AccountAddress, AccountPrivateKey, AccountViewKey};
     use snarkos_rpc::*;
@@ -65,6 +70,14 @@ mod protected_rpc_tests {
     fn initialize_test_rpc(
         storage: &Arc<MerkleTreeLedger>,
         parameters: PublicParameters<Components>,
+    ) -> MetaIoHandler<Meta> {
+        initialize_test_rpc_with_memory_pool(storage, parameters, Arc::new(Mutex::new(MemoryPool::new())))
+    }
+
+    fn initialize_test_rpc_with_memory_pool(
+        storage: &Arc<MerkleTreeLedger>,
+        parameters: PublicParameters<Components>,
+        memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
     ) -> MetaIoHandler<Meta> {
         let server_address = random_socket_address();
         let consensus = TEST_CONSENSUS.clone();
@@ -74,9 +87,6 @@ mod protected_rpc_tests {
             password: TEST_PASSWORD.to_string(),
         };
 
-        let memory_pool = MemoryPool::new();
-        let memory_pool_lock = Arc::new(Mutex::new(memory_pool));
-
         let sync_handler = SyncHandler::new(server_address.clone());
         let sync_handler_lock = Arc::new(Mutex::new(sync_handler));
 
@@ -86,6 +96,7 @@ mod protected_rpc_tests {
         let storage_path = storage.storage.db.path().to_path_buf();
 
         let rpc_impl = RpcImpl::new(
+            storage.clone(),
             storage,
             storage_path,
             parameters,
@@ -380,4 +391,218 @@ mod protected_rpc_tests {
         drop(rpc);
         kill_storage_sync(storage);
     }
+
+    #[test]
+    fn test_rpc_get_wallet_balance() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+
+        let consensus = TEST_CONSENSUS.clone();
+
+        consensus
+            .receive_block(&parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        let [miner_acc, _, _] = &FIXTURE_VK.test_accounts;
+
+        let system_parameters = &parameters.system_parameters;
+        let wallet_account = WalletAccount::new(system_parameters, &miner_acc.private_key.to_string()).unwrap();
+        Wallet::new(system_parameters.clone(), vec![wallet_account])
+            .sync(&storage)
+            .unwrap();
+
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let method = "getwalletbalance".to_string();
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"{}\", \"params\": [\"{}\"] }}",
+            method, miner_acc.address
+        );
+        let response = rpc.handle_request_sync(&request, meta.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        let balance: u64 = DATA.records_1.iter().map(|record| record.value()).sum();
+        assert_eq!(extracted["result"], balance);
+
+        let method = "getunspentrecords".to_string();
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"{}\", \"params\": [\"{}\"] }}",
+            method, miner_acc.address
+        );
+        let response = rpc.handle_request_sync(&request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        let unspent_records: Vec<UnspentRecord> = serde_json::from_value(extracted["result"].clone()).unwrap();
+        let expected_records: Vec<String> = DATA
+            .records_1
+            .iter()
+            .filter(|record| !record.is_dummy())
+            .map(|record| hex::encode(to_bytes![record].unwrap()))
+            .collect();
+
+        assert_eq!(
+            unspent_records
+                .into_iter()
+                .map(|record| record.record)
+                .collect::<Vec<_>>(),
+            expected_records
+        );
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_remove_memory_pool_entry() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+
+        let method = "removemempoolentry".to_string();
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"{}\", \"params\": [\"{}\"] }}",
+            method,
+            hex::encode(transaction.transaction_id().unwrap())
+        );
+        let response = rpc.handle_request_sync(&request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], false);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_remove_memory_pool_entry_stored() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let transaction_id = transaction.transaction_id().unwrap().to_vec();
+
+        let mut memory_pool = MemoryPool::new();
+        memory_pool
+            .insert(&storage, Entry {
+                size: TRANSACTION_2.len(),
+                transaction,
+            })
+            .unwrap();
+        assert!(memory_pool.transactions.contains_key(&transaction_id));
+
+        let memory_pool_lock = Arc::new(Mutex::new(memory_pool));
+        let rpc = initialize_test_rpc_with_memory_pool(&storage, parameters, memory_pool_lock.clone());
+
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"removemempoolentry\", \"params\": [\"{}\"] }}",
+            hex::encode(&transaction_id)
+        );
+        let response = rpc.handle_request_sync(&request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], true);
+
+        let memory_pool = memory_pool_lock.try_lock().unwrap();
+        assert!(!memory_pool.transactions.contains_key(&transaction_id));
+        assert_eq!(0, memory_pool.total_size);
+        drop(memory_pool);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_ban_peer() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"banpeer\", \"params\": [\"10.0.0.1/8\", 3600] }";
+        let response = rpc.handle_request_sync(request, meta.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"]["subnet"], "10.0.0.0/8");
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"addpeer\", \"params\": [\"10.1.2.3:4131\"] }";
+        let response = rpc.handle_request_sync(request, meta.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert!(extracted["error"].is_object());
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"listpeers\" }";
+        let response = rpc.handle_request_sync(request, meta.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"]["banned"][0]["subnet"], "10.0.0.0/8");
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"unbanpeer\", \"params\": [\"10.0.0.0/8\"] }";
+        let response = rpc.handle_request_sync(request, meta.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], true);
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"addpeer\", \"params\": [\"10.1.2.3:4131\"] }";
+        let response = rpc.handle_request_sync(request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], true);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_get_network_totals() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"getnettotals\" }";
+        let response = rpc.handle_request_sync(request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"]["bytes_sent"], 0);
+        assert_eq!(extracted["result"]["bytes_received"], 0);
+        assert!(extracted["result"]["bytes_sent_per_message"].is_object());
+        assert!(extracted["result"]["bytes_received_per_message"].is_object());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_disconnect_peer() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+        let meta = authentication();
+        let rpc = initialize_test_rpc(&storage, parameters);
+
+        let request =
+            "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"disconnectpeer\", \"params\": [\"127.0.0.1:4131\"] }";
+        let response = rpc.handle_request_sync(request, meta).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(extracted["result"], false);
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
 }
@@ -0,0 +1,107 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+/// Tests for RPC subscriptions
+mod rpc_pubsub_tests {
+    use snarkos_consensus::{ChainEvent, ChainNotifier, MemoryPool};
+    use snarkos_rpc::*;
+    use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, storage::*};
+
+    use jsonrpc_core::futures::{sync::mpsc, Stream};
+    use jsonrpc_pubsub::Session;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_subscribe_new_blocks() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let parameters = load_verifying_parameters();
+
+        let mut consensus = TEST_CONSENSUS.clone();
+        consensus.notifier = ChainNotifier::new(10);
+        let mut events = consensus.notifier.subscribe().unwrap();
+
+        let pubsub = RpcPubSub::default();
+        let io = pubsub.to_handler();
+
+        let (sender, receiver) = mpsc::channel(10);
+        let session = Arc::new(Session::new(sender));
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"subscribe_newBlocks\", \"params\": [] }";
+        let response = io.handle_request_sync(request, session.clone()).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        let subscription_id = extracted["result"].clone();
+
+        consensus
+            .receive_block(&parameters, &storage, &mut MemoryPool::new(), &DATA.block_1)
+            .unwrap();
+
+        pubsub.notify(events.try_recv().unwrap());
+
+        let notification = receiver.wait().next().unwrap().unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+
+        assert_eq!(notification["method"], "newBlocks");
+        assert_eq!(notification["params"]["subscription"], subscription_id);
+        assert_eq!(
+            notification["params"]["result"]["hash"],
+            hex::encode(DATA.block_1.header.get_hash().0)
+        );
+        assert_eq!(notification["params"]["result"]["height"], 1);
+
+        let request = format!(
+            "{{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"unsubscribe_newBlocks\", \"params\": [{}] }}",
+            subscription_id
+        );
+        let response = io.handle_request_sync(&request, session).unwrap();
+
+        let extracted: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(extracted["result"], true);
+
+        drop(io);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_notify_stalled_subscriber() {
+        let pubsub = RpcPubSub::default();
+        let io = pubsub.to_handler();
+
+        // A session without any room for buffered notifications, which are never read.
+        let (sender, receiver) = mpsc::channel(0);
+        let session = Arc::new(Session::new(sender));
+
+        let request = "{ \"jsonrpc\":\"2.0\", \"id\": 1, \"method\": \"subscribe_newBlocks\", \"params\": [] }";
+        io.handle_request_sync(request, session.clone()).unwrap();
+
+        // Notifying the stalled subscriber must not block.
+        for block_height in 1..=3 {
+            pubsub.notify(ChainEvent::NewBlock {
+                block_hash: DATA.block_1.header.get_hash(),
+                block_height,
+            });
+        }
+
+        // Only the first notification fit in the session buffer.
+        let notification = receiver.wait().next().unwrap().unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+
+        assert_eq!(notification["params"]["result"]["height"], 1);
+
+        drop(io);
+    }
+}
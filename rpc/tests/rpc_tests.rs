@@ -52,6 +52,7 @@ mod rpc_tests {
 
         Rpc::new(
             RpcImpl::new(
+                storage.clone(),
                 storage,
                 storage_path,
                 parameters,
@@ -160,6 +161,7 @@ mod rpc_tests {
             genesis_block.header.difficulty_target,
             block_response["difficulty_target"]
         );
+        assert_eq!(genesis_block.header.work().to_string(), block_response["total_work"]);
         assert_eq!(genesis_block.header.nonce, block_response["nonce"]);
 
         drop(rpc);
@@ -272,17 +274,119 @@ mod rpc_tests {
         let storage = Arc::new(FIXTURE_VK.ledger());
         let rpc = initialize_test_rpc(&storage);
 
-        let transaction = Tx::read(&TRANSACTION_1[..]).unwrap();
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let transaction_id = hex::encode(transaction.transaction_id().unwrap());
+
+        let response = rpc.request("sendtransaction", &[hex::encode(TRANSACTION_2.to_vec())]);
+        let result: SendTransactionResult = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(result.transaction_id, transaction_id);
+        assert!(result.accepted);
+        assert!(result.replaced.is_empty());
+        assert_eq!(result.reject_reason, None);
+
+        let response = rpc.request("sendtransaction", &[hex::encode(TRANSACTION_2.to_vec())]);
+        let result: SendTransactionResult = serde_json::from_str(&response).unwrap();
+
+        assert!(!result.accepted);
+        assert_eq!(
+            result.reject_reason,
+            Some("Transaction was rejected: the transaction is already in the memory pool".into())
+        );
+
+        let response = rpc.request("sendtransaction", &[hex::encode(TRANSACTION_1.to_vec())]);
+        let result: SendTransactionResult = serde_json::from_str(&response).unwrap();
 
+        assert!(!result.accepted);
         assert_eq!(
-            rpc.request("sendtransaction", &[hex::encode(TRANSACTION_1.to_vec())]),
-            format![r#""{}""#, hex::encode(transaction.transaction_id().unwrap())]
+            result.reject_reason,
+            Some("Transaction was rejected: coinbase transactions are not relayed".into())
         );
 
         drop(rpc);
         kill_storage_sync(storage);
     }
 
+    #[test]
+    fn test_rpc_memory_pool() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        let transaction = Tx::read(&TRANSACTION_2[..]).unwrap();
+        let transaction_id = hex::encode(transaction.transaction_id().unwrap());
+
+        rpc.request("sendtransaction", &[hex::encode(TRANSACTION_2.to_vec())]);
+
+        let info: MemoryPoolInfo =
+            serde_json::from_value(make_request_no_params(&rpc, "getmempoolinfo".into())).unwrap();
+
+        assert_eq!(info.size, 1);
+        assert_eq!(info.bytes, TRANSACTION_2.len());
+        assert!(info.max_bytes >= info.bytes);
+        assert_eq!(info.min_relay_fee, 0);
+
+        let raw_memory_pool: RawMemoryPool =
+            serde_json::from_value(make_request_no_params(&rpc, "getrawmempool".into())).unwrap();
+
+        assert_eq!(
+            raw_memory_pool,
+            RawMemoryPool::TransactionIds(vec![transaction_id.clone()])
+        );
+
+        let entry: MemoryPoolEntry =
+            serde_json::from_str(&rpc.request("getmempoolentry", &[transaction_id.clone()])).unwrap();
+
+        assert_eq!(entry.size, TRANSACTION_2.len());
+        assert_eq!(entry.transaction.txid, transaction_id);
+        assert_eq!(entry.transaction.transaction_metadata.block_number, None);
+
+        let raw_memory_pool: RawMemoryPool = serde_json::from_str(&rpc.request("getrawmempool", &[true])).unwrap();
+
+        assert_eq!(raw_memory_pool, RawMemoryPool::Entries(vec![entry]));
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
+    #[test]
+    fn test_rpc_submit_block() {
+        let storage = Arc::new(FIXTURE_VK.ledger());
+        let rpc = initialize_test_rpc(&storage);
+
+        // A block without a known parent is only stored as an orphan
+        let orphan_bytes = hex::encode(DATA.block_2.serialize().unwrap());
+
+        let response = rpc.request("submitblock", &[orphan_bytes]);
+        let result: SubmitBlockResult = serde_json::from_str(&response).unwrap();
+
+        assert!(!result.accepted);
+        assert!(!result.is_canon);
+        assert!(result.reject_reason.is_some());
+        assert_eq!(storage.get_latest_block_height(), 0);
+
+        let block_bytes = hex::encode(DATA.block_1.serialize().unwrap());
+        let block_hash = hex::encode(DATA.block_1.header.get_hash().0);
+
+        let response = rpc.request("submitblock", &[block_bytes.clone()]);
+        let result: SubmitBlockResult = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(result.hash, block_hash);
+        assert!(result.accepted);
+        assert!(result.is_canon);
+        assert_eq!(result.reject_reason, None);
+        assert_eq!(storage.get_latest_block_height(), 2);
+
+        let response = rpc.request("submitblock", &[block_bytes]);
+        let result: SubmitBlockResult = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(result.hash, block_hash);
+        assert!(!result.accepted);
+        assert!(result.reject_reason.is_some());
+
+        drop(rpc);
+        kill_storage_sync(storage);
+    }
+
     #[test]
     fn test_rpc_validate_transaction() {
         let storage = Arc::new(FIXTURE_VK.ledger());
@@ -0,0 +1,249 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{config::Config, consensus::consensus_parameters};
+use snarkos_consensus::{ChainNotifier, ConsensusParameters, MemoryPool, MerkleTreeLedger};
+use snarkos_dpc::base_dpc::{
+    instantiated::{Components, Tx},
+    parameters::PublicParameters,
+};
+use snarkos_errors::{consensus::ConsensusError, node::CliError};
+use snarkos_objects::Block;
+use snarkos_posw::txids_to_roots;
+use snarkos_utilities::{
+    bytes::{FromBytes, ToBytes},
+    to_bytes,
+};
+
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The number of blocks between progress reports.
+const PROGRESS_INTERVAL: u32 = 100;
+
+/// Returns the storage path of the node.
+fn storage_path(config: &Config) -> PathBuf {
+    let mut path = config.node.dir.clone();
+    path.push(&config.node.db);
+    path
+}
+
+/// Returns the block height passed as the given argument, if any.
+fn block_height(arguments: &ArgMatches, name: &str) -> Result<Option<u32>, CliError> {
+    match arguments.value_of(name) {
+        Some(value) => match value.parse::<u32>() {
+            Ok(height) => Ok(Some(height)),
+            Err(error) => Err(CliError::Message(format!(
+                "Invalid block height {:?} for --{}: {}",
+                value, name, error
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Exports the canon blocks of a node to a file.
+///
+/// The file starts with the height of the first exported block and the number of exported blocks,
+/// followed by each block prefixed with its length in bytes. All integers are little-endian `u32`s.
+pub struct ExportBlocksCLI;
+
+impl ExportBlocksCLI {
+    /// Writes the canon blocks from `from` to `to` (inclusive) to the given file.
+    pub fn export_blocks<P: AsRef<Path>>(
+        storage: &MerkleTreeLedger,
+        file: P,
+        from: u32,
+        to: Option<u32>,
+    ) -> Result<u32, CliError> {
+        let latest_block_height = storage.get_latest_block_height();
+        let to = to.unwrap_or(latest_block_height).min(latest_block_height);
+
+        if from > to {
+            return Err(CliError::Message(format!(
+                "Invalid block range {}..={} (the latest block height is {})",
+                from, to, latest_block_height
+            )));
+        }
+
+        let block_count = to - from + 1;
+        let mut writer = BufWriter::new(File::create(file)?);
+        from.write(&mut writer)?;
+        block_count.write(&mut writer)?;
+
+        for height in from..=to {
+            let block_bytes = to_bytes![storage.get_block_from_block_number(height)?]?;
+
+            (block_bytes.len() as u32).write(&mut writer)?;
+            writer.write_all(&block_bytes)?;
+
+            let exported = height - from + 1;
+            if exported % PROGRESS_INTERVAL == 0 || exported == block_count {
+                println!("Exported {} of {} blocks (height {})", exported, block_count, height);
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(block_count)
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        let file = arguments.value_of("file").unwrap_or_default();
+        let from = block_height(arguments, "from")?.unwrap_or(0);
+        let to = block_height(arguments, "to")?;
+
+        // Open a secondary storage instance, so blocks can be exported from a running node.
+        let storage = MerkleTreeLedger::open_secondary_at_path(storage_path(config))?;
+
+        let block_count = Self::export_blocks(&storage, file, from, to)?;
+        println!("Successfully exported {} blocks to {}", block_count, file);
+
+        Ok(())
+    }
+}
+
+/// Imports blocks exported by `ExportBlocksCLI` into the storage of a node.
+pub struct ImportBlocksCLI;
+
+impl ImportBlocksCLI {
+    /// Reads the blocks from the given file and adds the blocks from height `from` onwards to the ledger.
+    /// If `consensus` is `None`, the blocks are trusted and only their hashes are checked.
+    pub fn import_blocks<P: AsRef<Path>>(
+        storage: &MerkleTreeLedger,
+        file: P,
+        from: u32,
+        consensus: Option<(&ConsensusParameters, &PublicParameters<Components>)>,
+    ) -> Result<u32, CliError> {
+        let mut reader = BufReader::new(File::open(file)?);
+        let start_height: u32 = FromBytes::read(&mut reader)?;
+        let block_count: u32 = FromBytes::read(&mut reader)?;
+
+        let mut memory_pool = MemoryPool::new();
+        let mut imported = 0;
+
+        for index in 0..block_count {
+            let height = start_height.checked_add(index).ok_or_else(|| {
+                CliError::Message(format!(
+                    "Invalid block range of {} blocks from height {}",
+                    block_count, start_height
+                ))
+            })?;
+            let block_size: u32 = FromBytes::read(&mut reader)?;
+
+            // Skip the blocks below the resume height without deserializing them.
+            if height < from {
+                io::copy(&mut (&mut reader).take(block_size as u64), &mut io::sink())?;
+                continue;
+            }
+
+            let mut block_bytes = vec![];
+            (&mut reader).take(block_size as u64).read_to_end(&mut block_bytes)?;
+            let block: Block<Tx> = FromBytes::read(&block_bytes[..])?;
+
+            if !storage.block_hash_exists(&block.header.get_hash()) {
+                match consensus {
+                    Some((consensus, parameters)) => {
+                        consensus.receive_block(parameters, storage, &mut memory_pool, &block)?
+                    }
+                    None => Self::insert_trusted_block(storage, &block)?,
+                }
+                imported += 1;
+            }
+
+            let processed = index + 1;
+            if processed % PROGRESS_INTERVAL == 0 || processed == block_count {
+                println!(
+                    "Processed {} of {} blocks (height {}, latest block height {})",
+                    processed,
+                    block_count,
+                    height,
+                    storage.get_latest_block_height()
+                );
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Commits the given block to the canon chain, only checking that the block extends
+    /// the canon chain and that its transactions match the merkle roots of its header.
+    fn insert_trusted_block(storage: &MerkleTreeLedger, block: &Block<Tx>) -> Result<(), CliError> {
+        let latest_block_hash = storage.get_latest_block()?.header.get_hash();
+        if block.header.previous_block_hash != latest_block_hash {
+            return Err(ConsensusError::NoParent(
+                latest_block_hash.to_string(),
+                block.header.previous_block_hash.to_string(),
+            )
+            .into());
+        }
+
+        let transaction_ids = block.transactions.to_transaction_ids().map_err(ConsensusError::from)?;
+        let (merkle_root_hash, pedersen_merkle_root_hash, _) = txids_to_roots(&transaction_ids);
+
+        if block.header.merkle_root_hash != merkle_root_hash {
+            return Err(ConsensusError::MerkleRoot(block.header.merkle_root_hash.to_string()).into());
+        }
+        if block.header.pedersen_merkle_root_hash != pedersen_merkle_root_hash {
+            return Err(ConsensusError::PedersenMerkleRoot(block.header.pedersen_merkle_root_hash.to_string()).into());
+        }
+
+        Ok(storage.insert_and_commit(block)?)
+    }
+
+    /// Loads the consensus parameters used to fully verify the imported blocks.
+    fn load_consensus(config: &Config) -> Result<(ConsensusParameters, PublicParameters<Components>), CliError> {
+        println!("Loading Aleo parameters...");
+        let parameters = PublicParameters::<Components>::load(true)?;
+        let consensus = consensus_parameters(config, &parameters, ChainNotifier::default())?;
+
+        Ok((consensus, parameters))
+    }
+
+    pub fn parse(arguments: &ArgMatches, config: &Config) -> Result<(), CliError> {
+        let file = arguments.value_of("file").unwrap_or_default();
+        let trust = arguments.is_present("trust");
+
+        let storage = MerkleTreeLedger::open_at_path(storage_path(config))?;
+
+        // Resume from the block after the latest stored block by default.
+        let from = match block_height(arguments, "from")? {
+            Some(from) => from,
+            None => storage.get_latest_block_height() + 1,
+        };
+
+        let imported = match trust {
+            true => Self::import_blocks(&storage, file, from, None)?,
+            false => {
+                let (consensus, parameters) = Self::load_consensus(config)?;
+                Self::import_blocks(&storage, file, from, Some((&consensus, &parameters)))?
+            }
+        };
+
+        println!(
+            "Successfully imported {} blocks from {} (latest block height {})",
+            imported,
+            file,
+            storage.get_latest_block_height()
+        );
+
+        Ok(())
+    }
+}
@@ -15,7 +15,9 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
+    blocks::{ExportBlocksCLI, ImportBlocksCLI},
     cli::CLI,
+    dev::DEV_NETWORK_ID,
     parameters::{flag, option, subcommand, types::*},
     update::UpdateCLI,
 };
@@ -52,6 +54,8 @@ pub struct Config {
     pub aleo: Aleo,
     pub node: Node,
     pub miner: Miner,
+    #[serde(default)]
+    pub wallet: Wallet,
     pub rpc: JsonRPC,
     pub p2p: P2P,
 }
@@ -65,6 +69,8 @@ pub struct Aleo {
 pub struct JsonRPC {
     pub json_rpc: bool,
     pub port: u16,
+    #[serde(default)]
+    pub ws_port: Option<u16>,
     pub username: Option<String>,
     pub password: Option<String>,
 }
@@ -77,6 +83,10 @@ pub struct Node {
     pub ip: String,
     pub port: u16,
     pub verbose: u8,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub is_dev: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub dev_accounts: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -85,13 +95,32 @@ pub struct Miner {
     pub miner_address: String,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Wallet {
+    pub accounts: Vec<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct P2P {
     #[serde(skip_serializing, skip_deserializing)]
     pub bootnodes: Vec<String>,
     pub mempool_interval: u8,
+    #[serde(default)]
+    pub mempool_max_size: Option<usize>,
+    #[serde(default)]
+    pub min_relay_fee: Option<u64>,
     pub min_peers: u16,
     pub max_peers: u16,
+    #[serde(default)]
+    pub encryption: bool,
+    #[serde(default)]
+    pub require_encryption: bool,
+    #[serde(default)]
+    pub max_inbound_bandwidth: Option<u64>,
+    #[serde(default)]
+    pub max_outbound_bandwidth: Option<u64>,
+    #[serde(default)]
+    pub max_block_serving_bandwidth: Option<u64>,
 }
 
 impl Default for Config {
@@ -105,14 +134,18 @@ impl Default for Config {
                 ip: "0.0.0.0".into(),
                 port: 4131,
                 verbose: 1,
+                is_dev: false,
+                dev_accounts: vec![],
             },
             miner: Miner {
                 is_miner: false,
                 miner_address: "".into(),
             },
+            wallet: Wallet { accounts: vec![] },
             rpc: JsonRPC {
                 json_rpc: true,
                 port: 3030,
+                ws_port: None,
                 // TODO (raychu86) Establish a random username and password for the node operator by default
                 username: Some("Username".into()),
                 password: Some("Password".into()),
@@ -125,6 +158,13 @@ impl Default for Config {
                 mempool_interval: 5,
                 min_peers: 7,
                 max_peers: 25,
+                encryption: false,
+                require_encryption: false,
+                mempool_max_size: None,
+                min_relay_fee: None,
+                max_inbound_bandwidth: None,
+                max_outbound_bandwidth: None,
+                max_block_serving_bandwidth: None,
             },
         }
     }
@@ -181,20 +221,37 @@ impl Config {
     fn parse(&mut self, arguments: &ArgMatches, options: &[&str]) {
         options.iter().for_each(|option| match *option {
             // Flags
+            "dev" => self.dev(arguments.is_present(option)),
             "is-bootnode" => self.is_bootnode(arguments.is_present(option)),
             "is-miner" => self.is_miner(arguments.is_present(option)),
+            "encrypt" => self.encrypt(arguments.is_present(option)),
+            "require-encryption" => self.require_encryption(arguments.is_present(option)),
             "no-jsonrpc" => self.no_jsonrpc(arguments.is_present(option)),
             // Options
             "connect" => self.connect(arguments.value_of(option)),
+            "dev-accounts" => self.dev_accounts(arguments.value_of(option)),
             "ip" => self.ip(arguments.value_of(option)),
             "miner-address" => self.miner_address(arguments.value_of(option)),
+            "wallet-accounts" => self.wallet_accounts(arguments.value_of(option)),
             "mempool-interval" => self.mempool_interval(clap::value_t!(arguments.value_of(*option), u8).ok()),
+            "mempool-max-size" => self.mempool_max_size(clap::value_t!(arguments.value_of(*option), usize).ok()),
+            "min-relay-fee" => self.min_relay_fee(clap::value_t!(arguments.value_of(*option), u64).ok()),
             "max-peers" => self.max_peers(clap::value_t!(arguments.value_of(*option), u16).ok()),
             "min-peers" => self.min_peers(clap::value_t!(arguments.value_of(*option), u16).ok()),
+            "max-inbound-bandwidth" => {
+                self.max_inbound_bandwidth(clap::value_t!(arguments.value_of(*option), u64).ok())
+            }
+            "max-outbound-bandwidth" => {
+                self.max_outbound_bandwidth(clap::value_t!(arguments.value_of(*option), u64).ok())
+            }
+            "max-block-serving-bandwidth" => {
+                self.max_block_serving_bandwidth(clap::value_t!(arguments.value_of(*option), u64).ok())
+            }
             "network" => self.network(clap::value_t!(arguments.value_of(*option), u8).ok()),
             "path" => self.path(arguments.value_of(option)),
             "port" => self.port(clap::value_t!(arguments.value_of(*option), u16).ok()),
             "rpc-port" => self.rpc_port(clap::value_t!(arguments.value_of(*option), u16).ok()),
+            "rpc-ws-port" => self.rpc_ws_port(clap::value_t!(arguments.value_of(*option), u16).ok()),
             "rpc-username" => self.rpc_username(arguments.value_of(option)),
             "rpc-password" => self.rpc_password(arguments.value_of(option)),
             "verbose" => self.verbose(clap::value_t!(arguments.value_of(*option), u8).ok()),
@@ -228,6 +285,23 @@ impl Config {
         }
     }
 
+    /// Sets the node to run on a fresh local development network with an isolated storage path.
+    fn dev(&mut self, argument: bool) {
+        if argument {
+            self.node.is_dev = true;
+            self.node.db = "snarkos_dev".into();
+            self.p2p.bootnodes = vec![];
+            self.aleo.network_id = DEV_NETWORK_ID;
+        }
+    }
+
+    fn dev_accounts(&mut self, argument: Option<&str>) {
+        if let Some(accounts) = argument {
+            let sanitize_accounts = accounts.replace(&['[', ']', ' '][..], "");
+            self.node.dev_accounts = sanitize_accounts.split(',').map(|s| s.to_string()).collect();
+        }
+    }
+
     fn no_jsonrpc(&mut self, argument: bool) {
         self.rpc.json_rpc = !argument;
     }
@@ -243,6 +317,19 @@ impl Config {
         self.miner.is_miner = argument;
     }
 
+    fn encrypt(&mut self, argument: bool) {
+        if argument {
+            self.p2p.encryption = true;
+        }
+    }
+
+    fn require_encryption(&mut self, argument: bool) {
+        if argument {
+            self.p2p.encryption = true;
+            self.p2p.require_encryption = true;
+        }
+    }
+
     fn ip(&mut self, argument: Option<&str>) {
         if let Some(ip) = argument {
             self.node.ip = ip.to_string();
@@ -276,6 +363,13 @@ impl Config {
         }
     }
 
+    fn wallet_accounts(&mut self, argument: Option<&str>) {
+        if let Some(accounts) = argument {
+            let sanitize_accounts = accounts.replace(&['[', ']', ' '][..], "");
+            self.wallet.accounts = sanitize_accounts.split(',').map(|s| s.to_string()).collect();
+        }
+    }
+
     fn mempool_interval(&mut self, argument: Option<u8>) {
         if let Some(interval) = argument {
             self.p2p.mempool_interval = interval
@@ -294,12 +388,48 @@ impl Config {
         }
     }
 
+    fn mempool_max_size(&mut self, argument: Option<usize>) {
+        if let Some(mempool_max_size) = argument {
+            self.p2p.mempool_max_size = Some(mempool_max_size);
+        }
+    }
+
+    fn min_relay_fee(&mut self, argument: Option<u64>) {
+        if let Some(min_relay_fee) = argument {
+            self.p2p.min_relay_fee = Some(min_relay_fee);
+        }
+    }
+
+    fn max_inbound_bandwidth(&mut self, argument: Option<u64>) {
+        if let Some(bytes_per_second) = argument {
+            self.p2p.max_inbound_bandwidth = Some(bytes_per_second);
+        }
+    }
+
+    fn max_outbound_bandwidth(&mut self, argument: Option<u64>) {
+        if let Some(bytes_per_second) = argument {
+            self.p2p.max_outbound_bandwidth = Some(bytes_per_second);
+        }
+    }
+
+    fn max_block_serving_bandwidth(&mut self, argument: Option<u64>) {
+        if let Some(bytes_per_second) = argument {
+            self.p2p.max_block_serving_bandwidth = Some(bytes_per_second);
+        }
+    }
+
     fn rpc_port(&mut self, argument: Option<u16>) {
         if let Some(rpc_port) = argument {
             self.rpc.port = rpc_port;
         }
     }
 
+    fn rpc_ws_port(&mut self, argument: Option<u16>) {
+        if let Some(ws_port) = argument {
+            self.rpc.ws_port = Some(ws_port);
+        }
+    }
+
     fn rpc_username(&mut self, argument: Option<&str>) {
         if let Some(username) = argument {
             self.rpc.username = Some(username.to_string());
@@ -326,7 +456,14 @@ impl CLI for ConfigCli {
     type Config = Config;
 
     const ABOUT: AboutType = "Run an Aleo node (include -h for more options)";
-    const FLAGS: &'static [FlagType] = &[flag::NO_JSONRPC, flag::IS_BOOTNODE, flag::IS_MINER];
+    const FLAGS: &'static [FlagType] = &[
+        flag::NO_JSONRPC,
+        flag::IS_BOOTNODE,
+        flag::IS_MINER,
+        flag::DEV,
+        flag::ENCRYPT,
+        flag::REQUIRE_ENCRYPTION,
+    ];
     const NAME: NameType = "snarkOS";
     const OPTIONS: &'static [OptionType] = &[
         option::IP,
@@ -334,34 +471,54 @@ impl CLI for ConfigCli {
         option::PATH,
         option::CONNECT,
         option::MINER_ADDRESS,
+        option::WALLET_ACCOUNTS,
+        option::DEV_ACCOUNTS,
         option::MEMPOOL_INTERVAL,
+        option::MEMPOOL_MAX_SIZE,
+        option::MIN_RELAY_FEE,
         option::MIN_PEERS,
         option::MAX_PEERS,
+        option::MAX_INBOUND_BANDWIDTH,
+        option::MAX_OUTBOUND_BANDWIDTH,
+        option::MAX_BLOCK_SERVING_BANDWIDTH,
         option::NETWORK,
         option::RPC_PORT,
+        option::RPC_WS_PORT,
         option::RPC_USERNAME,
         option::RPC_PASSWORD,
         option::VERBOSE,
     ];
-    const SUBCOMMANDS: &'static [SubCommandType] = &[subcommand::UPDATE];
+    const SUBCOMMANDS: &'static [SubCommandType] =
+        &[subcommand::UPDATE, subcommand::EXPORT_BLOCKS, subcommand::IMPORT_BLOCKS];
 
     /// Handle all CLI arguments and flags for skeleton node
     fn parse(arguments: &ArgMatches) -> Result<Self::Config, CliError> {
         let mut config = Config::read_config()?;
         config.parse(arguments, &[
             "network",
+            "dev",
             "no-jsonrpc",
             "is-bootnode",
             "is-miner",
+            "encrypt",
+            "require-encryption",
             "ip",
             "port",
             "path",
             "connect",
             "miner-address",
+            "wallet-accounts",
+            "dev-accounts",
             "mempool-interval",
+            "mempool-max-size",
+            "min-relay-fee",
             "min-peers",
             "max-peers",
+            "max-inbound-bandwidth",
+            "max-outbound-bandwidth",
+            "max-block-serving-bandwidth",
             "rpc-port",
+            "rpc-ws-port",
             "rpc-username",
             "rpc-password",
             "verbose",
@@ -372,6 +529,14 @@ impl CLI for ConfigCli {
                 UpdateCLI::parse(arguments)?;
                 std::process::exit(0x0100);
             }
+            ("export-blocks", Some(arguments)) => {
+                ExportBlocksCLI::parse(arguments, &config)?;
+                std::process::exit(0x0100);
+            }
+            ("import-blocks", Some(arguments)) => {
+                ImportBlocksCLI::parse(arguments, &config)?;
+                std::process::exit(0x0100);
+            }
             _ => {}
         }
 
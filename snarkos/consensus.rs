@@ -0,0 +1,54 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{config::Config, dev::DEV_DIFFICULTY_TARGET};
+use snarkos_consensus::{ChainNotifier, ConsensusParameters};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters, BaseDPCComponents};
+use snarkos_errors::node::CliError;
+use snarkos_models::algorithms::{CRH, SNARK};
+use snarkos_objects::Network;
+use snarkos_posw::PoswMarlin;
+use snarkos_utilities::{to_bytes, ToBytes};
+
+/// Returns the consensus parameters of a node with the given configuration.
+/// A development node expects every block to have the development difficulty target.
+pub fn consensus_parameters(
+    config: &Config,
+    parameters: &PublicParameters<Components>,
+    notifier: ChainNotifier,
+) -> Result<ConsensusParameters, CliError> {
+    // Fetch the valid inner snark ids
+    let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
+        parameters.inner_snark_parameters.1.clone().into();
+    let inner_snark_id = parameters
+        .system_parameters
+        .inner_snark_verification_key_crh
+        .hash(&to_bytes![inner_snark_vk]?)?;
+
+    Ok(ConsensusParameters {
+        max_block_size: 1_000_000_000usize,
+        max_nonce: u32::max_value(),
+        target_block_time: 10i64,
+        fixed_difficulty_target: match config.node.is_dev {
+            true => Some(DEV_DIFFICULTY_TARGET),
+            false => None,
+        },
+        network: Network::from_network_id(config.aleo.network_id),
+        verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
+        authorized_inner_snark_ids: vec![to_bytes![inner_snark_id]?],
+        notifier,
+    })
+}
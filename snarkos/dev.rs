@@ -0,0 +1,150 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::{ConsensusParameters, MerkleTreeLedger};
+use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters};
+use snarkos_errors::{
+    consensus::ConsensusError,
+    node::{CliError, NodeError},
+};
+use snarkos_models::{algorithms::CRH, dpc::Record, objects::LedgerScheme};
+use snarkos_objects::{AccountAddress, Block, BlockHeader, BlockHeaderHash, DPCTransactions, ProofOfSuccinctWork};
+use snarkos_posw::txids_to_roots;
+use snarkos_utilities::{to_bytes, ToBytes};
+
+use chrono::Utc;
+use rand::thread_rng;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The network id of a local development network.
+pub const DEV_NETWORK_ID: u8 = 255;
+
+/// The difficulty target of every block on a local development network.
+pub const DEV_DIFFICULTY_TARGET: u64 = u64::MAX;
+
+/// Returns the path of the file marking the ledger at the given path as a development ledger.
+pub fn dev_ledger_marker<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut marker_path = path.as_ref().to_path_buf().into_os_string();
+    marker_path.push(".dev");
+    PathBuf::from(marker_path)
+}
+
+/// Creates a fresh ledger for a local development network at the given path.
+///
+/// An existing development ledger at the path is destroyed, and a new genesis block is generated
+/// with one coinbase transaction for each of the given accounts. The coinbase records
+/// of the funded accounts are stored in the new ledger. At least one account must be given.
+/// A ledger at the path that was not created by this function is never destroyed.
+pub fn open_dev_ledger<P: AsRef<Path>>(
+    path: P,
+    consensus: &ConsensusParameters,
+    parameters: &PublicParameters<Components>,
+    accounts: &[AccountAddress<Components>],
+) -> Result<MerkleTreeLedger, NodeError> {
+    if accounts.is_empty() {
+        return Err(CliError::Message(
+            "The development genesis block requires an account to fund (set --dev-accounts or --miner-address)".into(),
+        )
+        .into());
+    }
+
+    let path = path.as_ref().to_path_buf();
+    let marker_path = dev_ledger_marker(&path);
+
+    if path.exists() && !marker_path.exists() {
+        return Err(CliError::Message(format!(
+            "Refusing to replace the ledger at {}, as it is not a development ledger",
+            path.display()
+        ))
+        .into());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&marker_path, b"")?;
+    MerkleTreeLedger::destroy_storage(path.clone())?;
+
+    // The coinbase transactions are created against a temporary ledger, as the
+    // transactions of a genesis block are not verified against the ledger state.
+    let mut genesis_ledger_path = path.clone().into_os_string();
+    genesis_ledger_path.push("_genesis");
+    let genesis_ledger_path = PathBuf::from(genesis_ledger_path);
+
+    MerkleTreeLedger::destroy_storage(genesis_ledger_path.clone())?;
+    let genesis_ledger = MerkleTreeLedger::open_at_path(&genesis_ledger_path)?;
+
+    let program_vk_hash = to_bytes![ProgramVerificationKeyCRH::hash(
+        &parameters.system_parameters.program_verification_key_crh,
+        &to_bytes![parameters.noop_program_snark_parameters.verification_key]?
+    )?]?;
+
+    let rng = &mut thread_rng();
+    let mut transactions = DPCTransactions::new();
+    let mut records = vec![];
+
+    for account in accounts {
+        info!("Funding {} in the development genesis block", account);
+
+        let (coinbase_records, transaction) = consensus.create_coinbase_transaction(
+            0,
+            &DPCTransactions::new(),
+            parameters,
+            &program_vk_hash,
+            vec![program_vk_hash.clone(); NUM_OUTPUT_RECORDS],
+            vec![program_vk_hash.clone(); NUM_OUTPUT_RECORDS],
+            account.clone(),
+            &genesis_ledger,
+            rng,
+        )?;
+
+        transactions.push(transaction);
+        records.extend(coinbase_records.into_iter().filter(|record| !record.is_dummy()));
+    }
+
+    let ledger_parameters = genesis_ledger.ledger_parameters.clone();
+    drop(genesis_ledger);
+    MerkleTreeLedger::destroy_storage(genesis_ledger_path)?;
+
+    let (merkle_root_hash, pedersen_merkle_root_hash, _) =
+        txids_to_roots(&transactions.to_transaction_ids().map_err(ConsensusError::from)?);
+
+    let genesis_block = Block {
+        header: BlockHeader {
+            previous_block_hash: BlockHeaderHash([0u8; 32]),
+            merkle_root_hash,
+            pedersen_merkle_root_hash,
+            time: Utc::now().timestamp(),
+            difficulty_target: DEV_DIFFICULTY_TARGET,
+            nonce: 0,
+            proof: ProofOfSuccinctWork::default(),
+        },
+        transactions,
+    };
+
+    let ledger = MerkleTreeLedger::new(&path, ledger_parameters, genesis_block)?;
+    ledger.store_records(&records)?;
+
+    info!(
+        "Created a development ledger with genesis block {}",
+        ledger.get_block_hash(0)?
+    );
+
+    Ok(ledger)
+}
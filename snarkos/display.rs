@@ -79,6 +79,10 @@ pub fn render_init(config: &Config) -> String {
 
     if config.rpc.json_rpc {
         output += &format!("Listening for RPC requests on port {}\n", config.rpc.port);
+
+        if let Some(ws_port) = config.rpc.ws_port {
+            output += &format!("Listening for RPC subscriptions on port {}\n", ws_port);
+        }
     }
 
     format!("{}", output)
@@ -17,9 +17,13 @@
 #[macro_use]
 extern crate log;
 
+pub mod blocks;
 pub mod cli;
 pub mod config;
+pub mod consensus;
+pub mod dev;
 pub mod display;
 pub mod miner;
 pub mod parameters;
 pub mod update;
+pub mod wallet;
@@ -20,30 +20,65 @@ extern crate log;
 use snarkos::{
     cli::CLI,
     config::{Config, ConfigCli},
+    consensus::consensus_parameters,
+    dev::open_dev_ledger,
     display::render_init,
     miner::MinerInstance,
+    wallet::{wallet_accounts, WalletInstance, WALLET_PRIVATE_KEYS_ENV},
 };
-use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger};
-use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters, BaseDPCComponents};
-use snarkos_errors::node::NodeError;
-use snarkos_models::algorithms::{CRH, SNARK};
-use snarkos_network::{external::protocol::SyncHandler, internal::context::Context, Server};
-use snarkos_objects::{AccountAddress, Network};
-use snarkos_posw::PoswMarlin;
+use snarkos_consensus::{ChainNotifier, MemoryPool, MerkleTreeLedger, Wallet};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters};
+use snarkos_errors::{network::ServerError, node::NodeError};
+use snarkos_network::{
+    external::{protocol::SyncHandler, Encryption, RateLimits, StaticKeypair, STATIC_KEY_FILE},
+    internal::context::Context,
+    Server,
+};
+use snarkos_objects::AccountAddress;
 use snarkos_rpc::start_rpc_server;
-use snarkos_utilities::{to_bytes, ToBytes};
 
 use std::{net::SocketAddr, str::FromStr, sync::Arc};
-use tokio::{runtime::Runtime, sync::Mutex};
+use tokio::{
+    runtime::Runtime,
+    sync::{watch, Mutex},
+    task,
+};
+
+/// Resolves once the node receives a SIGINT or, on unix platforms, a SIGTERM signal.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = sigterm.recv() => {},
+                }
+                return;
+            }
+            Err(error) => error!("Failed to listen for SIGTERM signals ({})", error),
+        }
+    }
+
+    if let Err(error) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for SIGINT signals ({})", error);
+        std::future::pending::<()>().await;
+    }
+}
 
 /// Builds a node from configuration parameters.
-/// 1. Creates new storage database or uses existing.
-/// 2. Creates new memory pool or uses existing from storage.
-/// 3. Creates consensus parameters.
+/// 1. Creates consensus parameters.
+/// 2. Creates new storage database or uses existing. A development node always creates a new one.
+/// 3. Creates new memory pool or uses existing from storage.
 /// 4. Creates network server.
 /// 5. Starts rpc server thread.
 /// 6. Starts miner thread.
-/// 7. Starts network server listener.
+/// 7. Starts wallet thread.
+/// 8. Starts network server listener.
+/// 9. Upon receiving a SIGINT or SIGTERM signal, stops the miner and wallet, drains the network server,
+///    persists the memory pool and peer book, and flushes the storage database.
 async fn start_server(config: Config) -> Result<(), NodeError> {
     match config.node.verbose {
         0 => {}
@@ -62,11 +97,49 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
     let address = format! {"{}:{}", config.node.ip, config.node.port};
     let socket_address = address.parse::<SocketAddr>()?;
 
-    let mut path = config.node.dir;
+    info!("Loading Aleo parameters...");
+    // A development node generates its own genesis block, which requires the proving parameters.
+    let parameters = PublicParameters::<Components>::load(!config.miner.is_miner && !config.node.is_dev)?;
+    info!("Loading complete.");
+
+    // Broadcast chain events only if the RPC server serves subscriptions to them.
+    let notifier = match config.rpc.json_rpc && config.rpc.ws_port.is_some() {
+        true => ChainNotifier::new(1000),
+        false => ChainNotifier::default(),
+    };
+
+    // Set the initial consensus parameters.
+    let consensus = consensus_parameters(&config, &parameters, notifier.clone())?;
+
+    let mut path = config.node.dir.clone();
     path.push(&config.node.db);
-    let storage = Arc::new(MerkleTreeLedger::open_at_path(path.clone())?);
+    let storage = match config.node.is_dev {
+        true => {
+            // Fund the miner address if no development accounts are given.
+            let mut dev_account_addresses = config.node.dev_accounts.clone();
+            if dev_account_addresses.is_empty() && !config.miner.miner_address.is_empty() {
+                dev_account_addresses.push(config.miner.miner_address.clone());
+            }
+
+            let mut dev_accounts = vec![];
+            for dev_account in &dev_account_addresses {
+                dev_accounts.push(AccountAddress::<Components>::from_str(dev_account)?);
+            }
 
-    let memory_pool = MemoryPool::from_storage(&storage.clone())?;
+            info!("Generating a development genesis block...");
+            Arc::new(open_dev_ledger(&path, &consensus, &parameters, &dev_accounts)?)
+        }
+        false => Arc::new(MerkleTreeLedger::open_at_path(path.clone())?),
+    };
+
+    let mut memory_pool = MemoryPool::from_storage(&storage.clone())?;
+    memory_pool.notifier = notifier;
+    if let Some(max_size) = config.p2p.mempool_max_size {
+        memory_pool.set_max_size(max_size)?;
+    }
+    if let Some(min_relay_fee) = config.p2p.min_relay_fee {
+        memory_pool.min_relay_fee = min_relay_fee;
+    }
     let memory_pool_lock = Arc::new(Mutex::new(memory_pool.clone()));
 
     let bootnode = match config.p2p.bootnodes.len() {
@@ -77,29 +150,15 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
     let sync_handler = SyncHandler::new(bootnode);
     let sync_handler_lock = Arc::new(Mutex::new(sync_handler));
 
-    info!("Loading Aleo parameters...");
-    let parameters = PublicParameters::<Components>::load(!config.miner.is_miner)?;
-    info!("Loading complete.");
-
-    // Fetch the valid inner snark ids
-    let inner_snark_vk: <<Components as BaseDPCComponents>::InnerSNARK as SNARK>::VerificationParameters =
-        parameters.inner_snark_parameters.1.clone().into();
-    let inner_snark_id = parameters
-        .system_parameters
-        .inner_snark_verification_key_crh
-        .hash(&to_bytes![inner_snark_vk]?)?;
-
-    let authorized_inner_snark_ids = vec![to_bytes![inner_snark_id]?];
-
-    // Set the initial consensus parameters.
-    let consensus = ConsensusParameters {
-        max_block_size: 1_000_000_000usize,
-        max_nonce: u32::max_value(),
-        target_block_time: 10i64,
-        network: Network::from_network_id(config.aleo.network_id),
-        verifier: PoswMarlin::verify_only().expect("could not instantiate PoSW verifier"),
-        authorized_inner_snark_ids,
-    };
+    // Broadcast a shutdown to the node components once a SIGINT or SIGTERM signal is received.
+    let (shutdown_sender, mut shutdown_receiver) = watch::channel(false);
+    task::spawn(async move {
+        shutdown_signal().await;
+        info!("Received a shutdown signal. Shutting down the node...");
+        if let Err(error) = shutdown_sender.broadcast(true) {
+            error!("Failed to broadcast the shutdown signal ({})", error);
+        }
+    });
 
     let mut context = Arc::new(Context::new(
         socket_address,
@@ -111,7 +170,29 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
         false,
     ));
 
+    // Throttle the traffic with the peers to the configured rate limits.
+    context.bandwidth.set_limits(RateLimits {
+        inbound: config.p2p.max_inbound_bandwidth,
+        outbound: config.p2p.max_outbound_bandwidth,
+        block_serving: config.p2p.max_block_serving_bandwidth,
+    });
+
+    // Load the static key of the node, if its peer connections are encrypted.
+    if config.p2p.encryption || config.p2p.require_encryption {
+        let keypair =
+            StaticKeypair::load_or_generate(&config.node.dir.join(STATIC_KEY_FILE)).map_err(ServerError::from)?;
+        info!(
+            "Encrypting peer connections with static key {}",
+            hex::encode(keypair.public_key())
+        );
+
+        if let Some(mutable_context) = Arc::get_mut(&mut context) {
+            mutable_context.encryption = Encryption::new(keypair, config.p2p.require_encryption);
+        }
+    }
+
     // Start the miner task, if the mining configuration is enabled.
+    let mut miner_handle = None;
     if config.miner.is_miner {
         match AccountAddress::<Components>::from_str(&config.miner.miner_address) {
             Ok(miner_address) => {
@@ -119,15 +200,17 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
                     mutable_context.is_miner = true;
                 }
 
-                MinerInstance::new(
-                    miner_address,
-                    consensus.clone(),
-                    parameters.clone(),
-                    storage.clone(),
-                    memory_pool_lock.clone(),
-                    context.clone(),
-                )
-                .spawn();
+                miner_handle = Some(
+                    MinerInstance::new(
+                        miner_address,
+                        consensus.clone(),
+                        parameters.clone(),
+                        storage.clone(),
+                        memory_pool_lock.clone(),
+                        context.clone(),
+                    )
+                    .spawn(shutdown_receiver.clone()),
+                );
             }
             Err(_) => info!(
                 "Miner not started. Please specify a valid miner address in your ~/.snarkOS/config.toml file or by using the --miner-address option in the CLI."
@@ -135,6 +218,27 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
         }
     }
 
+    // Start the wallet task, if the wallet is configured to track any accounts.
+    let wallet_private_keys = match std::env::var(WALLET_PRIVATE_KEYS_ENV) {
+        Ok(private_keys) => private_keys
+            .split(',')
+            .map(|private_key| private_key.trim().to_string())
+            .filter(|private_key| !private_key.is_empty())
+            .collect(),
+        Err(_) => vec![],
+    };
+    let tracked_accounts = wallet_accounts(
+        &parameters.system_parameters,
+        &config.wallet.accounts,
+        &wallet_private_keys,
+    )?;
+
+    let mut wallet_handle = None;
+    if !tracked_accounts.is_empty() {
+        let wallet = Wallet::new(parameters.system_parameters.clone(), tracked_accounts);
+        wallet_handle = Some(WalletInstance::new(wallet, storage.clone()).spawn(shutdown_receiver.clone()));
+    }
+
     // Construct the server instance. Note this does not start the server.
     let server = Server::new(
         context,
@@ -144,7 +248,7 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
         memory_pool_lock.clone(),
         sync_handler_lock.clone(),
         15000, // 15 seconds
-    );
+    )?;
 
     // Start RPC thread, if the RPC configuration is enabled.
     if config.rpc.json_rpc {
@@ -157,7 +261,9 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
 
         start_rpc_server(
             config.rpc.port,
+            config.rpc.ws_port,
             secondary_storage.clone(),
+            storage.clone(),
             path,
             proving_parameters,
             server.context.clone(),
@@ -170,8 +276,35 @@ async fn start_server(config: Config) -> Result<(), NodeError> {
         .await?;
     }
 
-    // Start the main server thread.
-    server.listen().await?;
+    // Start the main server thread and run it until the node is shut down.
+    // The miner is stopped before the server drains its pending messages,
+    // so that the persisted memory pool reflects the last mined block.
+    server
+        .listen_until(async move {
+            while let Some(is_shutdown) = shutdown_receiver.recv().await {
+                if is_shutdown {
+                    break;
+                }
+            }
+
+            if let Some(miner_handle) = miner_handle {
+                info!("Waiting for the miner to stop...");
+                if let Err(error) = miner_handle.await {
+                    error!("Miner did not stop cleanly ({})", error);
+                }
+            }
+
+            if let Some(wallet_handle) = wallet_handle {
+                if let Err(error) = wallet_handle.await {
+                    error!("Wallet did not stop cleanly ({})", error);
+                }
+            }
+        })
+        .await?;
+
+    // Flush the storage before its handle is dropped and closed.
+    storage.flush()?;
+    info!("Shutdown complete");
 
     Ok(())
 }
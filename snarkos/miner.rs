@@ -16,11 +16,18 @@
 
 use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger, Miner};
 use snarkos_dpc::base_dpc::{instantiated::*, parameters::PublicParameters};
+use snarkos_errors::{consensus::ConsensusError, posw::PoswError};
 use snarkos_network::{external::propagate_block, internal::context::Context};
 use snarkos_objects::{AccountAddress, Block};
 
-use std::sync::Arc;
-use tokio::{sync::Mutex, task};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::{
+    sync::{watch, Mutex},
+    task,
+};
 
 /// Parameters for spawning a miner that runs proof of work to find a block.
 pub struct MinerInstance {
@@ -55,18 +62,38 @@ impl MinerInstance {
     /// Spawns a new miner on a new thread using MinerInstance parameters.
     /// Once a block is found, A block message is sent to all peers.
     /// Calling this function multiple times will spawn additional listeners on separate threads.
-    /// Miner threads are asynchronous so the only way to stop them is to kill the runtime they were started in. This may be changed in the future.
-    pub fn spawn(self) {
+    /// The miner stops once `shutdown` is set to `true`, cancelling the block it is currently mining.
+    /// Returns a handle that resolves once the miner has stopped.
+    pub fn spawn(self, shutdown: watch::Receiver<bool>) -> task::JoinHandle<()> {
+        // Cancel the proof of work in progress as soon as the shutdown is broadcast.
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut shutdown_watch = shutdown.clone();
+        let mining_cancelled = cancelled.clone();
+        task::spawn(async move {
+            while let Some(is_shutdown) = shutdown_watch.recv().await {
+                if is_shutdown {
+                    mining_cancelled.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+        });
+
         task::spawn(async move {
             let context = self.server_context.clone();
             let local_address = *self.server_context.local_address.read().await;
             info!("Initializing Aleo miner - Your miner address is {}", self.miner_address);
-            let miner = Miner::new(self.miner_address.clone(), self.consensus.clone());
+            let miner =
+                Miner::new(self.miner_address.clone(), self.consensus.clone()).with_cancellation(cancelled.clone());
 
             let mut mining_failure_count = 0;
             let mining_failure_threshold = 10;
 
             loop {
+                if *shutdown.borrow() {
+                    info!("Shutting down miner");
+                    break;
+                }
+
                 info!("Starting to mine the next block");
 
                 let (block_serialized, _coinbase_records) = match miner
@@ -74,6 +101,10 @@ impl MinerInstance {
                     .await
                 {
                     Ok(mined_block) => mined_block,
+                    Err(ConsensusError::PoswError(PoswError::MiningCancelled)) => {
+                        info!("Shutting down miner");
+                        break;
+                    }
                     Err(error) => {
                         warn!(
                             "Miner failed to mine a block {} time(s). (error message: {}).",
@@ -95,15 +126,18 @@ impl MinerInstance {
 
                 match Block::<Tx>::deserialize(&block_serialized) {
                     Ok(block) => {
-                        info!("Mined a new block!\t{:?}", hex::encode(block.header.get_hash().0));
+                        let block_hash = block.header.get_hash();
+                        info!("Mined a new block!\t{:?}", hex::encode(&block_hash.0));
 
-                        if let Err(err) = propagate_block(context.clone(), block_serialized, local_address).await {
+                        if let Err(err) =
+                            propagate_block(context.clone(), block_hash, block_serialized, local_address).await
+                        {
                             error!("Error propagating block to peers: {:?}", err);
                         }
                     }
                     Err(_) => continue,
                 }
             }
-        });
+        })
     }
 }
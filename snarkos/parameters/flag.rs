@@ -23,4 +23,15 @@ pub const IS_BOOTNODE: &str =
 
 pub const IS_MINER: &str = "[is-miner] --is-miner 'Start mining blocks from this node'";
 
+pub const ENCRYPT: &str = "[encrypt] --encrypt 'Encrypt the connections to peers using the static key of the node'";
+
+pub const REQUIRE_ENCRYPTION: &str =
+    "[require-encryption] --require-encryption 'Reject unencrypted connection requests from peers (implies --encrypt)'";
+
+pub const DEV: &str =
+    "[dev] --dev 'Run the node on a fresh local development network with a trivial difficulty (clears any previous development chain)'";
+
 pub const LIST: &str = "[list] -l --list 'List all available releases of snarkOS'";
+
+pub const TRUST: &str =
+    "[trust] --trust 'Import the blocks without verifying them, only checking that their hashes link up'";
@@ -72,6 +72,41 @@ pub const MAX_PEERS: OptionType = (
     &[],
 );
 
+pub const MEMPOOL_MAX_SIZE: OptionType = (
+    "[mempool-max-size] --mempool-max-size=[bytes] 'Specify the maximum total size of the transactions in the node's mempool'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MIN_RELAY_FEE: OptionType = (
+    "[min-relay-fee] --min-relay-fee=[fee] 'Specify the minimum fee per 1000 bytes of the transactions admitted to the node's mempool'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_INBOUND_BANDWIDTH: OptionType = (
+    "[max-inbound-bandwidth] --max-inbound-bandwidth=[bytes] 'Specify the maximum number of bytes per second the node receives from its peers'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_OUTBOUND_BANDWIDTH: OptionType = (
+    "[max-outbound-bandwidth] --max-outbound-bandwidth=[bytes] 'Specify the maximum number of bytes per second the node sends to its peers, except for blocks served to syncing peers'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const MAX_BLOCK_SERVING_BANDWIDTH: OptionType = (
+    "[max-block-serving-bandwidth] --max-block-serving-bandwidth=[bytes] 'Specify the maximum number of bytes per second of blocks the node serves to syncing peers'",
+    &[],
+    &[],
+    &[],
+);
+
 pub const NETWORK: OptionType = (
     "[network] --network=[network-id] 'Specify the network id (default = 1) of the node'",
     &[],
@@ -79,6 +114,20 @@ pub const NETWORK: OptionType = (
     &[],
 );
 
+pub const WALLET_ACCOUNTS: OptionType = (
+    "[wallet-accounts] --wallet-accounts=[accounts] 'Specify one or more account view keys to track in the node wallet'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const DEV_ACCOUNTS: OptionType = (
+    "[dev-accounts] --dev-accounts=[addresses] 'Specify one or more account addresses to fund in the development genesis block (defaults to the miner address)'",
+    &[],
+    &[],
+    &["dev"],
+);
+
 pub const RPC_PORT: OptionType = (
     "[rpc-port] --rpc-port=[rpc-port] 'Specify the port the json rpc server is run on'",
     &["no_jsonrpc"],
@@ -86,6 +135,13 @@ pub const RPC_PORT: OptionType = (
     &[],
 );
 
+pub const RPC_WS_PORT: OptionType = (
+    "[rpc-ws-port] --rpc-ws-port=[rpc-ws-port] 'Serve the json rpc subscriptions over WebSocket on the given port (disabled by default)'",
+    &["no_jsonrpc"],
+    &[],
+    &[],
+);
+
 pub const RPC_USERNAME: OptionType = (
     "[rpc-username] --rpc-username=[rpc-username] 'Specify a username for rpc authentication'",
     &["no-jsonrpc"],
@@ -106,3 +162,33 @@ pub const VERBOSE: OptionType = (
     &["0", "1", "2"],
     &[],
 );
+
+// Subcommands
+
+pub const BLOCKS_FILE: OptionType = (
+    "<file> 'Specify the file to export the blocks to or import the blocks from'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const EXPORT_FROM: OptionType = (
+    "[from] --from=[height] 'Specify the height of the first block to export (default = 0)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const EXPORT_TO: OptionType = (
+    "[to] --to=[height] 'Specify the height of the last block to export (default = latest block height)'",
+    &[],
+    &[],
+    &[],
+);
+
+pub const IMPORT_FROM: OptionType = (
+    "[from] --from=[height] 'Specify the height of the first block to import (default = latest block height + 1)'",
+    &[],
+    &[],
+    &[],
+);
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::parameters::{flag, types::*};
+use crate::parameters::{flag, option, types::*};
 
 use clap::AppSettings;
 // Format
@@ -31,3 +31,27 @@ pub const UPDATE: SubCommandType = (
         AppSettings::DisableVersion,
     ],
 );
+
+pub const EXPORT_BLOCKS: SubCommandType = (
+    "export-blocks",
+    "Export the canon blocks of the node to a file (include -h for more options)",
+    &[option::BLOCKS_FILE, option::EXPORT_FROM, option::EXPORT_TO],
+    &[],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
+
+pub const IMPORT_BLOCKS: SubCommandType = (
+    "import-blocks",
+    "Import blocks into the node from a file created by export-blocks (include -h for more options)",
+    &[option::BLOCKS_FILE, option::IMPORT_FROM],
+    &[flag::TRUST],
+    &[
+        AppSettings::ColoredHelp,
+        AppSettings::DisableHelpSubcommand,
+        AppSettings::DisableVersion,
+    ],
+);
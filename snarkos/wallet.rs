@@ -0,0 +1,109 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::{MerkleTreeLedger, Wallet, WalletAccount};
+use snarkos_dpc::base_dpc::{instantiated::Components, parameters::SystemParameters};
+use snarkos_errors::node::CliError;
+use snarkos_objects::AccountPrivateKey;
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tokio::{sync::watch, task, time::delay_for};
+
+/// The interval between two scans of the canon chain.
+const WALLET_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The environment variable holding the private keys of the accounts whose spent records are tracked.
+pub const WALLET_PRIVATE_KEYS_ENV: &str = "SNARKOS_WALLET_PRIVATE_KEYS";
+
+/// Returns the accounts tracked by the node wallet.
+///
+/// The configured accounts must be view keys, as private keys given on the command line leak through
+/// the process list and the shell history. The spent records of an account are only tracked if its
+/// private key is given separately, read from `WALLET_PRIVATE_KEYS_ENV` by the node.
+pub fn wallet_accounts(
+    system_parameters: &SystemParameters<Components>,
+    view_keys: &[String],
+    private_keys: &[String],
+) -> Result<Vec<WalletAccount>, CliError> {
+    let mut accounts = vec![];
+
+    for view_key in view_keys {
+        if AccountPrivateKey::<Components>::from_str(view_key).is_ok() {
+            return Err(CliError::Message(format!(
+                "The wallet accounts must be view keys. Set private keys in the {} environment variable instead",
+                WALLET_PRIVATE_KEYS_ENV
+            )));
+        }
+
+        accounts.push(WalletAccount::new(system_parameters, view_key)?);
+    }
+
+    for private_key in private_keys {
+        AccountPrivateKey::<Components>::from_str(private_key)
+            .map_err(|_| CliError::Message(format!("{} must only contain private keys", WALLET_PRIVATE_KEYS_ENV)))?;
+
+        accounts.push(WalletAccount::new(system_parameters, private_key)?);
+    }
+
+    Ok(accounts)
+}
+
+/// Parameters for spawning a wallet that tracks the records of its accounts in the canon chain.
+pub struct WalletInstance {
+    wallet: Arc<Wallet>,
+    storage: Arc<MerkleTreeLedger>,
+}
+
+impl WalletInstance {
+    /// Creates a new WalletInstance for spawning a wallet.
+    pub fn new(wallet: Wallet, storage: Arc<MerkleTreeLedger>) -> Self {
+        Self {
+            wallet: Arc::new(wallet),
+            storage,
+        }
+    }
+
+    /// Spawns a new wallet that periodically scans the new canon blocks for records of its accounts.
+    /// The wallet stops once `shutdown` is set to `true` and the current scan has finished.
+    /// Returns a handle that resolves once the wallet has stopped.
+    pub fn spawn(self, mut shutdown: watch::Receiver<bool>) -> task::JoinHandle<()> {
+        task::spawn(async move {
+            info!("Initializing Aleo wallet");
+
+            loop {
+                if *shutdown.borrow() {
+                    info!("Shutting down wallet");
+                    break;
+                }
+
+                let wallet = self.wallet.clone();
+                let storage = self.storage.clone();
+
+                match task::spawn_blocking(move || wallet.sync(&storage)).await {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(num_scanned_blocks)) => debug!("Wallet scanned {} new block(s)", num_scanned_blocks),
+                    Ok(Err(error)) => warn!("Wallet failed to scan the chain ({})", error),
+                    Err(error) => error!("Wallet scan did not complete ({})", error),
+                }
+
+                tokio::select! {
+                    _ = delay_for(WALLET_SYNC_INTERVAL) => {},
+                    _ = shutdown.recv() => {},
+                }
+            }
+        })
+    }
+}
@@ -29,17 +29,26 @@ pub const COL_MEMO: u32 = 7; // Memo -> index
 pub const COL_DIGEST: u32 = 8; // Ledger digest -> index
 pub const COL_RECORDS: u32 = 9; // commitment -> record bytes
 pub const COL_CHILD_HASHES: u32 = 10; // block hash -> vector of potential child hashes
-pub const NUM_COLS: u32 = 11;
+pub const COL_WALLET_RECORDS: u32 = 11; // commitment -> owned wallet record
+pub const COL_WALLET_BLOCKS: u32 = 12; // block hash -> wallet records discovered in the block
+pub const COL_BLOCK_WORK: u32 = 13; // block hash -> cumulative work of the chain ending at the block
+pub const NUM_COLS: u32 = 14;
 
 pub const KEY_BEST_BLOCK_NUMBER: &str = "BEST_BLOCK_NUMBER";
 pub const KEY_MEMORY_POOL: &str = "MEMORY_POOL";
 pub const KEY_PEER_BOOK: &str = "PEER_BOOK";
+pub const KEY_BANNED_PEERS: &str = "BANNED_PEERS";
+pub const KEY_ANCHOR_PEERS: &str = "ANCHOR_PEERS";
+pub const KEY_BLOCK_WORK_BACKFILLED: &str = "BLOCK_WORK_BACKFILLED";
 
 pub const KEY_CURR_CM_INDEX: &str = "CURRENT_CM_INDEX";
 pub const KEY_CURR_SN_INDEX: &str = "CURRENT_SN_INDEX";
 pub const KEY_CURR_MEMO_INDEX: &str = "CURRENT_MEMO_INDEX";
 pub const KEY_CURR_DIGEST: &str = "CURRENT_DIGEST";
 
+pub const KEY_WALLET_ACCOUNTS: &str = "WALLET_ACCOUNTS";
+pub const KEY_WALLET_TIP: &str = "WALLET_TIP";
+
 /// Represents address of certain transaction within block
 #[derive(Debug, PartialEq, Clone)]
 pub struct TransactionLocation {
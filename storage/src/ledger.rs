@@ -88,6 +88,41 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         self.storage.write(DatabaseTransaction(vec![op]))
     }
 
+    /// Get the stored banned peers.
+    pub fn get_banned_peers(&self) -> Result<Vec<u8>, StorageError> {
+        Ok(self.get(COL_META, &KEY_BANNED_PEERS.as_bytes().to_vec())?)
+    }
+
+    /// Store the banned peers.
+    pub fn store_banned_peers(&self, bans_serialized: Vec<u8>) -> Result<(), StorageError> {
+        let op = Op::Insert {
+            col: COL_META,
+            key: KEY_BANNED_PEERS.as_bytes().to_vec(),
+            value: bans_serialized,
+        };
+        self.storage.write(DatabaseTransaction(vec![op]))
+    }
+
+    /// Get the stored anchor peers.
+    pub fn get_anchor_peers(&self) -> Result<Vec<u8>, StorageError> {
+        Ok(self.get(COL_META, &KEY_ANCHOR_PEERS.as_bytes().to_vec())?)
+    }
+
+    /// Store the anchor peers, the outbound peers reconnected on restart.
+    pub fn store_anchor_peers(&self, anchors_serialized: Vec<u8>) -> Result<(), StorageError> {
+        let op = Op::Insert {
+            col: COL_META,
+            key: KEY_ANCHOR_PEERS.as_bytes().to_vec(),
+            value: anchors_serialized,
+        };
+        self.storage.write(DatabaseTransaction(vec![op]))
+    }
+
+    /// Flush the in-memory storage state to disk.
+    pub fn flush(&self) -> Result<(), StorageError> {
+        self.storage.flush()
+    }
+
     /// Destroy the storage given a path.
     pub fn destroy_storage(path: PathBuf) -> Result<(), StorageError> {
         Storage::destroy_storage(path)
@@ -135,13 +170,20 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
 
                 let merkle_tree = MerkleTree::new(ledger_parameters.clone(), &commitments)?;
 
-                Ok(Self {
+                let ledger = Self {
                     latest_block_height: RwLock::new(bytes_to_u32(val)),
                     storage: Arc::new(storage),
                     cm_merkle_tree: RwLock::new(merkle_tree),
                     ledger_parameters,
                     _transaction: PhantomData,
-                })
+                };
+
+                // Ledgers created before the cumulative work of blocks was tracked are migrated once
+                if primary {
+                    ledger.backfill_cumulative_work()?;
+                }
+
+                Ok(ledger)
             }
             None => {
                 // Add genesis block to database
@@ -14,11 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Ledger, COL_BLOCK_HEADER};
+use crate::{DatabaseTransaction, Ledger, Op, COL_BLOCK_HEADER, COL_BLOCK_WORK, COL_META, KEY_BLOCK_WORK_BACKFILLED};
 use snarkos_errors::storage::StorageError;
 use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
 use snarkos_objects::{Block, BlockHeader, BlockHeaderHash};
-use snarkos_utilities::FromBytes;
+use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
 
 impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     /// Returns true if the block for the given block header hash exists.
@@ -41,6 +41,54 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         }
     }
 
+    /// Returns the cumulative work of the chain of stored blocks ending at the given block hash.
+    pub fn get_cumulative_work(&self, block_hash: &BlockHeaderHash) -> Result<u128, StorageError> {
+        match self.storage.get(COL_BLOCK_WORK, &block_hash.0)? {
+            Some(cumulative_work_bytes) => Ok(u128::read(&cumulative_work_bytes[..])?),
+            None => Err(StorageError::MissingValue(block_hash.to_string())),
+        }
+    }
+
+    /// Stores the cumulative work of every stored block, once for a ledger created before it was tracked.
+    /// The work is accumulated from the blocks without a stored parent along their children.
+    pub fn backfill_cumulative_work(&self) -> Result<(), StorageError> {
+        if self.storage.exists(COL_META, KEY_BLOCK_WORK_BACKFILLED.as_bytes()) {
+            return Ok(());
+        }
+
+        let mut cumulative_works = vec![];
+        for (block_hash_bytes, block_header_bytes) in self.storage.get_iter(COL_BLOCK_HEADER)? {
+            let block_header = BlockHeader::read(&block_header_bytes[..])?;
+
+            if !self.block_hash_exists(&block_header.previous_block_hash) {
+                cumulative_works.push((BlockHeaderHash::new(block_hash_bytes.to_vec()), block_header.work()));
+            }
+        }
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        while let Some((block_hash, cumulative_work)) = cumulative_works.pop() {
+            for child_hash in self.get_child_block_hashes(&block_hash)? {
+                let child_work = self.get_block_header(&child_hash)?.work();
+                cumulative_works.push((child_hash, cumulative_work + child_work));
+            }
+
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_WORK,
+                key: block_hash.0.to_vec(),
+                value: to_bytes![cumulative_work]?.to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_BLOCK_WORK_BACKFILLED.as_bytes().to_vec(),
+            value: vec![1],
+        });
+
+        self.storage.write(database_transaction)
+    }
+
     /// Returns true if the block corresponding to this block's previous_block_hash exists.
     pub fn previous_block_hash_exists(&self, block: &Block<T>) -> bool {
         self.block_hash_exists(&block.header.previous_block_hash)
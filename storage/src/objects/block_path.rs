@@ -38,6 +38,9 @@ pub struct SideChainPath {
 
     /// Path of block hashes from the shared block to the latest diverging block (oldest first).
     pub path: Vec<BlockHeaderHash>,
+
+    /// Cumulative work of the side chain, from the genesis block to the latest diverging block.
+    pub cumulative_work: u128,
 }
 
 impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
@@ -56,6 +59,7 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         }
 
         let mut side_chain_path = vec![];
+        let mut side_chain_work = block_header.work();
         let mut parent_hash = block_header.previous_block_hash.clone();
 
         // Find the sidechain path (with a maximum size of OLDEST_FORK_THRESHOLD)
@@ -66,20 +70,24 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
                 Ok(block_num) => {
                     // Add the children from the latest block
 
-                    let longest_path = self.longest_child_path(block_hash)?;
+                    let (children_work, heaviest_path) = self.heaviest_child_path(block_hash)?;
 
-                    side_chain_path.extend(longest_path.1);
+                    side_chain_path.extend(heaviest_path);
 
                     return Ok(BlockPath::SideChain(SideChainPath {
                         shared_block_number: *block_num,
                         new_block_number: block_num + side_chain_path.len() as u32,
                         path: side_chain_path,
+                        cumulative_work: self.get_cumulative_work(&parent_hash)? + side_chain_work + children_work,
                     }));
                 }
                 // Add to the side_chain_path
                 Err(_) => {
+                    let parent_header = self.get_block_header(&parent_hash)?;
+
                     side_chain_path.insert(0, parent_hash.clone());
-                    parent_hash = self.get_block_header(&parent_hash)?.previous_block_hash;
+                    side_chain_work += parent_header.work();
+                    parent_hash = parent_header.previous_block_hash;
                 }
             }
         }
@@ -89,31 +97,38 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         )))
     }
 
-    /// Returns the path length and the longest path of children from the given block header
-    pub fn longest_child_path(
+    /// Returns the work and the heaviest path of children from the given block header.
+    /// The work of the given block itself is not included, and ties go to the child that was stored first.
+    pub fn heaviest_child_path(
         &self,
         block_hash: BlockHeaderHash,
-    ) -> Result<(usize, Vec<BlockHeaderHash>), StorageError> {
-        let children = self.get_child_block_hashes(&block_hash)?;
+    ) -> Result<(u128, Vec<BlockHeaderHash>), StorageError> {
+        let mut heaviest_child_path: Option<(u128, Vec<BlockHeaderHash>)> = None;
 
-        let mut final_path = vec![block_hash];
+        for child in self.get_child_block_hashes(&block_hash)? {
+            let child_work = self.get_block_header(&child)?.work();
+            let (descendants_work, path) = self.heaviest_child_path(child)?;
+            let work = child_work + descendants_work;
+
+            let is_heaviest = match &heaviest_child_path {
+                Some((heaviest_work, _)) => work > *heaviest_work,
+                None => true,
+            };
 
-        if children.len() == 0 {
-            Ok((1, final_path))
-        } else {
-            let mut paths = vec![];
-            for child in children {
-                paths.push(self.longest_child_path(child)?);
+            if is_heaviest {
+                heaviest_child_path = Some((work, path));
             }
+        }
+
+        let mut final_path = vec![block_hash];
 
-            match paths.iter().max_by_key(|x| x.0) {
-                Some((longest_child_path_length, longest_child_path)) => {
-                    final_path.extend(longest_child_path.clone());
+        match heaviest_child_path {
+            Some((heaviest_work, heaviest_path)) => {
+                final_path.extend(heaviest_path);
 
-                    Ok((*longest_child_path_length + 1, final_path))
-                }
-                None => Ok((1, final_path)),
+                Ok((heaviest_work, final_path))
             }
+            None => Ok((0, final_path)),
         }
     }
 }
@@ -157,6 +157,26 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
             value: to_bytes![block.transactions]?.to_vec(),
         });
 
+        let parent_cumulative_work = match self.block_hash_exists(&block.header.previous_block_hash) {
+            true => self.get_cumulative_work(&block.header.previous_block_hash)?,
+            false => 0,
+        };
+
+        // Update the cumulative work of the stored children that were received before this block
+        let mut cumulative_works = vec![(block.header.get_hash(), parent_cumulative_work + block.header.work())];
+        while let Some((block_hash, cumulative_work)) = cumulative_works.pop() {
+            for child_hash in self.get_child_block_hashes(&block_hash)? {
+                let child_work = self.get_block_header(&child_hash)?.work();
+                cumulative_works.push((child_hash, cumulative_work + child_work));
+            }
+
+            database_transaction.push(Op::Insert {
+                col: COL_BLOCK_WORK,
+                key: block_hash.0.to_vec(),
+                value: to_bytes![cumulative_work]?.to_vec(),
+            });
+        }
+
         self.storage.write(database_transaction)?;
 
         Ok(())
@@ -290,7 +310,7 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
         Ok(())
     }
 
-    /// Insert a block into the storage and commit as part of the longest chain.
+    /// Insert a block into the storage and commit as part of the canon chain.
     pub fn insert_and_commit(&self, block: &Block<T>) -> Result<(), StorageError> {
         let block_hash = block.header.get_hash();
 
@@ -317,11 +337,9 @@ impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
     pub fn revert_for_fork(&self, side_chain_path: &SideChainPath) -> Result<(), StorageError> {
         let latest_block_height = self.get_latest_block_height();
 
-        if side_chain_path.new_block_number > latest_block_height {
-            // Decommit all blocks on canon chain up to the shared block number with the side chain.
-            for _ in (side_chain_path.shared_block_number)..latest_block_height {
-                self.decommit_latest_block()?;
-            }
+        // Decommit all blocks on canon chain up to the shared block number with the side chain.
+        for _ in (side_chain_path.shared_block_number)..latest_block_height {
+            self.decommit_latest_block()?;
         }
 
         Ok(())
@@ -41,3 +41,6 @@ pub use records::*;
 
 pub mod transaction;
 pub use transaction::*;
+
+pub mod wallet;
+pub use wallet::*;
@@ -0,0 +1,203 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::*;
+use snarkos_errors::storage::StorageError;
+use snarkos_models::{algorithms::LoadableMerkleParameters, objects::Transaction};
+use snarkos_objects::{BlockHeader, BlockHeaderHash};
+
+use serde::{Deserialize, Serialize};
+
+/// A record owned by one of the accounts tracked by the node wallet.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletRecord {
+    /// The serialized record
+    pub record: Vec<u8>,
+    /// The serialized serial number of the record, if the owner's private key is known
+    pub serial_number: Option<Vec<u8>>,
+    /// True if the serial number of the record exists in the canon chain
+    pub is_spent: bool,
+}
+
+/// The wallet records discovered in a scanned block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletBlock {
+    /// The hash of the parent block
+    pub previous_block_hash: BlockHeaderHash,
+    /// The commitments of the wallet records created in the block
+    pub commitments: Vec<Vec<u8>>,
+}
+
+impl<T: Transaction, P: LoadableMerkleParameters> Ledger<T, P> {
+    /// Get the serialized accounts that the wallet state was built for.
+    pub fn get_wallet_accounts(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        self.storage.get(COL_META, KEY_WALLET_ACCOUNTS.as_bytes())
+    }
+
+    /// Get the height and hash of the last block scanned by the wallet.
+    pub fn get_wallet_tip(&self) -> Result<Option<(u32, BlockHeaderHash)>, StorageError> {
+        match self.storage.get(COL_META, KEY_WALLET_TIP.as_bytes())? {
+            Some(wallet_tip_bytes) => Ok(Some(bincode::deserialize(&wallet_tip_bytes[..])?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a wallet record given the record commitment.
+    pub fn get_wallet_record(&self, commitment: &[u8]) -> Result<Option<WalletRecord>, StorageError> {
+        match self.storage.get(COL_WALLET_RECORDS, commitment)? {
+            Some(wallet_record_bytes) => Ok(Some(bincode::deserialize(&wallet_record_bytes[..])?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get all wallet records with their record commitments.
+    pub fn get_wallet_records(&self) -> Result<Vec<(Vec<u8>, WalletRecord)>, StorageError> {
+        let mut wallet_records = vec![];
+
+        for (commitment, wallet_record_bytes) in self.storage.get_iter(COL_WALLET_RECORDS)? {
+            wallet_records.push((commitment.to_vec(), bincode::deserialize(&wallet_record_bytes[..])?));
+        }
+
+        Ok(wallet_records)
+    }
+
+    /// Store the wallet records discovered in a block and advance the wallet tip to the block.
+    pub fn insert_wallet_block(
+        &self,
+        block_height: u32,
+        block_header: &BlockHeader,
+        wallet_records: Vec<(Vec<u8>, WalletRecord)>,
+    ) -> Result<(), StorageError> {
+        let block_hash = block_header.get_hash();
+
+        let mut database_transaction = DatabaseTransaction::new();
+        let mut commitments = vec![];
+
+        for (commitment, wallet_record) in wallet_records {
+            database_transaction.push(Op::Insert {
+                col: COL_WALLET_RECORDS,
+                key: commitment.clone(),
+                value: bincode::serialize(&wallet_record)?,
+            });
+            commitments.push(commitment);
+        }
+
+        let wallet_block = WalletBlock {
+            previous_block_hash: block_header.previous_block_hash.clone(),
+            commitments,
+        };
+
+        database_transaction.push(Op::Insert {
+            col: COL_WALLET_BLOCKS,
+            key: block_hash.0.to_vec(),
+            value: bincode::serialize(&wallet_block)?,
+        });
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_WALLET_TIP.as_bytes().to_vec(),
+            value: bincode::serialize(&(block_height, block_hash))?,
+        });
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Remove the wallet records discovered in the wallet tip and move the wallet tip to its parent.
+    pub fn remove_wallet_tip(&self) -> Result<(), StorageError> {
+        let (block_height, block_hash) = match self.get_wallet_tip()? {
+            Some(wallet_tip) => wallet_tip,
+            None => return Ok(()),
+        };
+
+        let wallet_block: WalletBlock = match self.storage.get(COL_WALLET_BLOCKS, &block_hash.0)? {
+            Some(wallet_block_bytes) => bincode::deserialize(&wallet_block_bytes[..])?,
+            None => return Err(StorageError::MissingValue(block_hash.to_string())),
+        };
+
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for commitment in wallet_block.commitments {
+            database_transaction.push(Op::Delete {
+                col: COL_WALLET_RECORDS,
+                key: commitment,
+            });
+        }
+
+        database_transaction.push(Op::Delete {
+            col: COL_WALLET_BLOCKS,
+            key: block_hash.0.to_vec(),
+        });
+
+        match block_height {
+            0 => database_transaction.push(Op::Delete {
+                col: COL_META,
+                key: KEY_WALLET_TIP.as_bytes().to_vec(),
+            }),
+            _ => database_transaction.push(Op::Insert {
+                col: COL_META,
+                key: KEY_WALLET_TIP.as_bytes().to_vec(),
+                value: bincode::serialize(&(block_height - 1, wallet_block.previous_block_hash))?,
+            }),
+        }
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Update existing wallet records.
+    pub fn update_wallet_records(&self, wallet_records: Vec<(Vec<u8>, WalletRecord)>) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for (commitment, wallet_record) in wallet_records {
+            database_transaction.push(Op::Insert {
+                col: COL_WALLET_RECORDS,
+                key: commitment,
+                value: bincode::serialize(&wallet_record)?,
+            });
+        }
+
+        self.storage.write(database_transaction)
+    }
+
+    /// Remove the wallet state and start a new one for the given serialized accounts.
+    pub fn reset_wallet(&self, accounts: Vec<u8>) -> Result<(), StorageError> {
+        let mut database_transaction = DatabaseTransaction::new();
+
+        for (commitment, _) in self.storage.get_iter(COL_WALLET_RECORDS)? {
+            database_transaction.push(Op::Delete {
+                col: COL_WALLET_RECORDS,
+                key: commitment.to_vec(),
+            });
+        }
+
+        for (block_hash, _) in self.storage.get_iter(COL_WALLET_BLOCKS)? {
+            database_transaction.push(Op::Delete {
+                col: COL_WALLET_BLOCKS,
+                key: block_hash.to_vec(),
+            });
+        }
+
+        database_transaction.push(Op::Delete {
+            col: COL_META,
+            key: KEY_WALLET_TIP.as_bytes().to_vec(),
+        });
+        database_transaction.push(Op::Insert {
+            col: COL_META,
+            key: KEY_WALLET_ACCOUNTS.as_bytes().to_vec(),
+            value: accounts,
+        });
+
+        self.storage.write(database_transaction)
+    }
+}
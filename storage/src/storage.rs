@@ -131,6 +131,18 @@ impl Storage {
         Ok(())
     }
 
+    /// Returns `Ok(())` after flushing the memtables of every column to disk.
+    /// If RocksDB fails to flush, returns [StorageError](snarkos_errors::storage::StorageError).
+    pub fn flush(&self) -> Result<(), StorageError> {
+        for cf_name in &self.cf_names {
+            if let Some(cf) = self.db.cf_handle(cf_name) {
+                self.db.flush_cf(cf)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns true if a value exists for a key and col pair.
     pub fn exists(&self, col: u32, key: &[u8]) -> bool {
         match self.db.get_cf(self.get_cf_ref(col), key) {
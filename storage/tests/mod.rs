@@ -24,6 +24,7 @@ mod test_storage {
         PedersenMerkleRootHash,
         ProofOfSuccinctWork,
     };
+    use snarkos_storage::COL_BLOCK_WORK;
     use snarkos_testing::storage::*;
 
     use std::sync::Arc;
@@ -69,6 +70,48 @@ mod test_storage {
         kill_storage_sync(blockchain);
     }
 
+    #[test]
+    pub fn test_backfill_cumulative_work() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+
+        let genesis_header = blockchain.get_latest_block().unwrap().header;
+        let block = Block {
+            header: BlockHeader {
+                difficulty_target: 100,
+                nonce: 99,
+                merkle_root_hash: MerkleRootHash([0; 32]),
+                previous_block_hash: genesis_header.get_hash(),
+                time: 123,
+                proof: ProofOfSuccinctWork::default(),
+                pedersen_merkle_root_hash: PedersenMerkleRootHash([0; 32]),
+            },
+            transactions: DPCTransactions::new(),
+        };
+
+        blockchain.insert_and_commit(&block).unwrap();
+
+        // Drop the cumulative work, as in a ledger created before it was tracked
+        let block_work = blockchain
+            .storage
+            .db
+            .cf_handle(&blockchain.storage.cf_names[COL_BLOCK_WORK as usize])
+            .unwrap();
+        for block_hash in &[genesis_header.get_hash(), block.header.get_hash()] {
+            blockchain.storage.db.delete_cf(block_work, &block_hash.0).unwrap();
+        }
+
+        assert!(blockchain.get_cumulative_work(&block.header.get_hash()).is_err());
+
+        blockchain.backfill_cumulative_work().unwrap();
+
+        assert_eq!(
+            genesis_header.work() + block.header.work(),
+            blockchain.get_cumulative_work(&block.header.get_hash()).unwrap()
+        );
+
+        kill_storage_sync(blockchain);
+    }
+
     #[test]
     pub fn test_storage() {
         let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
@@ -110,6 +153,30 @@ mod test_storage {
         kill_storage_sync(blockchain);
     }
 
+    #[test]
+    pub fn test_storage_banned_peers() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+        let bans_serialized = vec![0u8];
+
+        assert!(blockchain.store_banned_peers(bans_serialized.clone()).is_ok());
+        assert!(blockchain.get_banned_peers().is_ok());
+        assert_eq!(bans_serialized, blockchain.get_banned_peers().unwrap());
+
+        kill_storage_sync(blockchain);
+    }
+
+    #[test]
+    pub fn test_storage_anchor_peers() {
+        let (blockchain, _): (Arc<Store>, _) = open_test_blockchain();
+        let anchors_serialized = vec![0u8];
+
+        assert!(blockchain.store_anchor_peers(anchors_serialized.clone()).is_ok());
+        assert!(blockchain.get_anchor_peers().is_ok());
+        assert_eq!(anchors_serialized, blockchain.get_anchor_peers().unwrap());
+
+        kill_storage_sync(blockchain);
+    }
+
     #[test]
     pub fn test_destroy_storage() {
         let mut path = std::env::temp_dir();
@@ -18,11 +18,12 @@ use snarkos_consensus::{ConsensusParameters, MemoryPool, MerkleTreeLedger, Miner
 use snarkos_dpc::base_dpc::{instantiated::*, record::DPCRecord, record_payload::RecordPayload};
 use snarkos_errors::consensus::ConsensusError;
 use snarkos_models::dpc::{DPCScheme, Program, Record};
-use snarkos_objects::{dpc::DPCTransactions, Account, AccountAddress, Block};
+use snarkos_objects::{dpc::DPCTransactions, Account, AccountAddress, Block, BlockHeader};
+use snarkos_posw::{txids_to_roots, PoswMarlin};
 use snarkos_testing::consensus::*;
 use snarkos_utilities::bytes::ToBytes;
 
-use rand::Rng;
+use rand::{thread_rng, Rng};
 use std::{fs::File, path::PathBuf};
 
 fn setup_test_data() -> Result<TestData, ConsensusError> {
@@ -64,6 +65,16 @@ fn setup_test_data() -> Result<TestData, ConsensusError> {
     )?;
     let alternative_block_2_header = miner.find_block(&block_2.transactions, &alternative_block_1_header)?;
 
+    // Find a block 1 at the target block time, which keeps the harder difficulty target of genesis
+
+    let genesis_header = ledger.get_block_header(&block_1.header.previous_block_hash)?;
+    let harder_block_1_header = mine_header(
+        &consensus,
+        &block_1.transactions,
+        &genesis_header,
+        genesis_header.time + consensus.target_block_time,
+    )?;
+
     let test_data = TestData {
         block_1,
         block_2,
@@ -71,6 +82,7 @@ fn setup_test_data() -> Result<TestData, ConsensusError> {
         records_2: coinbase_records_2,
         alternative_block_1_header,
         alternative_block_2_header,
+        harder_block_1_header,
     };
 
     Ok(test_data)
@@ -110,6 +122,32 @@ fn mine_block(
     Ok((block, coinbase_records))
 }
 
+/// Mines a block header for the given transactions with a fixed timestamp.
+fn mine_header(
+    consensus: &ConsensusParameters,
+    transactions: &DPCTransactions<Tx>,
+    parent_header: &BlockHeader,
+    time: i64,
+) -> Result<BlockHeader, ConsensusError> {
+    let txids = transactions.to_transaction_ids()?;
+    let (merkle_root_hash, pedersen_merkle_root_hash, subroots) = txids_to_roots(&txids);
+
+    let difficulty_target = consensus.get_block_difficulty(parent_header, time);
+
+    let (nonce, proof) =
+        PoswMarlin::load()?.mine(&subroots, difficulty_target, &mut thread_rng(), consensus.max_nonce)?;
+
+    Ok(BlockHeader {
+        previous_block_hash: parent_header.get_hash(),
+        merkle_root_hash,
+        pedersen_merkle_root_hash,
+        time,
+        difficulty_target,
+        nonce,
+        proof: proof.into(),
+    })
+}
+
 /// Spends some value from inputs owned by the sender, to the receiver,
 /// and pays back whatever we are left with.
 fn send<R: Rng>(
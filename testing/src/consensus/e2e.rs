@@ -59,6 +59,16 @@ pub static ALTERNATIVE_BLOCK_2: Lazy<Vec<u8>> = Lazy::new(|| {
     to_bytes![alternative_block_2].unwrap()
 });
 
+// Block 1 mined on top of genesis at the target block time, so it carries more work than `BLOCK_1`
+pub static HARDER_BLOCK_1: Lazy<Vec<u8>> = Lazy::new(|| {
+    let harder_block_1 = Block {
+        header: DATA.harder_block_1_header.clone(),
+        transactions: DATA.block_1.transactions.clone(),
+    };
+
+    to_bytes![harder_block_1].unwrap()
+});
+
 pub fn genesis() -> Block<Tx> {
     let genesis_block: Block<Tx> = FromBytes::read(GenesisBlock::load_bytes().as_slice()).unwrap();
 
@@ -72,6 +82,7 @@ pub struct TestData {
     pub records_2: Vec<DPCRecord<Components>>,
     pub alternative_block_1_header: BlockHeader,
     pub alternative_block_2_header: BlockHeader,
+    pub harder_block_1_header: BlockHeader,
 }
 
 impl ToBytes for TestData {
@@ -89,6 +100,7 @@ impl ToBytes for TestData {
 
         self.alternative_block_1_header.write(&mut writer)?;
         self.alternative_block_2_header.write(&mut writer)?;
+        self.harder_block_1_header.write(&mut writer)?;
 
         Ok(())
     }
@@ -112,6 +124,7 @@ impl FromBytes for TestData {
 
         let alternative_block_1_header: BlockHeader = FromBytes::read(&mut reader)?;
         let alternative_block_2_header: BlockHeader = FromBytes::read(&mut reader)?;
+        let harder_block_1_header: BlockHeader = FromBytes::read(&mut reader)?;
 
         Ok(Self {
             block_1,
@@ -120,6 +133,7 @@ impl FromBytes for TestData {
             records_2,
             alternative_block_1_header,
             alternative_block_2_header,
+            harder_block_1_header,
         })
     }
 }
@@ -0,0 +1,162 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_errors::objects::TransactionError;
+use snarkos_models::objects::Transaction;
+use snarkos_utilities::{to_bytes, FromBytes, ToBytes};
+
+use std::io::{Read, Result as IoResult, Write};
+
+/// A transaction without a proof, spending and creating the given records.
+/// Used to fill memory pools with many distinct or conflicting transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockTx {
+    pub id: [u8; 32],
+    pub serial_numbers: Vec<[u8; 32]>,
+    pub commitments: Vec<[u8; 32]>,
+    pub memo: [u8; 32],
+    pub value_balance: i64,
+}
+
+impl MockTx {
+    /// Returns the `index`th transaction, spending and creating two records and paying the given fee.
+    pub fn new(index: u64, fee: i64) -> Self {
+        let bytes = |tag: u8| {
+            let mut bytes = [0u8; 32];
+            bytes[..8].copy_from_slice(&index.to_le_bytes());
+            bytes[31] = tag;
+            bytes
+        };
+
+        Self {
+            id: bytes(0),
+            serial_numbers: vec![bytes(1), bytes(2)],
+            commitments: vec![bytes(3), bytes(4)],
+            memo: bytes(5),
+            value_balance: fee,
+        }
+    }
+}
+
+impl Transaction for MockTx {
+    type Commitment = [u8; 32];
+    type Digest = [u8; 32];
+    type EncryptedRecord = [u8; 32];
+    type InnerSNARKID = [u8; 32];
+    type LocalDataRoot = [u8; 32];
+    type Memorandum = [u8; 32];
+    type ProgramCommitment = [u8; 32];
+    type SerialNumber = [u8; 32];
+    type ValueBalance = i64;
+
+    fn transaction_id(&self) -> Result<[u8; 32], TransactionError> {
+        Ok(self.id)
+    }
+
+    fn network_id(&self) -> u8 {
+        0
+    }
+
+    fn ledger_digest(&self) -> &Self::Digest {
+        &[0u8; 32]
+    }
+
+    fn inner_snark_id(&self) -> &Self::InnerSNARKID {
+        &[0u8; 32]
+    }
+
+    fn old_serial_numbers(&self) -> &[Self::SerialNumber] {
+        &self.serial_numbers
+    }
+
+    fn new_commitments(&self) -> &[Self::Commitment] {
+        &self.commitments
+    }
+
+    fn program_commitment(&self) -> &Self::ProgramCommitment {
+        &[0u8; 32]
+    }
+
+    fn local_data_root(&self) -> &Self::LocalDataRoot {
+        &[0u8; 32]
+    }
+
+    fn value_balance(&self) -> i64 {
+        self.value_balance
+    }
+
+    fn memorandum(&self) -> &Self::Memorandum {
+        &self.memo
+    }
+
+    fn encrypted_records(&self) -> &[Self::EncryptedRecord] {
+        &[]
+    }
+
+    fn size(&self) -> usize {
+        to_bytes![self].map(|bytes| bytes.len()).unwrap_or(0)
+    }
+}
+
+impl ToBytes for MockTx {
+    #[inline]
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.id.write(&mut writer)?;
+
+        (self.serial_numbers.len() as u32).write(&mut writer)?;
+        for sn in &self.serial_numbers {
+            sn.write(&mut writer)?;
+        }
+
+        (self.commitments.len() as u32).write(&mut writer)?;
+        for cm in &self.commitments {
+            cm.write(&mut writer)?;
+        }
+
+        self.memo.write(&mut writer)?;
+        self.value_balance.write(&mut writer)
+    }
+}
+
+impl FromBytes for MockTx {
+    #[inline]
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let id: [u8; 32] = FromBytes::read(&mut reader)?;
+
+        let num_serial_numbers: u32 = FromBytes::read(&mut reader)?;
+        let mut serial_numbers = vec![];
+        for _ in 0..num_serial_numbers {
+            serial_numbers.push(FromBytes::read(&mut reader)?);
+        }
+
+        let num_commitments: u32 = FromBytes::read(&mut reader)?;
+        let mut commitments = vec![];
+        for _ in 0..num_commitments {
+            commitments.push(FromBytes::read(&mut reader)?);
+        }
+
+        let memo: [u8; 32] = FromBytes::read(&mut reader)?;
+        let value_balance: i64 = FromBytes::read(&mut reader)?;
+
+        Ok(Self {
+            id,
+            serial_numbers,
+            commitments,
+            memo,
+            value_balance,
+        })
+    }
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use snarkos_consensus::ConsensusParameters;
+use snarkos_consensus::{ChainNotifier, ConsensusParameters};
 use snarkos_dpc::instantiated::Components;
 use snarkos_errors::objects::TransactionError;
 use snarkos_models::{algorithms::CRH, dpc::DPCComponents, objects::Transaction, parameters::Parameters};
@@ -32,6 +32,9 @@ pub use e2e::*;
 mod fixture;
 pub use fixture::*;
 
+mod mock_transaction;
+pub use mock_transaction::*;
+
 pub static TEST_CONSENSUS: Lazy<ConsensusParameters> = Lazy::new(|| {
     let inner_snark_verification_key_crh_parameters: <<Components as DPCComponents>::InnerSNARKVerificationKeyCRH as CRH>::Parameters = FromBytes::read(InnerSNARKVKCRHParameters::load_bytes().unwrap().as_slice()).unwrap();
 
@@ -49,9 +52,11 @@ pub static TEST_CONSENSUS: Lazy<ConsensusParameters> = Lazy::new(|| {
         max_block_size: 1_000_000usize,
         max_nonce: u32::max_value(),
         target_block_time: 2i64, //unix seconds
+        fixed_difficulty_target: None,
         network: Network::Mainnet,
         verifier: PoswMarlin::verify_only().unwrap(),
         authorized_inner_snark_ids: vec![inner_snark_id],
+        notifier: ChainNotifier::default(),
     }
 });
 
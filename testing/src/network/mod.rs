@@ -18,7 +18,7 @@ use crate::consensus::*;
 use snarkos_consensus::{MemoryPool, MerkleTreeLedger};
 use snarkos_dpc::base_dpc::{instantiated::Components, parameters::PublicParameters};
 use snarkos_network::{
-    external::{Channel, SyncHandler},
+    external::{Channel, NodeIdentity, Services, SyncHandler},
     internal::context::Context,
     Server,
 };
@@ -27,6 +27,9 @@ use rand::Rng;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::{net::TcpListener, sync::Mutex};
 
+mod simulation;
+pub use simulation::*;
+
 pub const LOCALHOST: &'static str = "0.0.0.0:";
 pub const CONNECTION_FREQUENCY_LONG: u64 = 100000; // 100 seconds
 pub const CONNECTION_FREQUENCY_SHORT: u64 = 100; // .1 seconds
@@ -44,6 +47,15 @@ pub async fn sleep(time: u64) {
     tokio::time::delay_for(std::time::Duration::from_millis(time)).await;
 }
 
+/// Returns the identity of a full node running on the test network with the given storage
+pub fn test_node_identity(storage: &MerkleTreeLedger) -> NodeIdentity {
+    NodeIdentity::new(
+        TEST_CONSENSUS.network.id(),
+        storage.get_block_hash(0).unwrap(),
+        Services::FULL_NODE,
+    )
+}
+
 /// Returns a server struct with given argumnets
 pub fn initialize_test_server(
     server_address: SocketAddr,
@@ -68,6 +80,7 @@ pub fn initialize_test_server(
         sync_handler_lock,
         connection_frequency,
     )
+    .unwrap()
 }
 
 /// Starts a server on a new thread. Takes full ownership of server.
@@ -80,7 +93,7 @@ pub async fn connect_channel(listener: &mut TcpListener, address: SocketAddr) ->
     let channel = Channel::new_write_only(address).await.unwrap();
     let (reader, _socket) = listener.accept().await.unwrap();
 
-    channel.update_reader(Arc::new(Mutex::new(reader)))
+    channel.update_reader(Arc::new(Mutex::new(reader.into())))
 }
 
 /// Returns the next tcp channel connected to the listener
@@ -0,0 +1,335 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{consensus::*, dpc::load_verifying_parameters};
+use snarkos_consensus::{MemoryPool, MerkleTreeLedger};
+use snarkos_dpc::base_dpc::instantiated::Tx;
+use snarkos_network::{
+    external::{Channel, Encryption, MemoryListener, MemoryNetwork, Stream, SyncHandler, Transport},
+    internal::context::Context,
+    Server,
+};
+use snarkos_objects::{Block, BlockHeaderHash};
+
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::Mutex,
+    task,
+    time,
+};
+
+pub const SIMULATION_PORT: u16 = 4130;
+pub const SIMULATION_CONNECTION_FREQUENCY: u64 = 1000; // 1 second of simulated time
+pub const SIMULATION_STEP: Duration = Duration::from_millis(50);
+
+/// The number of times every task may run between two steps of the clock
+const SIMULATION_YIELDS: usize = 100;
+
+/// Returns a runtime running all simulated nodes on the current thread, with the time driver enabled
+pub fn simulation_runtime() -> Runtime {
+    Builder::new().basic_scheduler().enable_all().build().unwrap()
+}
+
+/// Advances the paused clock by one step, and lets the tasks handle the messages delivered in the meantime
+async fn advance_clock() {
+    time::advance(SIMULATION_STEP).await;
+
+    for _ in 0..SIMULATION_YIELDS {
+        task::yield_now().await;
+    }
+}
+
+/// Pauses the tokio clock and runs the future, advancing the clock step by step until the future completes.
+/// Must be called on a runtime returned by `simulation_runtime`.
+pub async fn run_simulated<F: Future>(future: F) -> F::Output {
+    time::pause();
+    tokio::pin!(future);
+
+    loop {
+        tokio::select! {
+            output = &mut future => return output,
+            _ = advance_clock() => {}
+        }
+    }
+}
+
+/// A peer on a memory network that doesn't run a server, so a test can check the messages sent to it by hand
+pub struct SimulatedPeer {
+    pub address: SocketAddr,
+    network: Arc<MemoryNetwork>,
+    listener: MemoryListener,
+}
+
+impl SimulatedPeer {
+    /// Returns a peer listening at the given address of the memory network
+    pub fn bind(network: Arc<MemoryNetwork>, address: SocketAddr) -> Self {
+        let listener = network.clone().bind(address).unwrap();
+
+        Self {
+            address,
+            network,
+            listener,
+        }
+    }
+
+    /// Returns a new channel from the node at the given address to this peer
+    pub async fn connect_from(&self, address: SocketAddr) -> Arc<Channel> {
+        let transport = Transport::Memory(self.network.clone(), address);
+
+        Arc::new(
+            Channel::connect(self.address, &transport, &Encryption::default())
+                .await
+                .unwrap(),
+        )
+    }
+
+    /// Returns the channel of the next connection accepted by this peer
+    pub async fn accept(&mut self) -> Channel {
+        let stream = self.listener.accept().await.unwrap();
+
+        Channel::accept(Stream::Memory(stream), &Encryption::default())
+            .await
+            .unwrap()
+    }
+}
+
+/// A node running in a simulation
+pub struct SimulatedNode {
+    pub address: SocketAddr,
+    pub context: Arc<Context>,
+    pub storage: Arc<MerkleTreeLedger>,
+    pub memory_pool_lock: Arc<Mutex<MemoryPool<Tx>>>,
+    pub sync_handler_lock: Arc<Mutex<SyncHandler>>,
+}
+
+impl SimulatedNode {
+    /// Returns the height of the canon chain of the node
+    pub fn height(&self) -> u32 {
+        self.storage.get_latest_block_height()
+    }
+
+    /// Returns the hash of the latest block of the canon chain of the node
+    pub fn latest_block_hash(&self) -> BlockHeaderHash {
+        self.storage.get_block_hash(self.height()).unwrap()
+    }
+
+    /// Adds a block to the canon chain of the node without announcing it to its peers
+    pub fn insert_block(&self, block: &Block<Tx>) {
+        self.storage.insert_and_commit(block).unwrap();
+    }
+}
+
+/// Runs several servers in one process, connected by a memory network.
+///
+/// The tokio clock is paused when the simulation starts, and is only advanced by the simulation itself.
+/// Timeouts, latencies and the connection handlers of all nodes fire at the same simulated time
+/// in every run, however long the nodes take to handle their messages.
+pub struct Simulation {
+    pub network: Arc<MemoryNetwork>,
+    pub nodes: Vec<SimulatedNode>,
+    servers: Vec<Server>,
+}
+
+impl Simulation {
+    /// Returns a simulation of nodes with fresh ledgers. All nodes connect to the first node when they start.
+    /// The seed chooses the messages dropped by the memory network.
+    pub fn new(num_nodes: usize, seed: u64) -> Self {
+        assert!(num_nodes > 0 && num_nodes < 255);
+
+        let network = Arc::new(MemoryNetwork::new(seed));
+        let mut nodes = Vec::with_capacity(num_nodes);
+        let mut servers = Vec::with_capacity(num_nodes);
+
+        for index in 0..num_nodes {
+            let address = Self::node_address(index);
+            let bootnodes = match index {
+                0 => vec![],
+                _ => vec![Self::node_address(0).to_string()],
+            };
+
+            let mut context = Context::new(address, 5, (num_nodes - 1) as u16, 10, false, bootnodes, false);
+            context.transport = Transport::Memory(network.clone(), address);
+            let context = Arc::new(context);
+
+            let storage = Arc::new(FIXTURE_VK.ledger());
+            let memory_pool_lock = Arc::new(Mutex::new(MemoryPool::new()));
+            let sync_handler_lock = Arc::new(Mutex::new(SyncHandler::new(Self::node_address(0))));
+
+            servers.push(Server::new(
+                context.clone(),
+                TEST_CONSENSUS.clone(),
+                storage.clone(),
+                load_verifying_parameters(),
+                memory_pool_lock.clone(),
+                sync_handler_lock.clone(),
+                SIMULATION_CONNECTION_FREQUENCY,
+            )
+            .unwrap());
+
+            nodes.push(SimulatedNode {
+                address,
+                context,
+                storage,
+                memory_pool_lock,
+                sync_handler_lock,
+            });
+        }
+
+        Self {
+            network,
+            nodes,
+            servers,
+        }
+    }
+
+    /// Returns the address of the node at the given index.
+    /// Every node has its own /16 subnet, so a node banning a peer doesn't ban the other nodes,
+    /// and the nodes can open outbound connections to each other.
+    pub fn node_address(index: usize) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, index as u8 + 1, 0, 1)), SIMULATION_PORT)
+    }
+
+    /// Pauses the tokio clock and starts the servers of all nodes.
+    /// Must be called on a runtime returned by `simulation_runtime`.
+    pub fn start(&mut self) {
+        time::pause();
+
+        for server in self.servers.drain(..) {
+            task::spawn(async move { server.listen().await.unwrap() });
+        }
+    }
+
+    /// Advances the clock by one step, and lets the nodes handle the messages delivered in the meantime
+    pub async fn step(&self) {
+        advance_clock().await;
+    }
+
+    /// Runs the simulation for the given simulated time
+    pub async fn run_for(&self, duration: Duration) {
+        let steps = duration.as_millis() / SIMULATION_STEP.as_millis();
+
+        for _ in 0..steps {
+            self.step().await;
+        }
+    }
+
+    /// Runs the simulation until the condition holds, or the given simulated time has passed.
+    /// Returns true if the condition holds.
+    pub async fn run_until<F: Fn(&Self) -> bool>(&self, condition: F, timeout: Duration) -> bool {
+        let mut elapsed = Duration::from_secs(0);
+
+        while !condition(self) {
+            if elapsed >= timeout {
+                return false;
+            }
+
+            self.step().await;
+            elapsed += SIMULATION_STEP;
+        }
+
+        true
+    }
+
+    /// Runs the simulation until every node is connected to all other nodes, or the given simulated time has passed.
+    /// Returns true if all nodes are connected.
+    pub async fn run_until_connected(&self, timeout: Duration) -> bool {
+        let mut elapsed = Duration::from_secs(0);
+
+        while !self.is_connected().await {
+            if elapsed >= timeout {
+                return false;
+            }
+
+            self.step().await;
+            elapsed += SIMULATION_STEP;
+        }
+
+        true
+    }
+
+    /// Runs the simulation until all nodes agree on their latest block, or the given simulated time has passed.
+    /// Returns true if the chains of all nodes converged.
+    pub async fn run_until_converged(&self, timeout: Duration) -> bool {
+        self.run_until(Self::has_converged, timeout).await
+    }
+
+    /// Splits the nodes at the given indexes into groups that can't reach each other
+    pub fn partition(&self, groups: &[&[usize]]) {
+        let groups: Vec<Vec<SocketAddr>> = groups
+            .iter()
+            .map(|group| group.iter().map(|index| self.nodes[*index].address).collect())
+            .collect();
+
+        self.network.partition(&groups);
+    }
+
+    /// Removes all partitions between the nodes
+    pub fn heal(&self) {
+        self.network.heal();
+    }
+
+    /// Requests a connection from one node to another, as a node operator would
+    pub async fn connect(&self, from: usize, to: usize) {
+        let address = self.nodes[to].address;
+
+        self.nodes[from]
+            .context
+            .peer_book
+            .write()
+            .await
+            .request_connection(address);
+    }
+
+    /// Returns true if every node is connected to all other nodes
+    pub async fn is_connected(&self) -> bool {
+        for node in &self.nodes {
+            if (node.context.peer_book.read().await.connected_total() as usize) < self.nodes.len() - 1 {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns true if all nodes have the same latest block
+    pub fn has_converged(&self) -> bool {
+        let latest_block_hash = self.nodes[0].latest_block_hash();
+
+        self.nodes
+            .iter()
+            .all(|node| node.latest_block_hash() == latest_block_hash)
+    }
+
+    /// Returns the heights of the canon chains of all nodes
+    pub fn heights(&self) -> Vec<u32> {
+        self.nodes.iter().map(SimulatedNode::height).collect()
+    }
+
+    /// Returns the storage paths of all nodes, to remove them once the runtime is dropped
+    pub fn storage_paths(&self) -> Vec<PathBuf> {
+        self.nodes
+            .iter()
+            .map(|node| node.storage.storage.db.path().to_owned())
+            .collect()
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod blocks {
+    use snarkos::blocks::{ExportBlocksCLI, ImportBlocksCLI};
+    use snarkos_consensus::{MemoryPool, MerkleTreeLedger};
+    use snarkos_dpc::base_dpc::instantiated::Tx;
+    use snarkos_objects::Block;
+    use snarkos_testing::{consensus::*, dpc::load_verifying_parameters, storage::*};
+    use snarkos_utilities::{
+        bytes::{FromBytes, ToBytes},
+        to_bytes,
+    };
+
+    use std::{fs::File, io::Write, path::PathBuf};
+
+    fn export_file_path() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("{}.blocks", random_storage_path()));
+        path
+    }
+
+    // Returns a ledger with blocks 1 and 2 on top of the genesis block.
+    fn source_ledger() -> MerkleTreeLedger {
+        let ledger = FIXTURE_VK.ledger();
+        let parameters = load_verifying_parameters();
+        let mut memory_pool = MemoryPool::new();
+
+        for block_bytes in [&BLOCK_1[..], &BLOCK_2[..]].iter() {
+            let block = Block::<Tx>::read(*block_bytes).unwrap();
+            TEST_CONSENSUS
+                .receive_block(&parameters, &ledger, &mut memory_pool, &block)
+                .unwrap();
+        }
+
+        ledger
+    }
+
+    // Export all the blocks of a ledger and import them into a ledger that only stores the genesis block.
+    fn round_trip(verify: bool) {
+        let source = source_ledger();
+        let destination = FIXTURE_VK.ledger();
+        let parameters = load_verifying_parameters();
+        let file = export_file_path();
+
+        let exported = ExportBlocksCLI::export_blocks(&source, &file, 0, None).unwrap();
+        assert_eq!(3, exported);
+
+        let consensus = match verify {
+            true => Some((&*TEST_CONSENSUS, &parameters)),
+            false => None,
+        };
+
+        // The genesis block is already stored, so only blocks 1 and 2 are imported.
+        let imported = ImportBlocksCLI::import_blocks(&destination, &file, 0, consensus).unwrap();
+        assert_eq!(2, imported);
+
+        assert_eq!(source.get_latest_block_height(), destination.get_latest_block_height());
+        assert_eq!(
+            source.get_latest_block().unwrap(),
+            destination.get_latest_block().unwrap()
+        );
+
+        std::fs::remove_file(file).unwrap();
+        kill_storage(source);
+        kill_storage(destination);
+    }
+
+    #[test]
+    fn export_import_trusted() {
+        round_trip(false);
+    }
+
+    #[test]
+    fn export_import_verified() {
+        round_trip(true);
+    }
+
+    #[test]
+    fn export_invalid_range() {
+        let ledger = FIXTURE_VK.ledger();
+        let file = export_file_path();
+
+        assert!(ExportBlocksCLI::export_blocks(&ledger, &file, 1, None).is_err());
+
+        kill_storage(ledger);
+    }
+
+    #[test]
+    fn import_overflowing_range() {
+        let ledger = FIXTURE_VK.ledger();
+        let file = export_file_path();
+
+        // A file claiming a second block above the maximum block height
+        let genesis_bytes = to_bytes![ledger.get_block_from_block_number(0).unwrap()].unwrap();
+        let mut writer = File::create(&file).unwrap();
+        u32::MAX.write(&mut writer).unwrap();
+        2u32.write(&mut writer).unwrap();
+        (genesis_bytes.len() as u32).write(&mut writer).unwrap();
+        writer.write_all(&genesis_bytes).unwrap();
+        drop(writer);
+
+        assert!(ImportBlocksCLI::import_blocks(&ledger, &file, 0, None).is_err());
+
+        std::fs::remove_file(file).unwrap();
+        kill_storage(ledger);
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod dev {
+    use snarkos::dev::{dev_ledger_marker, open_dev_ledger, DEV_DIFFICULTY_TARGET};
+    use snarkos_testing::{
+        consensus::{FIXTURE, FIXTURE_VK, TEST_CONSENSUS},
+        dpc::load_verifying_parameters,
+        storage::*,
+    };
+
+    fn dev_ledger_path() -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(random_storage_path());
+        path
+    }
+
+    // A development ledger without any account to fund is rejected instead of building an empty genesis block.
+    #[test]
+    fn open_dev_ledger_without_accounts() {
+        let parameters = load_verifying_parameters();
+
+        assert!(open_dev_ledger(dev_ledger_path(), &TEST_CONSENSUS, &parameters, &[]).is_err());
+    }
+
+    #[test]
+    fn open_dev_ledger_with_account() {
+        let account = FIXTURE.test_accounts[0].address.clone();
+
+        let ledger = open_dev_ledger(dev_ledger_path(), &TEST_CONSENSUS, &FIXTURE.parameters, &[account]).unwrap();
+
+        let genesis_block = ledger.get_latest_block().unwrap();
+
+        assert_eq!(0, ledger.get_latest_block_height());
+        assert_eq!(1, genesis_block.transactions.len());
+        assert_eq!(DEV_DIFFICULTY_TARGET, genesis_block.header.difficulty_target);
+
+        let marker_path = dev_ledger_marker(ledger.storage.db.path());
+        assert!(marker_path.exists());
+
+        kill_storage(ledger);
+        std::fs::remove_file(marker_path).unwrap();
+    }
+
+    // A ledger that was not created by a development node is never destroyed.
+    #[test]
+    fn open_dev_ledger_on_existing_ledger() {
+        let account = FIXTURE.test_accounts[0].address.clone();
+        let ledger = FIXTURE_VK.ledger();
+        let path = ledger.storage.db.path().to_owned();
+        let genesis_block = ledger.get_latest_block().unwrap();
+
+        assert!(open_dev_ledger(&path, &TEST_CONSENSUS, &FIXTURE.parameters, &[account]).is_err());
+
+        assert!(!dev_ledger_marker(&path).exists());
+        assert_eq!(genesis_block, ledger.get_latest_block().unwrap());
+
+        kill_storage(ledger);
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+mod wallet {
+    use snarkos::wallet::wallet_accounts;
+    use snarkos_objects::AccountViewKey;
+    use snarkos_testing::{consensus::FIXTURE_VK, dpc::load_verifying_parameters};
+
+    #[test]
+    fn accept_view_keys() {
+        let parameters = load_verifying_parameters();
+        let system_parameters = &parameters.system_parameters;
+        let account = &FIXTURE_VK.test_accounts[0];
+
+        let view_key = AccountViewKey::from_private_key(
+            &system_parameters.account_signature,
+            &system_parameters.account_commitment,
+            &account.private_key,
+        )
+        .unwrap();
+
+        let accounts = wallet_accounts(system_parameters, &[view_key.to_string()], &[]).unwrap();
+
+        assert_eq!(1, accounts.len());
+        assert_eq!(account.address, accounts[0].address);
+        assert!(accounts[0].private_key.is_none());
+
+        // The private key of an account is only accepted from the environment.
+        let accounts = wallet_accounts(system_parameters, &[], &[account.private_key.to_string()]).unwrap();
+
+        assert_eq!(account.address, accounts[0].address);
+        assert!(accounts[0].private_key.is_some());
+    }
+
+    #[test]
+    fn reject_private_keys() {
+        let parameters = load_verifying_parameters();
+        let private_key = FIXTURE_VK.test_accounts[0].private_key.to_string();
+
+        assert!(wallet_accounts(&parameters.system_parameters, &[private_key], &[]).is_err());
+    }
+}